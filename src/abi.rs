@@ -0,0 +1,360 @@
+//! Solidity ABI encoding and decoding, wired to [`call`], [`input`] and [`ret`].
+//!
+//! Values are described by [`Token`]s and decoded according to [`ParamType`]s. Tuples are
+//! encoded as sequences of tokens; fixed arrays are encoded the same way as tuples of their
+//! elements.
+//!
+//! ```ignore
+//! let balance = abi::call_function(&token, BALANCE_OF, &[Token::Address(owner)], &[ParamType::Uint])?;
+//! abi::ret_encoded(&[Token::Bool(true)]);
+//! ```
+//!
+//! [`call`]: ../fn.call.html
+//! [`input`]: ../fn.input.html
+//! [`ret`]: ../fn.ret.html
+//! [`Token`]: enum.Token.html
+//! [`ParamType`]: enum.ParamType.html
+
+use pwasm_std::{
+	Box, String, Vec,
+	types::{U256, Address}
+};
+
+use ext::{self, Error};
+#[cfg(any(feature = "keccak", feature = "tiny-keccak"))]
+use hash;
+
+/// Type of an ABI value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParamType {
+	/// `address`.
+	Address,
+	/// `uint<M>`, decoded to its 256-bit word.
+	Uint,
+	/// `bool`.
+	Bool,
+	/// `bytes<M>`, with `M` from 1 to 32.
+	FixedBytes(usize),
+	/// `bytes`.
+	Bytes,
+	/// `string`.
+	String,
+	/// `T[k]`.
+	FixedArray(Box<ParamType>, usize),
+	/// `T[]`.
+	Array(Box<ParamType>),
+}
+
+impl ParamType {
+	/// Whether values are encoded out of place, after the head of the enclosing sequence.
+	pub fn is_dynamic(&self) -> bool {
+		match *self {
+			ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+			ParamType::FixedArray(ref inner, len) => len > 0 && inner.is_dynamic(),
+			_ => false,
+		}
+	}
+
+	/// Size of the value in the head of the enclosing sequence.
+	fn head_len(&self) -> usize {
+		match *self {
+			ParamType::FixedArray(ref inner, len) if !self.is_dynamic() => len * inner.head_len(),
+			_ => 32,
+		}
+	}
+}
+
+/// ABI value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Token {
+	/// `address`.
+	Address(Address),
+	/// `uint<M>`.
+	Uint(U256),
+	/// `bool`.
+	Bool(bool),
+	/// `bytes<M>`, of at most 32 bytes; longer values are truncated when encoded.
+	FixedBytes(Vec<u8>),
+	/// `bytes`.
+	Bytes(Vec<u8>),
+	/// `string`.
+	String(String),
+	/// `T[k]`.
+	FixedArray(Vec<Token>),
+	/// `T[]`.
+	Array(Vec<Token>),
+}
+
+impl Token {
+	/// Whether the token is encoded out of place, after the head of the enclosing sequence.
+	pub fn is_dynamic(&self) -> bool {
+		match *self {
+			Token::Bytes(_) | Token::String(_) | Token::Array(_) => true,
+			Token::FixedArray(ref tokens) => tokens.iter().any(Token::is_dynamic),
+			_ => false,
+		}
+	}
+
+	/// Address held by the token.
+	pub fn to_address(&self) -> Option<Address> {
+		match *self {
+			Token::Address(address) => Some(address),
+			_ => None,
+		}
+	}
+
+	/// Integer held by the token.
+	pub fn to_uint(&self) -> Option<U256> {
+		match *self {
+			Token::Uint(value) => Some(value),
+			_ => None,
+		}
+	}
+
+	/// Boolean held by the token.
+	pub fn to_bool(&self) -> Option<bool> {
+		match *self {
+			Token::Bool(value) => Some(value),
+			_ => None,
+		}
+	}
+
+	/// Bytes held by a `bytes<M>` or `bytes` token.
+	pub fn into_bytes(self) -> Option<Vec<u8>> {
+		match self {
+			Token::FixedBytes(bytes) | Token::Bytes(bytes) => Some(bytes),
+			_ => None,
+		}
+	}
+
+	/// String held by the token.
+	pub fn into_string(self) -> Option<String> {
+		match self {
+			Token::String(string) => Some(string),
+			_ => None,
+		}
+	}
+
+	/// Elements of an array token.
+	pub fn into_array(self) -> Option<Vec<Token>> {
+		match self {
+			Token::FixedArray(tokens) | Token::Array(tokens) => Some(tokens),
+			_ => None,
+		}
+	}
+}
+
+fn word(value: U256) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	value.to_big_endian(&mut word);
+	word
+}
+
+fn push_padded(out: &mut Vec<u8>, data: &[u8]) {
+	out.extend_from_slice(data);
+	let padding = (32 - data.len() % 32) % 32;
+	for _ in 0..padding {
+		out.push(0);
+	}
+}
+
+/// Size of the token in the head of the enclosing sequence.
+fn head_len(token: &Token) -> usize {
+	match *token {
+		Token::FixedArray(ref tokens) if !token.is_dynamic() => tokens.iter().map(head_len).sum(),
+		_ => 32,
+	}
+}
+
+/// Encoding of a token in place, or in the tail for dynamic tokens.
+fn encode_token(token: &Token, out: &mut Vec<u8>) {
+	match *token {
+		Token::Address(ref address) => {
+			let mut word = [0u8; 32];
+			word[12..].copy_from_slice(&<[u8; 20]>::from(*address));
+			out.extend_from_slice(&word);
+		},
+		Token::Uint(value) => out.extend_from_slice(&word(value)),
+		Token::Bool(value) => out.extend_from_slice(&word(U256::from(value as u64))),
+		Token::FixedBytes(ref bytes) => push_padded(out, &bytes[..bytes.len().min(32)]),
+		Token::Bytes(ref bytes) => {
+			out.extend_from_slice(&word(U256::from(bytes.len() as u64)));
+			push_padded(out, bytes);
+		},
+		Token::String(ref string) => {
+			out.extend_from_slice(&word(U256::from(string.len() as u64)));
+			push_padded(out, string.as_bytes());
+		},
+		Token::FixedArray(ref tokens) => out.extend_from_slice(&encode(tokens)),
+		Token::Array(ref tokens) => {
+			out.extend_from_slice(&word(U256::from(tokens.len() as u64)));
+			out.extend_from_slice(&encode(tokens));
+		},
+	}
+}
+
+/// Encodes a sequence of tokens, as the arguments or return values of a function.
+pub fn encode(tokens: &[Token]) -> Vec<u8> {
+	let mut heads = Vec::new();
+	let mut tails = Vec::new();
+	let heads_len = tokens.iter().map(head_len).sum::<usize>();
+	for token in tokens {
+		if token.is_dynamic() {
+			heads.extend_from_slice(&word(U256::from((heads_len + tails.len()) as u64)));
+			encode_token(token, &mut tails);
+		} else {
+			encode_token(token, &mut heads);
+		}
+	}
+	heads.extend_from_slice(&tails);
+	heads
+}
+
+/// Encodes a call of the function `selector` with `args`.
+pub fn encode_call(selector: [u8; 4], args: &[Token]) -> Vec<u8> {
+	let mut input = Vec::new();
+	input.extend_from_slice(&selector);
+	input.extend_from_slice(&encode(args));
+	input
+}
+
+#[cfg(any(feature = "keccak", feature = "tiny-keccak"))]
+/// Selector of a function signature like `transfer(address,uint256)`.
+pub fn selector(signature: &str) -> [u8; 4] {
+	let hash = <[u8; 32]>::from(hash::keccak256(signature.as_bytes()));
+	[hash[0], hash[1], hash[2], hash[3]]
+}
+
+fn read_word(data: &[u8], offset: usize) -> Result<&[u8], Error> {
+	if offset > data.len() || data.len() - offset < 32 {
+		return Err(Error);
+	}
+	Ok(&data[offset..offset + 32])
+}
+
+fn read_usize(data: &[u8], offset: usize) -> Result<usize, Error> {
+	let word = read_word(data, offset)?;
+	if word[..24].iter().any(|b| *b != 0) {
+		return Err(Error);
+	}
+	let value = U256::from_big_endian(word).low_u64();
+	if value > usize::max_value() as u64 {
+		return Err(Error);
+	}
+	Ok(value as usize)
+}
+
+fn read_bytes(data: &[u8], offset: usize) -> Result<&[u8], Error> {
+	let len = read_usize(data, offset)?;
+	let start = offset + 32;
+	if data.len() - start < len {
+		return Err(Error);
+	}
+	Ok(&data[start..start + len])
+}
+
+/// Decodes a token whose encoding starts at `offset` of `data`.
+fn decode_token(param: &ParamType, data: &[u8], offset: usize) -> Result<Token, Error> {
+	match *param {
+		ParamType::Address => {
+			let word = read_word(data, offset)?;
+			if word[..12].iter().any(|b| *b != 0) {
+				return Err(Error);
+			}
+			let mut address = [0u8; 20];
+			address.copy_from_slice(&word[12..]);
+			Ok(Token::Address(Address::from(address)))
+		},
+		ParamType::Uint => Ok(Token::Uint(U256::from_big_endian(read_word(data, offset)?))),
+		ParamType::Bool => match read_usize(data, offset)? {
+			0 => Ok(Token::Bool(false)),
+			1 => Ok(Token::Bool(true)),
+			_ => Err(Error),
+		},
+		ParamType::FixedBytes(len) => {
+			if len == 0 || len > 32 {
+				return Err(Error);
+			}
+			Ok(Token::FixedBytes(read_word(data, offset)?[..len].to_vec()))
+		},
+		ParamType::Bytes => Ok(Token::Bytes(read_bytes(data, offset)?.to_vec())),
+		ParamType::String => {
+			let bytes = read_bytes(data, offset)?.to_vec();
+			String::from_utf8(bytes).map(Token::String).map_err(|_| Error)
+		},
+		ParamType::FixedArray(ref inner, len) => {
+			// every element takes at least a word, which bounds the allocation below
+			if offset > data.len() || len > (data.len() - offset) / 32 {
+				return Err(Error);
+			}
+			let params: Vec<ParamType> = (0..len).map(|_| (**inner).clone()).collect();
+			decode_at(&params, data, offset).map(Token::FixedArray)
+		},
+		ParamType::Array(ref inner) => {
+			let len = read_usize(data, offset)?;
+			// every element takes at least a word, which bounds the allocation below
+			if len > (data.len() - offset - 32) / 32 {
+				return Err(Error);
+			}
+			let params: Vec<ParamType> = (0..len).map(|_| (**inner).clone()).collect();
+			decode_at(&params, data, offset + 32).map(Token::Array)
+		},
+	}
+}
+
+/// Decodes a sequence whose head starts at `base` of `data`; offsets are relative to `base`.
+fn decode_at(params: &[ParamType], data: &[u8], base: usize) -> Result<Vec<Token>, Error> {
+	let mut tokens = Vec::with_capacity(params.len());
+	let mut head = base;
+	for param in params {
+		let token = if param.is_dynamic() {
+			let offset = read_usize(data, head)?;
+			if offset > data.len() - base {
+				return Err(Error);
+			}
+			decode_token(param, data, base + offset)?
+		} else {
+			decode_token(param, data, head)?
+		};
+		tokens.push(token);
+		head += param.head_len();
+	}
+	Ok(tokens)
+}
+
+/// Decodes a sequence of values of the given types.
+///
+/// # Errors
+///
+/// If `data` is too short, holds out-of-bounds offsets, or values invalid for their type.
+pub fn decode(params: &[ParamType], data: &[u8]) -> Result<Vec<Token>, Error> {
+	decode_at(params, data, 0)
+}
+
+/// Decodes the arguments of the current call, after its 4-byte selector.
+pub fn decode_input(params: &[ParamType]) -> Result<Vec<Token>, Error> {
+	let input = ext::input();
+	if input.len() < 4 {
+		return Err(Error);
+	}
+	decode(params, &input[4..])
+}
+
+#[cfg(all(feature = "eip211", feature = "kip6"))]
+/// Calls the function `selector` of `address` with `args` and all
+/// [`forwardable_gas`](../fn.forwardable_gas.html), decoding its output as `outputs`, requires
+/// EIP-211 and KIP-6.
+pub fn call_function(
+	address: &Address,
+	selector: [u8; 4],
+	args: &[Token],
+	outputs: &[ParamType],
+) -> Result<Vec<Token>, Error> {
+	let output = ext::call_with_output(ext::forwardable_gas(), address, U256::zero(), &encode_call(selector, args))?;
+	decode(outputs, &output)
+}
+
+/// Returns the encoded `tokens` from the current call.
+pub fn ret_encoded(tokens: &[Token]) -> ! {
+	ext::ret(&encode(tokens))
+}
@@ -0,0 +1,134 @@
+//! Heap usage statistics, for hunting down hidden allocations.
+//!
+//! Wrap the allocator of the contract in [`Counting`] and report the statistics of the
+//! execution with [`stats`] or, on chain, with [`log_stats`]. Counters are process-wide; a wasm
+//! instance serves a single execution, natively they are cleared with [`reset`].
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOC: Counting<MyAllocator> = Counting(MyAllocator);
+//! ```
+//!
+//! [`Counting`]: struct.Counting.html
+//! [`stats`]: fn.stats.html
+//! [`log_stats`]: fn.log_stats.html
+//! [`reset`]: fn.reset.html
+
+#[cfg(not(feature = "std"))]
+use core::alloc::{GlobalAlloc, Layout};
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+#[cfg(feature = "std")]
+use std::alloc::{GlobalAlloc, Layout};
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+
+use pwasm_std::types::{H256, U256};
+
+use ext;
+
+/// Topic of `HeapStats(uint256,uint256,uint256)`, logged with the allocation count, the peak
+/// and the current heap usage in bytes.
+pub const HEAP_STATS_TOPIC: [u8; 32] = [
+	0xc2, 0x45, 0x49, 0xb9, 0xdb, 0x28, 0x70, 0xf6, 0x7c, 0x47, 0x29, 0x5e, 0x84, 0xb9, 0x86, 0x5f,
+	0x4b, 0xaa, 0x5e, 0x5b, 0x54, 0x25, 0x98, 0x8a, 0x42, 0xf8, 0x94, 0xea, 0xd4, 0x61, 0x0e, 0x90,
+];
+
+static ALLOCATIONS: AtomicUsize = ATOMIC_USIZE_INIT;
+static DEALLOCATIONS: AtomicUsize = ATOMIC_USIZE_INIT;
+static CURRENT: AtomicUsize = ATOMIC_USIZE_INIT;
+static PEAK: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Heap usage statistics.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+	/// Number of allocations, including reallocations.
+	pub allocations: usize,
+	/// Number of deallocations.
+	pub deallocations: usize,
+	/// Bytes currently allocated.
+	pub current: usize,
+	/// Maximum number of bytes allocated at once.
+	pub peak: usize,
+}
+
+/// Allocator wrapper counting the allocations made through `A`.
+#[derive(Debug, Default)]
+pub struct Counting<A>(pub A);
+
+fn allocated(size: usize) {
+	ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+	let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+	let mut peak = PEAK.load(Ordering::Relaxed);
+	while current > peak {
+		match PEAK.compare_exchange_weak(peak, current, Ordering::Relaxed, Ordering::Relaxed) {
+			Ok(_) => break,
+			Err(actual) => peak = actual,
+		}
+	}
+}
+
+fn deallocated(size: usize) {
+	DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+	CURRENT.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for Counting<A> {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		let ptr = self.0.alloc(layout);
+		if !ptr.is_null() {
+			allocated(layout.size());
+		}
+		ptr
+	}
+
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		let ptr = self.0.alloc_zeroed(layout);
+		if !ptr.is_null() {
+			allocated(layout.size());
+		}
+		ptr
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		self.0.dealloc(ptr, layout);
+		deallocated(layout.size());
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let new_ptr = self.0.realloc(ptr, layout, new_size);
+		if !new_ptr.is_null() {
+			deallocated(layout.size());
+			allocated(new_size);
+			DEALLOCATIONS.fetch_sub(1, Ordering::Relaxed);
+		}
+		new_ptr
+	}
+}
+
+/// Statistics accumulated so far.
+pub fn stats() -> Stats {
+	Stats {
+		allocations: ALLOCATIONS.load(Ordering::Relaxed),
+		deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+		current: CURRENT.load(Ordering::Relaxed),
+		peak: PEAK.load(Ordering::Relaxed),
+	}
+}
+
+/// Clears the counters; the peak restarts from the current usage.
+pub fn reset() {
+	ALLOCATIONS.store(0, Ordering::Relaxed);
+	DEALLOCATIONS.store(0, Ordering::Relaxed);
+	PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+}
+
+/// Logs `HeapStats(allocations, peak, current)` as a debug event.
+pub fn log_stats() {
+	let stats = stats();
+	let mut data = [0u8; 96];
+	U256::from(stats.allocations as u64).to_big_endian(&mut data[..32]);
+	U256::from(stats.peak as u64).to_big_endian(&mut data[32..64]);
+	U256::from(stats.current as u64).to_big_endian(&mut data[64..]);
+	ext::log(&[H256::from(HEAP_STATS_TOPIC)], &data);
+}
@@ -0,0 +1,381 @@
+//! Runtime imports implemented on top of the Ethereum Environment Interface of eWASM.
+//!
+//! EEI takes values as 128-bit little-endian numbers and reports call and creation failures
+//! without telling running out of gas apart, so values above 128 bits trap and failed calls
+//! read as trapped. It has no `EXTCODEHASH`, which is computed from the code, so accounts
+//! without code hash to `keccak256("")` whether they exist or not. Creations cannot be given a
+//! gas limit, so the `create-gas` feature is not supported.
+
+#[cfg(feature = "kip4")]
+compile_error!("the ewasm backend has no create2 import");
+#[cfg(feature = "eip1344")]
+compile_error!("the ewasm backend has no chainid import");
+#[cfg(feature = "eip3198")]
+compile_error!("the ewasm backend has no basefee import");
+#[cfg(feature = "eip1153")]
+compile_error!("the ewasm backend has no transient storage imports");
+#[cfg(feature = "create-gas")]
+compile_error!("the ewasm backend cannot limit create gas");
+
+#[cfg(not(feature = "std"))]
+use core::{cmp, ptr, slice};
+#[cfg(feature = "std")]
+use std::{cmp, ptr, slice};
+
+use pwasm_std;
+
+use hash::keccak256;
+
+mod eei {
+	#[link(wasm_import_module = "ethereum")]
+	extern "C" {
+		pub fn call(gas: i64, address: *const u8, value: *const u8, data: *const u8, data_len: u32) -> i32;
+
+		#[link_name = "callDelegate"]
+		pub fn call_delegate(gas: i64, address: *const u8, data: *const u8, data_len: u32) -> i32;
+
+		#[link_name = "callStatic"]
+		pub fn call_static(gas: i64, address: *const u8, data: *const u8, data_len: u32) -> i32;
+
+		pub fn create(value: *const u8, data: *const u8, data_len: u32, result: *mut u8) -> i32;
+
+		#[link_name = "getBlockHash"]
+		pub fn get_block_hash(number: i64, result: *mut u8) -> i32;
+
+		#[link_name = "getExternalBalance"]
+		pub fn get_external_balance(address: *const u8, result: *mut u8);
+
+		#[link_name = "getExternalCodeSize"]
+		pub fn get_external_code_size(address: *const u8) -> u32;
+
+		#[link_name = "externalCodeCopy"]
+		pub fn external_code_copy(address: *const u8, result: *mut u8, code_offset: u32, len: u32);
+
+		#[link_name = "getBlockCoinbase"]
+		pub fn get_block_coinbase(result: *mut u8);
+
+		#[link_name = "getBlockTimestamp"]
+		pub fn get_block_timestamp() -> i64;
+
+		#[link_name = "getBlockNumber"]
+		pub fn get_block_number() -> i64;
+
+		#[link_name = "getBlockDifficulty"]
+		pub fn get_block_difficulty(result: *mut u8);
+
+		#[link_name = "getBlockGasLimit"]
+		pub fn get_block_gas_limit() -> i64;
+
+		#[cfg(feature = "kip6")]
+		#[link_name = "getGasLeft"]
+		pub fn get_gas_left() -> i64;
+
+		#[cfg(feature = "gasprice")]
+		#[link_name = "getTxGasPrice"]
+		pub fn get_tx_gas_price(result: *mut u8);
+
+		#[link_name = "getCaller"]
+		pub fn get_caller(result: *mut u8);
+
+		#[link_name = "getAddress"]
+		pub fn get_address(result: *mut u8);
+
+		#[link_name = "getCallValue"]
+		pub fn get_call_value(result: *mut u8);
+
+		#[link_name = "getTxOrigin"]
+		pub fn get_tx_origin(result: *mut u8);
+
+		pub fn log(
+			data: *const u8,
+			data_len: u32,
+			topic_count: u32,
+			topic1: *const u8,
+			topic2: *const u8,
+			topic3: *const u8,
+			topic4: *const u8
+		);
+
+		#[link_name = "selfDestruct"]
+		pub fn self_destruct(beneficiary: *const u8) -> !;
+
+		pub fn finish(data: *const u8, data_len: u32) -> !;
+
+		pub fn revert(data: *const u8, data_len: u32) -> !;
+
+		#[link_name = "getCallDataSize"]
+		pub fn get_call_data_size() -> u32;
+
+		#[link_name = "callDataCopy"]
+		pub fn call_data_copy(result: *mut u8, data_offset: u32, len: u32);
+
+		#[link_name = "getReturnDataSize"]
+		pub fn get_return_data_size() -> u32;
+
+		#[link_name = "returnDataCopy"]
+		pub fn return_data_copy(result: *mut u8, data_offset: u32, len: u32);
+
+		#[link_name = "storageLoad"]
+		pub fn storage_load(key: *const u8, result: *mut u8);
+
+		#[link_name = "storageStore"]
+		pub fn storage_store(key: *const u8, value: *const u8);
+	}
+}
+
+/// Maps an EEI call or creation status, `1` for a failure and `2` for a revert.
+fn status(eei_status: i32) -> i32 {
+	match eei_status {
+		0 => 0,
+		2 => 1,
+		_ => -1,
+	}
+}
+
+/// Reads a big-endian 256-bit value as a little-endian 128-bit one, trapping if it does not fit.
+unsafe fn read_u128(ptr: *const u8) -> [u8; 16] {
+	let value = slice::from_raw_parts(ptr, 32);
+	if value[..16].iter().any(|b| *b != 0) {
+		::ext::trap();
+	}
+	let mut res = [0u8; 16];
+	for i in 0..16 {
+		res[i] = value[31 - i];
+	}
+	res
+}
+
+/// Writes a little-endian value of at most 32 bytes as a big-endian 256-bit one.
+unsafe fn write_le(value: &[u8], dest: *mut u8) {
+	let dest = slice::from_raw_parts_mut(dest, 32);
+	for i in 0..32 {
+		dest[31 - i] = value.get(i).cloned().unwrap_or(0);
+	}
+}
+
+unsafe fn write_u64(value: u64, dest: *mut u8) {
+	let mut le = [0u8; 8];
+	for i in 0..8 {
+		le[i] = (value >> (8 * i)) as u8;
+	}
+	write_le(&le, dest);
+}
+
+unsafe fn copy_return_data(result_ptr: *mut u8, result_len: u32) {
+	let len = cmp::min(eei::get_return_data_size(), result_len);
+	if len > 0 {
+		eei::return_data_copy(result_ptr, 0, len);
+	}
+}
+
+unsafe fn call_status(eei_status: i32, result_ptr: *mut u8, result_len: u32) -> i32 {
+	let status = status(eei_status);
+	if status != -1 {
+		copy_return_data(result_ptr, result_len);
+	}
+	status
+}
+
+pub unsafe fn ccall(
+	gas: i64,
+	address: *const u8,
+	val_ptr: *const u8,
+	input_ptr: *const u8,
+	input_len: u32,
+	result_ptr: *mut u8,
+	result_len: u32,
+) -> i32 {
+	let value = read_u128(val_ptr);
+	call_status(eei::call(gas, address, value.as_ptr(), input_ptr, input_len), result_ptr, result_len)
+}
+
+pub unsafe fn dcall(
+	gas: i64,
+	address: *const u8,
+	input_ptr: *const u8,
+	input_len: u32,
+	result_ptr: *mut u8,
+	result_len: u32,
+) -> i32 {
+	call_status(eei::call_delegate(gas, address, input_ptr, input_len), result_ptr, result_len)
+}
+
+pub unsafe fn scall(
+	gas: i64,
+	address: *const u8,
+	input_ptr: *const u8,
+	input_len: u32,
+	result_ptr: *mut u8,
+	result_len: u32,
+) -> i32 {
+	call_status(eei::call_static(gas, address, input_ptr, input_len), result_ptr, result_len)
+}
+
+pub unsafe fn blockhash(number: i64, dest: *mut u8) {
+	if eei::get_block_hash(number, dest) != 0 {
+		slice::from_raw_parts_mut(dest, 32).copy_from_slice(&[0u8; 32]);
+	}
+}
+
+pub unsafe fn balance(address: *const u8, dest: *mut u8) {
+	let mut balance = [0u8; 16];
+	eei::get_external_balance(address, balance.as_mut_ptr());
+	write_le(&balance, dest);
+}
+
+#[cfg(feature = "extcode")]
+pub unsafe fn extcodesize(address: *const u8) -> u32 {
+	eei::get_external_code_size(address)
+}
+
+#[cfg(feature = "extcode")]
+pub unsafe fn extcodecopy(address: *const u8, dest: *mut u8, len: u32) {
+	eei::external_code_copy(address, dest, 0, cmp::min(len, eei::get_external_code_size(address)));
+}
+
+#[cfg(feature = "extcode")]
+pub unsafe fn extcodehash(address: *const u8, dest: *mut u8) {
+	let mut code = pwasm_std::Vec::new();
+	code.resize(eei::get_external_code_size(address) as usize, 0);
+	if !code.is_empty() {
+		eei::external_code_copy(address, code.as_mut_ptr(), 0, code.len() as u32);
+	}
+	slice::from_raw_parts_mut(dest, 32).copy_from_slice(&<[u8; 32]>::from(keccak256(&code)));
+}
+
+pub unsafe fn coinbase(dest: *mut u8) {
+	eei::get_block_coinbase(dest);
+}
+
+pub unsafe fn timestamp() -> i64 {
+	eei::get_block_timestamp()
+}
+
+pub unsafe fn blocknumber() -> i64 {
+	eei::get_block_number()
+}
+
+pub unsafe fn difficulty(dest: *mut u8) {
+	let mut difficulty = [0u8; 32];
+	eei::get_block_difficulty(difficulty.as_mut_ptr());
+	write_le(&difficulty, dest);
+}
+
+pub unsafe fn gaslimit(dest: *mut u8) {
+	write_u64(eei::get_block_gas_limit() as u64, dest);
+}
+
+#[cfg(feature = "kip6")]
+pub unsafe fn gasleft() -> i64 {
+	eei::get_gas_left()
+}
+
+#[cfg(feature = "gasprice")]
+pub unsafe fn gasprice(dest: *mut u8) {
+	let mut price = [0u8; 16];
+	eei::get_tx_gas_price(price.as_mut_ptr());
+	write_le(&price, dest);
+}
+
+#[cfg(feature = "eip1884")]
+pub unsafe fn selfbalance(dest: *mut u8) {
+	let mut address = [0u8; 20];
+	eei::get_address(address.as_mut_ptr());
+	balance(address.as_ptr(), dest);
+}
+
+pub unsafe fn sender(dest: *mut u8) {
+	eei::get_caller(dest);
+}
+
+pub unsafe fn address(dest: *mut u8) {
+	eei::get_address(dest);
+}
+
+pub unsafe fn value(dest: *mut u8) {
+	let mut value = [0u8; 16];
+	eei::get_call_value(value.as_mut_ptr());
+	write_le(&value, dest);
+}
+
+pub unsafe fn origin(dest: *mut u8) {
+	eei::get_tx_origin(dest);
+}
+
+pub unsafe fn elog(
+	topic_ptr: *const u8,
+	topic_count: u32,
+	data_ptr: *const u8,
+	data_len: u32
+) {
+	let topic = |index: u32| if index < topic_count { topic_ptr.offset(32 * index as isize) } else { ptr::null() };
+	eei::log(data_ptr, data_len, topic_count, topic(0), topic(1), topic(2), topic(3));
+}
+
+pub unsafe fn create(
+	endowment: *const u8,
+	code_ptr: *const u8,
+	code_len: u32,
+	result_ptr: *mut u8
+) -> i32 {
+	let endowment = read_u128(endowment);
+	status(eei::create(endowment.as_ptr(), code_ptr, code_len, result_ptr))
+}
+
+pub unsafe fn suicide(refund: *const u8) -> ! {
+	eei::self_destruct(refund)
+}
+
+pub unsafe fn ret(ptr: *const u8, len: u32) -> ! {
+	eei::finish(ptr, len)
+}
+
+#[cfg(feature = "eip140")]
+pub unsafe fn revert(ptr: *const u8, len: u32) -> ! {
+	eei::revert(ptr, len)
+}
+
+pub unsafe fn input_length() -> u32 {
+	eei::get_call_data_size()
+}
+
+pub unsafe fn fetch_input(dst: *mut u8) {
+	eei::call_data_copy(dst, 0, eei::get_call_data_size());
+}
+
+#[cfg(feature = "input-range")]
+pub unsafe fn fetch_input_range(offset: u32, len: u32, dst: *mut u8) {
+	eei::call_data_copy(dst, offset, len);
+}
+
+#[cfg(feature = "eip211")]
+pub unsafe fn return_data_length() -> u32 {
+	eei::get_return_data_size()
+}
+
+#[cfg(feature = "eip211")]
+pub unsafe fn fetch_return_data(dst: *mut u8) {
+	eei::return_data_copy(dst, 0, eei::get_return_data_size());
+}
+
+pub unsafe fn storage_read(key: *const u8, dst: *mut u8) {
+	eei::storage_load(key, dst);
+}
+
+pub unsafe fn storage_write(key: *const u8, src: *const u8) {
+	eei::storage_store(key, src);
+}
+
+#[cfg(feature = "storage-batch")]
+pub unsafe fn storage_read_multi(keys: *const u8, count: u32, dst: *mut u8) {
+	for i in 0..count as isize {
+		eei::storage_load(keys.offset(32 * i), dst.offset(32 * i));
+	}
+}
+
+#[cfg(feature = "storage-batch")]
+pub unsafe fn storage_write_multi(entries: *const u8, count: u32) {
+	for i in 0..count as isize {
+		eei::storage_store(entries.offset(64 * i), entries.offset(64 * i + 32));
+	}
+}
@@ -0,0 +1,30 @@
+//! Host functions behind the safe API, selected at compile time.
+//!
+//! Every backend exposes an `external` module with the same set of `unsafe` functions, named
+//! and typed after the imports of the Parity/OpenEthereum wasm runtime; `ext` and `storage`
+//! only call those. The backend is chosen by feature:
+//!
+//! - `backend-pwasm`, used unless another backend is selected: the functions are the runtime
+//!   imports themselves.
+//! - `backend-ewasm`: the functions are implemented on top of the Ethereum Environment
+//!   Interface (EEI) of eWASM, imported from the `ethereum` module. EEI always provides reverts,
+//!   return data, external code and input ranges, so this enables the `eip140`, `eip211`,
+//!   `extcode` and `input-range` features.
+//!
+//! The `native` feature replaces either backend with the native externalities.
+
+#[cfg(all(feature = "backend-pwasm", feature = "backend-ewasm"))]
+compile_error!("only one of the backend-pwasm and backend-ewasm features can be enabled");
+
+#[cfg(feature = "native")]
+pub(crate) use native::external;
+
+#[cfg(all(not(feature = "native"), not(feature = "backend-ewasm")))]
+pub(crate) mod pwasm;
+#[cfg(all(not(feature = "native"), not(feature = "backend-ewasm")))]
+pub(crate) use self::pwasm as external;
+
+#[cfg(all(not(feature = "native"), feature = "backend-ewasm"))]
+pub(crate) mod ewasm;
+#[cfg(all(not(feature = "native"), feature = "backend-ewasm"))]
+pub(crate) use self::ewasm as external;
@@ -0,0 +1,177 @@
+//! Imports of the Parity/OpenEthereum wasm runtime, from the `env` module.
+
+extern "C" {
+	// Various call variants
+	//
+	// Return 0 on success and -1 on failure. Runtimes providing the `eip211` imports return 1
+	// when the callee reverted and -2 when it ran out of gas instead.
+
+	/// Direct/classic call.
+	/// Corresponds to "CALL" opcode in EVM
+	pub fn ccall(
+		gas: i64,
+		address: *const u8,
+		val_ptr: *const u8,
+		input_ptr: *const u8,
+		input_len: u32,
+		result_ptr: *mut u8,
+		result_len: u32,
+	) -> i32;
+
+	/// Delegate call.
+	/// Corresponds to "CALLCODE" opcode in EVM
+	pub fn dcall(
+		gas: i64,
+		address: *const u8,
+		input_ptr: *const u8,
+		input_len: u32,
+		result_ptr: *mut u8,
+		result_len: u32,
+	) -> i32;
+
+	/// Static call.
+	/// Corresponds to "STACICCALL" opcode in EVM
+	pub fn scall(
+		gas: i64,
+		address: *const u8,
+		input_ptr: *const u8,
+		input_len: u32,
+		result_ptr: *mut u8,
+		result_len: u32,
+	) -> i32;
+
+	// environmental blockchain functions (runtime might not provide all of these!)
+
+	pub fn blockhash(number: i64, dest: *mut u8);
+
+	pub fn balance(address: *const u8, dest: *mut u8);
+
+	#[cfg(feature = "extcode")]
+	pub fn extcodesize(address: *const u8) -> u32;
+
+	#[cfg(feature = "extcode")]
+	pub fn extcodecopy(address: *const u8, dest: *mut u8, len: u32);
+
+	#[cfg(feature = "extcode")]
+	pub fn extcodehash(address: *const u8, dest: *mut u8);
+
+	pub fn coinbase(dest: *mut u8);
+
+	pub fn timestamp() -> i64;
+
+	pub fn blocknumber() -> i64;
+
+	pub fn difficulty(dest: *mut u8);
+
+	pub fn gaslimit(dest: *mut u8);
+
+	#[cfg(feature = "kip6")]
+	pub fn gasleft() -> i64;
+
+	#[cfg(feature = "eip1344")]
+	pub fn chainid() -> i64;
+
+	#[cfg(feature = "eip3198")]
+	pub fn basefee(dest: *mut u8);
+
+	#[cfg(feature = "gasprice")]
+	pub fn gasprice(dest: *mut u8);
+
+	#[cfg(feature = "eip1884")]
+	pub fn selfbalance(dest: *mut u8);
+
+	pub fn sender(dest: *mut u8);
+
+	pub fn address(dest: *mut u8);
+
+	pub fn value(dest: *mut u8);
+
+	pub fn origin(dest: *mut u8);
+
+	pub fn elog(
+		topic_ptr: *const u8,
+		topic_count: u32,
+		data_ptr: *const u8,
+		data_len: u32
+	);
+
+	pub fn create(
+		endowment: *const u8,
+		code_ptr: *const u8,
+		code_len: u32,
+		result_ptr: *mut u8
+	) -> i32;
+
+	#[cfg(feature = "kip4")]
+	pub fn create2(
+		endowment: *const u8,
+		salt: *const u8,
+		code_ptr: *const u8,
+		code_len: u32,
+		result_ptr: *mut u8
+	) -> i32;
+
+	#[cfg(feature = "create-gas")]
+	pub fn gcreate(
+		gas: i64,
+		endowment: *const u8,
+		code_ptr: *const u8,
+		code_len: u32,
+		result_ptr: *mut u8,
+		gas_used_ptr: *mut u64
+	) -> i32;
+
+	#[cfg(all(feature = "kip4", feature = "create-gas"))]
+	pub fn gcreate2(
+		gas: i64,
+		endowment: *const u8,
+		salt: *const u8,
+		code_ptr: *const u8,
+		code_len: u32,
+		result_ptr: *mut u8,
+		gas_used_ptr: *mut u64
+	) -> i32;
+
+	pub fn suicide(refund: *const u8) -> !;
+
+	pub fn ret(ptr: *const u8, len: u32) -> !;
+
+	#[cfg(feature = "eip140")]
+	pub fn revert(ptr: *const u8, len: u32) -> !;
+
+	pub fn input_length() -> u32;
+
+	pub fn fetch_input(dst: *mut u8);
+
+	#[cfg(feature = "input-range")]
+	pub fn fetch_input_range(offset: u32, len: u32, dst: *mut u8);
+
+	#[cfg(feature = "eip211")]
+	/// Length of the data returned by the last call.
+	/// Corresponds to "RETURNDATASIZE" opcode in EVM
+	pub fn return_data_length() -> u32;
+
+	#[cfg(feature = "eip211")]
+	/// Copies the data returned by the last call.
+	/// Corresponds to "RETURNDATACOPY" opcode in EVM
+	pub fn fetch_return_data(dst: *mut u8);
+
+	pub fn storage_read(key: *const u8, dst: *mut u8);
+
+	pub fn storage_write(key: *const u8, src: *const u8);
+
+	#[cfg(feature = "storage-batch")]
+	pub fn storage_read_multi(keys: *const u8, count: u32, dst: *mut u8);
+
+	#[cfg(feature = "storage-batch")]
+	pub fn storage_write_multi(entries: *const u8, count: u32);
+
+	#[cfg(feature = "eip1153")]
+	pub fn transient_storage_read(key: *const u8, dst: *mut u8);
+
+	#[cfg(feature = "eip1153")]
+	pub fn transient_storage_write(key: *const u8, src: *const u8);
+
+	#[cfg(all(feature = "keccak", not(feature = "tiny-keccak")))]
+	pub fn keccak(input_ptr: *const u8, input_len: u32, dest: *mut u8);
+}
@@ -0,0 +1,168 @@
+//! Batched execution of several calls to the current contract in one transaction.
+//!
+//! A batch is a sequence of operations, each framed as a 4-byte big-endian length followed by
+//! the call input. Operations run in order as delegate calls to the contract itself, so they
+//! see the original sender and value. As every operation would see the value again, batches
+//! cannot be sent with value.
+//!
+//! Batch calls are not ABI encoded: their selectors are hashes of `pwasm.batch.*` identifiers
+//! rather than of function signatures, and the framed batch follows them as is, so ABI tooling
+//! has to build the input with [`encode`](fn.encode.html).
+
+#[cfg(feature = "kip6")]
+use pwasm_std::String;
+use pwasm_std::Vec;
+
+#[cfg(feature = "kip6")]
+use ext;
+use ext::Error;
+
+/// Selector of an all-or-nothing batch call, followed by the framed batch.
+///
+/// First 4 bytes of `keccak256("pwasm.batch.execute")`, not an ABI function selector.
+pub const EXECUTE_BATCH: [u8; 4] = [0xdf, 0xa8, 0xcc, 0xd5];
+/// Selector of a best-effort batch call, followed by the framed batch.
+///
+/// First 4 bytes of `keccak256("pwasm.batch.tryExecute")`, not an ABI function selector.
+pub const TRY_EXECUTE_BATCH: [u8; 4] = [0xbc, 0x1f, 0xd6, 0x35];
+
+/// What happens when an operation of a batch fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+	/// The whole call traps, reverting every operation.
+	AllOrNothing,
+	/// The failure is reported and the remaining operations still run.
+	BestEffort,
+}
+
+/// Result of an operation of a batch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpResult {
+	/// Whether the operation succeeded.
+	pub success: bool,
+	/// Data returned by the operation, truncated to the requested length.
+	pub data: Vec<u8>,
+}
+
+/// Frames `ops` into a batch.
+pub fn encode(ops: &[&[u8]]) -> Vec<u8> {
+	let mut batch = Vec::with_capacity(ops.iter().map(|op| 4 + op.len()).sum());
+	for op in ops {
+		push_frame(&mut batch, op);
+	}
+	batch
+}
+
+/// Splits a batch into its operations.
+///
+/// # Errors
+///
+/// Returns [`Error`](../struct.Error.html) if a frame is truncated.
+pub fn decode(mut batch: &[u8]) -> Result<Vec<&[u8]>, Error> {
+	let mut ops = Vec::new();
+	while !batch.is_empty() {
+		if batch.len() < 4 {
+			return Err(Error);
+		}
+		let len = (batch[0] as usize) << 24 | (batch[1] as usize) << 16 | (batch[2] as usize) << 8 | batch[3] as usize;
+		if batch.len() - 4 < len {
+			return Err(Error);
+		}
+		ops.push(&batch[4..4 + len]);
+		batch = &batch[4 + len..];
+	}
+	Ok(ops)
+}
+
+#[cfg(feature = "kip6")]
+/// Executes the operations of `batch` against the current contract, requires KIP-6.
+///
+/// Each operation is given all [`forwardable_gas`](../fn.forwardable_gas.html). Up to
+/// `result_len` bytes returned by every operation are kept.
+///
+/// # Errors
+///
+/// Returns [`Error`](../struct.Error.html) if the batch is malformed, or if the current call
+/// carries value, which every operation would see again.
+///
+/// # Panics
+///
+/// Reverts with the reason `batch operation <index> failed` on the first failed operation in
+/// [`Mode::AllOrNothing`](enum.Mode.html).
+pub fn execute_batch(batch: &[u8], mode: Mode, result_len: usize) -> Result<Vec<OpResult>, Error> {
+	if !ext::value().is_zero() {
+		return Err(Error);
+	}
+	let this = ext::address();
+	let mut results = Vec::new();
+	for op in decode(batch)? {
+		let mut data = Vec::new();
+		data.resize(result_len, 0);
+		let success = ext::call_code(ext::forwardable_gas(), &this, op, &mut data).is_ok();
+		if !success && mode == Mode::AllOrNothing {
+			ext::revert_with_reason(&failure_reason(results.len()));
+		}
+		results.push(OpResult { success: success, data: data });
+	}
+	Ok(results)
+}
+
+#[cfg(feature = "kip6")]
+/// Answers batch calls, returning on any other input, requires KIP-6.
+///
+/// The call returns one frame per operation holding a status byte (`1` on success) followed by
+/// up to `result_len` bytes of its result.
+///
+/// # Panics
+///
+/// Reverts on a malformed batch, on a batch sent with value, or on a failed operation of an
+/// all-or-nothing batch.
+pub fn handle(input: &[u8], result_len: usize) {
+	if input.len() < 4 {
+		return;
+	}
+	let mode = match [input[0], input[1], input[2], input[3]] {
+		EXECUTE_BATCH => Mode::AllOrNothing,
+		TRY_EXECUTE_BATCH => Mode::BestEffort,
+		_ => return,
+	};
+	if !ext::value().is_zero() {
+		ext::revert_with_reason("batch sent with value");
+	}
+	let results = match execute_batch(&input[4..], mode, result_len) {
+		Ok(results) => results,
+		Err(Error) => ext::revert_with_reason("malformed batch"),
+	};
+	let mut output = Vec::new();
+	let mut frame = Vec::with_capacity(1 + result_len);
+	for result in results {
+		frame.clear();
+		frame.push(result.success as u8);
+		frame.extend_from_slice(&result.data);
+		push_frame(&mut output, &frame);
+	}
+	ext::ret(&output)
+}
+
+#[cfg(feature = "kip6")]
+fn failure_reason(index: usize) -> String {
+	let mut digits = Vec::new();
+	let mut rest = index;
+	loop {
+		digits.push((b'0' + (rest % 10) as u8) as char);
+		rest /= 10;
+		if rest == 0 {
+			break;
+		}
+	}
+	let mut reason = String::from("batch operation ");
+	reason.extend(digits.into_iter().rev());
+	reason.push_str(" failed");
+	reason
+}
+
+fn push_frame(out: &mut Vec<u8>, data: &[u8]) {
+	let len = data.len() as u32;
+	out.extend_from_slice(&[(len >> 24) as u8, (len >> 16) as u8, (len >> 8) as u8, len as u8]);
+	out.extend_from_slice(data);
+}
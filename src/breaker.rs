@@ -0,0 +1,172 @@
+//! Circuit breaker pausing outflows once a volume threshold is exceeded within a period.
+//!
+//! The breaker keeps its state in six consecutive storage slots and trips when the outflow
+//! recorded in the current period would exceed the threshold. Once tripped, every outflow is
+//! refused until the admin resets it.
+
+use pwasm_std::types::{H256, U256, Address};
+
+use ext::{self, Error};
+
+/// Topic of `CircuitBreakerTripped(uint256,uint256)`, logged with the outflow and the threshold.
+pub const TRIPPED_TOPIC: [u8; 32] = [
+	0x50, 0xd2, 0x6c, 0x67, 0x52, 0xa0, 0xcd, 0xd6, 0xf4, 0x76, 0x2f, 0xe2, 0xaf, 0x36, 0x66, 0xa6,
+	0x26, 0x6e, 0xa9, 0x1b, 0xd7, 0xa7, 0xd3, 0x44, 0x02, 0x57, 0x67, 0x95, 0x9b, 0x5d, 0x8e, 0xb7,
+];
+/// Topic of `CircuitBreakerReset(address)`, logged with the admin.
+pub const RESET_TOPIC: [u8; 32] = [
+	0x07, 0x66, 0x3e, 0xe3, 0xf7, 0xc1, 0x09, 0x90, 0x57, 0xfc, 0x9f, 0xc5, 0x55, 0xef, 0xb6, 0x75,
+	0xb7, 0x7f, 0xe2, 0x34, 0x38, 0x6e, 0xc9, 0x77, 0xb1, 0x2f, 0x15, 0xd5, 0xf2, 0x4f, 0xd0, 0x30,
+];
+
+const THRESHOLD: u8 = 0;
+const PERIOD: u8 = 1;
+const PERIOD_START: u8 = 2;
+const OUTFLOW: u8 = 3;
+const TRIPPED: u8 = 4;
+const ADMIN: u8 = 5;
+
+/// Circuit breaker stored in the slots `base || 0` to `base || 5` of the current contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitBreaker {
+	base: [u8; 31],
+}
+
+impl CircuitBreaker {
+	/// Breaker stored under `base`.
+	pub fn new(base: [u8; 31]) -> Self {
+		CircuitBreaker { base: base }
+	}
+
+	fn key(&self, slot: u8) -> H256 {
+		let mut key = [0u8; 32];
+		key[..31].copy_from_slice(&self.base);
+		key[31] = slot;
+		H256::from(key)
+	}
+
+	fn read_u256(&self, slot: u8) -> U256 {
+		U256::from_big_endian(&::read(&self.key(slot)))
+	}
+
+	fn write_u256(&self, slot: u8, value: U256) {
+		let mut word = [0u8; 32];
+		value.to_big_endian(&mut word);
+		::write(&self.key(slot), &word);
+	}
+
+	/// Sets the admin, the outflow allowed per period and the period length in seconds.
+	///
+	/// Meant to be called from the constructor; the first period starts now.
+	pub fn configure(&self, admin: &Address, threshold: U256, period: u64) {
+		let mut word = [0u8; 32];
+		word[12..].copy_from_slice(&<[u8; 20]>::from(*admin));
+		::write(&self.key(ADMIN), &word);
+		self.write_u256(THRESHOLD, threshold);
+		self.write_u256(PERIOD, period.into());
+		self.write_u256(PERIOD_START, ext::timestamp().into());
+		self.write_u256(OUTFLOW, U256::zero());
+	}
+
+	/// Admin allowed to reset the breaker.
+	pub fn admin(&self) -> Address {
+		let mut admin = [0u8; 20];
+		admin.copy_from_slice(&::read(&self.key(ADMIN))[12..]);
+		Address::from(admin)
+	}
+
+	/// Outflow allowed per period.
+	pub fn threshold(&self) -> U256 {
+		self.read_u256(THRESHOLD)
+	}
+
+	/// Length of a period in seconds.
+	pub fn period(&self) -> u64 {
+		self.read_u256(PERIOD).low_u64()
+	}
+
+	/// Outflow recorded in the current period.
+	pub fn outflow(&self) -> U256 {
+		if self.period_ended() { U256::zero() } else { self.read_u256(OUTFLOW) }
+	}
+
+	/// Whether outflows are paused.
+	pub fn is_tripped(&self) -> bool {
+		::read(&self.key(TRIPPED))[31] != 0
+	}
+
+	fn period_ended(&self) -> bool {
+		let start = self.read_u256(PERIOD_START).low_u64();
+		ext::timestamp() >= start.saturating_add(self.period())
+	}
+
+	/// Records an outflow of `amount`, tripping the breaker if it exceeds the threshold.
+	///
+	/// # Errors
+	///
+	/// Returns [`Error`](../struct.Error.html) if the breaker is or gets tripped. The outflow
+	/// must then be refused without trapping, since a trap would also revert the trip.
+	pub fn record_outflow(&self, amount: U256) -> Result<(), Error> {
+		if self.is_tripped() {
+			return Err(Error);
+		}
+		if self.period_ended() {
+			self.write_u256(PERIOD_START, ext::timestamp().into());
+			self.write_u256(OUTFLOW, U256::zero());
+		}
+		let (outflow, overflow) = self.read_u256(OUTFLOW).overflowing_add(amount);
+		let threshold = self.threshold();
+		if overflow || outflow > threshold {
+			let mut flag = [0u8; 32];
+			flag[31] = 1;
+			::write(&self.key(TRIPPED), &flag);
+			let mut data = [0u8; 64];
+			let outflow = if overflow { U256::max_value() } else { outflow };
+			outflow.to_big_endian(&mut data[..32]);
+			threshold.to_big_endian(&mut data[32..]);
+			ext::log(&[H256::from(TRIPPED_TOPIC)], &data);
+			return Err(Error);
+		}
+		self.write_u256(OUTFLOW, outflow);
+		Ok(())
+	}
+
+	/// Traps if the breaker is tripped.
+	pub fn require_not_tripped(&self) {
+		if self.is_tripped() {
+			ext::trap();
+		}
+	}
+
+	/// Resumes outflows and starts a new period.
+	///
+	/// # Errors
+	///
+	/// Returns [`Error`](../struct.Error.html) if the sender is not the admin.
+	pub fn reset(&self) -> Result<(), Error> {
+		let admin = self.admin();
+		if ext::sender() != admin {
+			return Err(Error);
+		}
+		::write(&self.key(TRIPPED), &[0u8; 32]);
+		self.write_u256(PERIOD_START, ext::timestamp().into());
+		self.write_u256(OUTFLOW, U256::zero());
+		let mut data = [0u8; 32];
+		data[12..].copy_from_slice(&<[u8; 20]>::from(admin));
+		ext::log(&[H256::from(RESET_TOPIC)], &data);
+		Ok(())
+	}
+
+	/// Changes the outflow allowed per period.
+	///
+	/// # Errors
+	///
+	/// Returns [`Error`](../struct.Error.html) if the sender is not the admin.
+	pub fn set_threshold(&self, threshold: U256) -> Result<(), Error> {
+		if ext::sender() != self.admin() {
+			return Err(Error);
+		}
+		self.write_u256(THRESHOLD, threshold);
+		Ok(())
+	}
+}
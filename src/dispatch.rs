@@ -0,0 +1,91 @@
+//! Routing of calls to handlers by function selector.
+//!
+//! A [`Dispatcher`] reads the 4-byte selector at the start of the input and runs the handler
+//! registered for it with the rest of the input. The output of the handler is returned to the
+//! caller, and an error reverts the call.
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub fn call() {
+//! 	Dispatcher::new()
+//! 		.route(abi::selector("totalSupply()"), |_| Ok(total_supply().to_vec()))
+//! 		.route(abi::selector("transfer(address,uint256)"), |args| {
+//! 			let args = abi::decode(&[ParamType::Address, ParamType::Uint], args)?;
+//! 			transfer(&args[0], &args[1])
+//! 		})
+//! 		.dispatch()
+//! }
+//! ```
+//!
+//! [`Dispatcher`]: struct.Dispatcher.html
+
+use pwasm_std::{Box, Vec};
+
+use ext::{self, Error};
+
+type Handler<'a> = Box<dyn FnMut(&[u8]) -> Result<Vec<u8>, Error> + 'a>;
+
+/// Router of the current call to the handler of its selector.
+pub struct Dispatcher<'a> {
+	routes: Vec<([u8; 4], Handler<'a>)>,
+	fallback: Option<Handler<'a>>,
+}
+
+impl<'a> Default for Dispatcher<'a> {
+	fn default() -> Self {
+		Dispatcher::new()
+	}
+}
+
+impl<'a> Dispatcher<'a> {
+	/// Dispatcher without routes, rejecting every call.
+	pub fn new() -> Self {
+		Dispatcher { routes: Vec::new(), fallback: None }
+	}
+
+	/// Runs `handler` with the arguments after the selector for calls to `selector`.
+	///
+	/// If several handlers are registered for the same selector, the first one is used.
+	pub fn route<F>(mut self, selector: [u8; 4], handler: F) -> Self
+		where F: FnMut(&[u8]) -> Result<Vec<u8>, Error> + 'a
+	{
+		self.routes.push((selector, Box::new(handler)));
+		self
+	}
+
+	/// Runs `handler` with the whole input for calls matching no route, including calls with
+	/// an input shorter than a selector.
+	pub fn fallback<F>(mut self, handler: F) -> Self
+		where F: FnMut(&[u8]) -> Result<Vec<u8>, Error> + 'a
+	{
+		self.fallback = Some(Box::new(handler));
+		self
+	}
+
+	/// Runs the handler for `input` and returns its output.
+	///
+	/// # Errors
+	///
+	/// If the handler fails, or no handler matches `input` and there is no fallback.
+	pub fn handle(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+		if input.len() >= 4 {
+			let (selector, args) = input.split_at(4);
+			if let Some(&mut (_, ref mut handler)) = self.routes.iter_mut().find(|route| &route.0[..] == selector) {
+				return handler(args);
+			}
+		}
+		match self.fallback {
+			Some(ref mut handler) => handler(input),
+			None => Err(Error),
+		}
+	}
+
+	/// Handles the input of the current call, returning the output of the handler with
+	/// [`ret`](../fn.ret.html), or reverting with no data if it fails.
+	pub fn dispatch(mut self) -> ! {
+		match self.handle(&ext::input()) {
+			Ok(output) => ext::ret(&output),
+			Err(Error) => ext::revert(&[]),
+		}
+	}
+}
@@ -0,0 +1,39 @@
+//! EIP-712 hashing of typed structured data.
+//!
+//! Struct hashes are computed by the caller; this module provides the domain separator and
+//! the final hash to be signed.
+
+use pwasm_std::types::{H256, Address};
+
+use hash::keccak256;
+
+/// Type of the domain in EIP-712 notation, with all of the standard fields present.
+pub const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+/// Type hash of an EIP-712 struct, `keccak256(encodeType(type))`.
+pub fn type_hash(encoded_type: &str) -> H256 {
+	keccak256(encoded_type.as_bytes())
+}
+
+/// Domain separator, `hashStruct(domain)` for a domain of [`DOMAIN_TYPE`](constant.DOMAIN_TYPE.html).
+pub fn domain_separator(name: &str, version: &str, chain_id: u64, verifying_contract: &Address) -> H256 {
+	let mut encoded = [0u8; 5 * 32];
+	encoded[..32].copy_from_slice(&<[u8; 32]>::from(keccak256(DOMAIN_TYPE.as_bytes())));
+	encoded[32..64].copy_from_slice(&<[u8; 32]>::from(keccak256(name.as_bytes())));
+	encoded[64..96].copy_from_slice(&<[u8; 32]>::from(keccak256(version.as_bytes())));
+	for i in 0..8 {
+		encoded[127 - i] = (chain_id >> (8 * i)) as u8;
+	}
+	encoded[140..].copy_from_slice(&<[u8; 20]>::from(*verifying_contract));
+	keccak256(&encoded)
+}
+
+/// Hash to be signed for EIP-712 typed data, `keccak256("\x19\x01" || domainSeparator || hashStruct(message))`.
+pub fn typed_data_hash(domain_separator: &H256, struct_hash: &H256) -> H256 {
+	let mut encoded = [0u8; 66];
+	encoded[0] = 0x19;
+	encoded[1] = 0x01;
+	encoded[2..34].copy_from_slice(&<[u8; 32]>::from(*domain_separator));
+	encoded[34..].copy_from_slice(&<[u8; 32]>::from(*struct_hash));
+	keccak256(&encoded)
+}
@@ -0,0 +1,84 @@
+//! Size and deployment cost estimation of compiled contracts.
+//!
+//! The estimate covers the transaction intrinsic gas and the code deposit cost. Gas spent by
+//! the constructor itself depends on its execution and is not included.
+
+use validate::{self, ValidationError};
+
+/// Gas prices relevant to contract deployment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeploySchedule {
+	/// Base cost of any transaction.
+	pub tx_gas: u64,
+	/// Additional base cost of a contract creating transaction.
+	pub tx_create_gas: u64,
+	/// Cost per zero byte of transaction data.
+	pub tx_data_zero_gas: u64,
+	/// Cost per non-zero byte of transaction data.
+	pub tx_data_non_zero_gas: u64,
+	/// Cost per byte of deposited code.
+	pub create_data_gas: u64,
+	/// Maximum size of deposited code.
+	pub max_code_size: usize,
+}
+
+impl Default for DeploySchedule {
+	fn default() -> Self {
+		DeploySchedule {
+			tx_gas: 21000,
+			tx_create_gas: 32000,
+			tx_data_zero_gas: 4,
+			tx_data_non_zero_gas: 68,
+			create_data_gas: 200,
+			max_code_size: 24576,
+		}
+	}
+}
+
+/// Deployment estimate of a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeployEstimate {
+	/// Size of the deployment module in bytes.
+	pub size: usize,
+	/// Size of the deposited code in bytes.
+	pub code_size: usize,
+	/// Number of imports of the deployment module.
+	pub imports: usize,
+	/// Intrinsic gas of the creating transaction.
+	pub intrinsic_gas: u64,
+	/// Gas paid for depositing the code.
+	pub deposit_gas: u64,
+	/// Whether the deposited code exceeds `DeploySchedule::max_code_size`.
+	pub exceeds_code_size: bool,
+}
+
+impl DeployEstimate {
+	/// Total estimated gas, excluding constructor execution.
+	pub fn gas(&self) -> u64 {
+		self.intrinsic_gas + self.deposit_gas
+	}
+}
+
+/// Estimates deployment of `wasm`, assuming the module is also the code being deposited.
+pub fn estimate(wasm: &[u8], schedule: &DeploySchedule) -> Result<DeployEstimate, ValidationError> {
+	estimate_with_code(wasm, wasm.len(), schedule)
+}
+
+/// Estimates deployment of the `wasm` module whose constructor deposits `code_size` bytes.
+pub fn estimate_with_code(wasm: &[u8], code_size: usize, schedule: &DeploySchedule) -> Result<DeployEstimate, ValidationError> {
+	let imports = validate::imports(wasm)?.len();
+	let zeros = wasm.iter().filter(|b| **b == 0).count() as u64;
+	let non_zeros = wasm.len() as u64 - zeros;
+
+	Ok(DeployEstimate {
+		size: wasm.len(),
+		code_size: code_size,
+		imports: imports,
+		intrinsic_gas: schedule.tx_gas
+			+ schedule.tx_create_gas
+			+ zeros * schedule.tx_data_zero_gas
+			+ non_zeros * schedule.tx_data_non_zero_gas,
+		deposit_gas: code_size as u64 * schedule.create_data_gas,
+		exceeds_code_size: code_size > schedule.max_code_size,
+	})
+}
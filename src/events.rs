@@ -0,0 +1,315 @@
+//! Typed events on top of [`log`](../fn.log.html).
+//!
+//! Topics are 32-byte words: `topic0` identifies the event and is the keccak-256 hash of its
+//! signature, indexed parameters follow, left-padded. The fixed-arity [`log1`] to [`log4`]
+//! cannot be given too many topics; [`LogBuilder`] checks the count when a topic is added.
+//!
+//! ```ignore
+//! struct Transfer { from: Address, to: Address, value: U256 }
+//!
+//! impl Event for Transfer {
+//! 	fn topic0() -> H256 { signature_topic("Transfer(address,address,uint256)") }
+//!
+//! 	fn write(&self, log: LogBuilder) -> LogBuilder {
+//! 		log.topic(&self.from).topic(&self.to).word(&self.value)
+//! 	}
+//! }
+//!
+//! Transfer { from: from, to: to, value: value }.emit();
+//! ```
+//!
+//! Implementing [`DecodeEvent`] as well reads captured logs back into the event, which the
+//! native backend uses for `Externalities::decoded_logs`:
+//!
+//! ```ignore
+//! impl DecodeEvent for Transfer {
+//! 	fn read(log: &mut LogReader) -> Option<Self> {
+//! 		Some(Transfer { from: log.topic()?, to: log.topic()?, value: log.word()? })
+//! 	}
+//! }
+//! ```
+//!
+//! [`log1`]: fn.log1.html
+//! [`log4`]: fn.log4.html
+//! [`LogBuilder`]: struct.LogBuilder.html
+//! [`DecodeEvent`]: trait.DecodeEvent.html
+
+use pwasm_std::{
+	self,
+	types::{H256, U256, Address}
+};
+
+use ext;
+#[cfg(any(feature = "keccak", feature = "tiny-keccak"))]
+use hash;
+
+/// Maximum number of topics of a log, `topic0` included.
+pub const MAX_TOPICS: usize = 4;
+
+#[cfg(any(feature = "keccak", feature = "tiny-keccak"))]
+/// Keccak-256 hash of an event signature like `Transfer(address,address,uint256)`, its `topic0`.
+pub fn signature_topic(signature: &str) -> H256 {
+	hash::keccak256(signature.as_bytes())
+}
+
+/// Value usable as an indexed parameter or a data word.
+pub trait Topic {
+	/// The value as a 32-byte word.
+	fn to_topic(&self) -> H256;
+}
+
+impl Topic for H256 {
+	fn to_topic(&self) -> H256 {
+		*self
+	}
+}
+
+impl Topic for [u8; 32] {
+	fn to_topic(&self) -> H256 {
+		H256::from(*self)
+	}
+}
+
+impl Topic for Address {
+	fn to_topic(&self) -> H256 {
+		let mut word = [0u8; 32];
+		word[12..].copy_from_slice(&<[u8; 20]>::from(*self));
+		H256::from(word)
+	}
+}
+
+impl Topic for U256 {
+	fn to_topic(&self) -> H256 {
+		let mut word = [0u8; 32];
+		self.to_big_endian(&mut word);
+		H256::from(word)
+	}
+}
+
+impl Topic for u64 {
+	fn to_topic(&self) -> H256 {
+		U256::from(*self).to_topic()
+	}
+}
+
+impl Topic for bool {
+	fn to_topic(&self) -> H256 {
+		let mut word = [0u8; 32];
+		word[31] = *self as u8;
+		H256::from(word)
+	}
+}
+
+/// Value decodable from an indexed parameter or a data word, the inverse of [`Topic`](trait.Topic.html).
+pub trait FromTopic: Sized {
+	/// The value encoded by `word`, or `None` if `word` is not a valid encoding.
+	fn from_topic(word: &H256) -> Option<Self>;
+}
+
+impl FromTopic for H256 {
+	fn from_topic(word: &H256) -> Option<Self> {
+		Some(*word)
+	}
+}
+
+impl FromTopic for [u8; 32] {
+	fn from_topic(word: &H256) -> Option<Self> {
+		Some(<[u8; 32]>::from(*word))
+	}
+}
+
+impl FromTopic for Address {
+	fn from_topic(word: &H256) -> Option<Self> {
+		let word = <[u8; 32]>::from(*word);
+		if word[..12].iter().any(|&b| b != 0) {
+			return None;
+		}
+		let mut address = [0u8; 20];
+		address.copy_from_slice(&word[12..]);
+		Some(Address::from(address))
+	}
+}
+
+impl FromTopic for U256 {
+	fn from_topic(word: &H256) -> Option<Self> {
+		Some(U256::from_big_endian(&<[u8; 32]>::from(*word)))
+	}
+}
+
+impl FromTopic for u64 {
+	fn from_topic(word: &H256) -> Option<Self> {
+		let value = U256::from_topic(word)?;
+		if value > U256::from(u64::max_value()) {
+			return None;
+		}
+		Some(value.low_u64())
+	}
+}
+
+impl FromTopic for bool {
+	fn from_topic(word: &H256) -> Option<Self> {
+		let word = <[u8; 32]>::from(*word);
+		if word[..31].iter().any(|&b| b != 0) {
+			return None;
+		}
+		match word[31] {
+			0 => Some(false),
+			1 => Some(true),
+			_ => None,
+		}
+	}
+}
+
+/// Builder of a log with up to [`MAX_TOPICS`](constant.MAX_TOPICS.html) topics.
+#[derive(Clone, Debug)]
+pub struct LogBuilder {
+	topics: [H256; MAX_TOPICS],
+	count: usize,
+	data: pwasm_std::Vec<u8>,
+}
+
+impl LogBuilder {
+	/// Log of the event identified by `topic0`.
+	pub fn new(topic0: H256) -> Self {
+		LogBuilder::anonymous().topic(&topic0)
+	}
+
+	/// Log without `topic0`, as emitted by anonymous events.
+	pub fn anonymous() -> Self {
+		LogBuilder { topics: [H256::zero(); MAX_TOPICS], count: 0, data: pwasm_std::Vec::new() }
+	}
+
+	/// Adds an indexed parameter.
+	///
+	/// # Panics
+	///
+	/// Traps if the log already has [`MAX_TOPICS`](constant.MAX_TOPICS.html) topics.
+	pub fn topic<T: Topic>(mut self, topic: &T) -> Self {
+		if self.count == MAX_TOPICS {
+			ext::trap();
+		}
+		self.topics[self.count] = topic.to_topic();
+		self.count += 1;
+		self
+	}
+
+	/// Appends a word to the data.
+	pub fn word<T: Topic>(mut self, word: &T) -> Self {
+		self.data.extend_from_slice(&<[u8; 32]>::from(word.to_topic()));
+		self
+	}
+
+	/// Appends raw bytes to the data.
+	pub fn data(mut self, data: &[u8]) -> Self {
+		self.data.extend_from_slice(data);
+		self
+	}
+
+	/// Emits the log.
+	pub fn emit(&self) {
+		ext::log(&self.topics[..self.count], &self.data)
+	}
+}
+
+/// Event with a typed layout.
+pub trait Event {
+	/// Topic identifying the event, usually [`signature_topic`](fn.signature_topic.html) of its signature.
+	fn topic0() -> H256;
+
+	/// Adds the indexed parameters and the data of the event to `log`.
+	fn write(&self, log: LogBuilder) -> LogBuilder;
+
+	/// Emits the event.
+	fn emit(&self) {
+		self.write(LogBuilder::new(Self::topic0())).emit()
+	}
+}
+
+/// Reader of the indexed parameters and the data of a log, in the order [`LogBuilder`] wrote them.
+///
+/// [`LogBuilder`]: struct.LogBuilder.html
+#[derive(Clone, Debug)]
+pub struct LogReader<'a> {
+	topics: &'a [H256],
+	data: &'a [u8],
+}
+
+impl<'a> LogReader<'a> {
+	/// Reader of the given indexed parameters, `topic0` excluded, and data.
+	pub fn new(topics: &'a [H256], data: &'a [u8]) -> Self {
+		LogReader { topics: topics, data: data }
+	}
+
+	/// Reads the next indexed parameter.
+	pub fn topic<T: FromTopic>(&mut self) -> Option<T> {
+		let (topic, rest) = self.topics.split_first()?;
+		self.topics = rest;
+		T::from_topic(topic)
+	}
+
+	/// Reads the next word of the data.
+	pub fn word<T: FromTopic>(&mut self) -> Option<T> {
+		if self.data.len() < 32 {
+			return None;
+		}
+		let mut word = [0u8; 32];
+		word.copy_from_slice(&self.data[..32]);
+		self.data = &self.data[32..];
+		T::from_topic(&H256::from(word))
+	}
+
+	/// Reads the rest of the data.
+	pub fn data(&mut self) -> &'a [u8] {
+		let data = self.data;
+		self.data = &[];
+		data
+	}
+
+	/// Whether all indexed parameters and data have been read.
+	pub fn is_empty(&self) -> bool {
+		self.topics.is_empty() && self.data.is_empty()
+	}
+}
+
+/// Event which can be read back from a captured log.
+pub trait DecodeEvent: Event + Sized {
+	/// Reads the indexed parameters and the data written by [`Event::write`](trait.Event.html#tymethod.write).
+	fn read(log: &mut LogReader) -> Option<Self>;
+
+	/// The event logged with the given topics and data.
+	///
+	/// `None` if `topic0` is not the one of the event, or the rest of the log is not a valid
+	/// encoding of it or is not entirely read.
+	fn decode(topics: &[H256], data: &[u8]) -> Option<Self> {
+		let (topic0, topics) = topics.split_first()?;
+		if *topic0 != Self::topic0() {
+			return None;
+		}
+		let mut log = LogReader::new(topics, data);
+		let event = Self::read(&mut log)?;
+		if !log.is_empty() {
+			return None;
+		}
+		Some(event)
+	}
+}
+
+/// Emits a log with `topic0` only.
+pub fn log1(topic0: H256, data: &[u8]) {
+	ext::log(&[topic0], data)
+}
+
+/// Emits a log with `topic0` and one indexed parameter.
+pub fn log2<A: Topic>(topic0: H256, a: &A, data: &[u8]) {
+	ext::log(&[topic0, a.to_topic()], data)
+}
+
+/// Emits a log with `topic0` and two indexed parameters.
+pub fn log3<A: Topic, B: Topic>(topic0: H256, a: &A, b: &B, data: &[u8]) {
+	ext::log(&[topic0, a.to_topic(), b.to_topic()], data)
+}
+
+/// Emits a log with `topic0` and three indexed parameters.
+pub fn log4<A: Topic, B: Topic, C: Topic>(topic0: H256, a: &A, b: &B, c: &C, data: &[u8]) {
+	ext::log(&[topic0, a.to_topic(), b.to_topic(), c.to_topic()], data)
+}
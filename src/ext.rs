@@ -1,109 +1,93 @@
 //! Safe wrapper around externalities invokes.
 
+#[cfg(not(feature = "std"))]
+use core::str;
+#[cfg(feature = "std")]
+use std::str;
+
 use pwasm_std::{
 	self,
 	types::{H256, U256, Address}
 };
 
+use backend::external;
+
 /// Generic wasm error
 #[derive(Debug)]
 pub struct Error;
 
-mod external {
-	extern "C" {
-		// Various call variants
-
-		/// Direct/classic call.
-		/// Corresponds to "CALL" opcode in EVM
-		pub fn ccall(
-			gas: i64,
-			address: *const u8,
-			val_ptr: *const u8,
-			input_ptr: *const u8,
-			input_len: u32,
-			result_ptr: *mut u8,
-			result_len: u32,
-		) -> i32;
-
-		/// Delegate call.
-		/// Corresponds to "CALLCODE" opcode in EVM
-		pub fn dcall(
-			gas: i64,
-			address: *const u8,
-			input_ptr: *const u8,
-			input_len: u32,
-			result_ptr: *mut u8,
-			result_len: u32,
-		) -> i32;
-
-		/// Static call.
-		/// Corresponds to "STACICCALL" opcode in EVM
-		pub fn scall(
-			gas: i64,
-			address: *const u8,
-			input_ptr: *const u8,
-			input_len: u32,
-			result_ptr: *mut u8,
-			result_len: u32,
-		) -> i32;
-
-		// environmental blockchain functions (runtime might not provide all of these!)
-
-		pub fn blockhash(number: i64, dest: *mut u8);
-
-		pub fn balance(address: *const u8, dest: *mut u8);
-
-		pub fn coinbase(dest: *mut u8);
-
-		pub fn timestamp() -> i64;
-
-		pub fn blocknumber() -> i64;
-
-		pub fn difficulty(dest: *mut u8);
-
-		pub fn gaslimit(dest: *mut u8);
-
-		#[cfg(feature = "kip6")]
-		pub fn gasleft() -> i64;
-
-		pub fn sender(dest: *mut u8);
-
-		pub fn address(dest: *mut u8);
-
-		pub fn value(dest: *mut u8);
-
-		pub fn origin(dest: *mut u8);
-
-		pub fn elog(
-			topic_ptr: *const u8,
-			topic_count: u32,
-			data_ptr: *const u8,
-			data_len: u32
-		);
-
-		pub fn create(
-			endowment: *const u8,
-			code_ptr: *const u8,
-			code_len: u32,
-			result_ptr: *mut u8
-		) -> i32;
+/// Failure of a message call
+///
+/// The Parity runtime returns `-1` for any failed call, which maps to [`Trapped`]. With the
+/// `eip211` feature, the runtime is expected to also tell reverts (status `1`, with the payload
+/// as return data) and running out of gas (status `-2`) apart; this is the convention of the
+/// `eip211` host functions, implemented by the native externalities.
+///
+/// [`Trapped`]: #variant.Trapped
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallError {
+	/// The callee reverted, with the given payload, requires EIP-211
+	Reverted(pwasm_std::Vec<u8>),
+	/// The callee ran out of gas, requires EIP-211
+	OutOfGas,
+	/// The callee trapped, or the runtime does not tell why the call failed
+	Trapped,
+	/// The call was not made, as it sends value where none can be sent, or has no gas set
+	/// without KIP-6
+	Invalid,
+}
 
-		#[cfg(feature = "kip4")]
-		pub fn create2(
-			endowment: *const u8,
-			salt: *const u8,
-			code_ptr: *const u8,
-			code_len: u32,
-			result_ptr: *mut u8
-		) -> i32;
+impl CallError {
+	/// Selector of Solidity `Error(string)` revert payloads
+	pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+	/// Maps a call status returned by the runtime: `1` is a revert and `-2` running out of gas
+	#[cfg(feature = "eip211")]
+	fn from_status(status: i32) -> Self {
+		match status {
+			1 => CallError::Reverted(return_data()),
+			-2 => CallError::OutOfGas,
+			_ => CallError::Trapped,
+		}
+	}
 
-		pub fn suicide(refund: *const u8) -> !;
+	/// Maps a call status returned by the runtime, which only tells that the call failed
+	#[cfg(not(feature = "eip211"))]
+	fn from_status(_status: i32) -> Self {
+		CallError::Trapped
+	}
 
-		pub fn ret(ptr: *const u8, len: u32) -> !;
+	/// Reason message of a Solidity-style `Error(string)` revert
+	pub fn reason(&self) -> Option<&str> {
+		let payload = match *self {
+			CallError::Reverted(ref payload) => payload,
+			_ => return None,
+		};
+		if payload.get(..4) != Some(&CallError::ERROR_SELECTOR[..]) {
+			return None;
+		}
+		if U256::from_big_endian(payload.get(4..36)?) != U256::from(32u64) {
+			return None;
+		}
+		let len = U256::from_big_endian(payload.get(36..68)?);
+		let message = payload.get(68..)?;
+		if len > U256::from(message.len() as u64) {
+			return None;
+		}
+		str::from_utf8(message.get(..len.low_u64() as usize)?).ok()
+	}
+}
 
-		pub fn input_length() -> u32;
+impl From<CallError> for Error {
+	fn from(_: CallError) -> Error {
+		Error
+	}
+}
 
-		pub fn fetch_input(dst: *mut u8);
+fn call_result(status: i32) -> Result<(), CallError> {
+	match status {
+		0 => Ok(()),
+		status => Err(CallError::from_status(status)),
 	}
 }
 
@@ -122,6 +106,39 @@ pub fn balance(address: &Address) -> U256 {
 	unsafe { fetch_u256(|x| external::balance(address.as_ptr(), x) ) }
 }
 
+#[cfg(feature = "extcode")]
+/// Get size of the code of the given account, in bytes.
+///
+/// Corresponds to "EXTCODESIZE" opcode in EVM. Zero for external accounts, and for contracts
+/// whose constructor is still running.
+pub fn code_size(address: &Address) -> u64 {
+	unsafe { external::extcodesize(address.as_ptr()) as u64 }
+}
+
+#[cfg(feature = "extcode")]
+/// Allocates and fetches the code of the given account.
+///
+/// Corresponds to "EXTCODECOPY" opcode in EVM.
+pub fn code_at(address: &Address) -> pwasm_std::Vec<u8> {
+	let mut code = pwasm_std::Vec::new();
+	code.resize(code_size(address) as usize, 0);
+	if !code.is_empty() {
+		unsafe { external::extcodecopy(address.as_ptr(), code.as_mut_ptr(), code.len() as u32); }
+	}
+	code
+}
+
+#[cfg(feature = "extcode")]
+/// Get keccak-256 hash of the code of the given account (EIP-1052).
+///
+/// Corresponds to "EXTCODEHASH" opcode in EVM. Returns H256::zero() for accounts that do not
+/// exist, and the hash of empty code for existing accounts without code.
+pub fn code_hash(address: &Address) -> H256 {
+	let mut res = H256::zero();
+	unsafe { external::extcodehash(address.as_ptr(), res.as_mut_ptr()); }
+	res
+}
+
 /// Create a new account with the given code
 ///
 /// # Errors
@@ -174,6 +191,95 @@ pub fn create2(endowment: U256, salt: H256, code: &[u8]) -> Result<Address, Erro
 	}
 }
 
+#[cfg(feature = "create-gas")]
+/// Outcome of [`create_with_gas`] or [`create2_with_gas`]
+///
+/// [`create_with_gas`]: fn.create_with_gas.html
+/// [`create2_with_gas`]: fn.create2_with_gas.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CreateResult {
+	/// Address of the new contract, `None` if the creation failed
+	pub address: Option<Address>,
+	/// Gas consumed by the constructor
+	pub gas_used: u64,
+	/// Data the constructor reverted with, empty unless it reverted, and always without EIP-211
+	pub revert_data: pwasm_std::Vec<u8>,
+}
+
+#[cfg(feature = "create-gas")]
+impl CreateResult {
+	fn from_status(status: i32, address: Address, gas_used: u64) -> Self {
+		CreateResult {
+			address: if status == 0 { Some(address) } else { None },
+			gas_used: gas_used,
+			revert_data: CreateResult::revert_data(status),
+		}
+	}
+
+	/// Payload of a constructor revert, reported with status `1` like for [`CallError`]
+	///
+	/// [`CallError`]: enum.CallError.html
+	#[cfg(feature = "eip211")]
+	fn revert_data(status: i32) -> pwasm_std::Vec<u8> {
+		if status == 1 { return_data() } else { pwasm_std::Vec::new() }
+	}
+
+	#[cfg(not(feature = "eip211"))]
+	fn revert_data(_status: i32) -> pwasm_std::Vec<u8> {
+		pwasm_std::Vec::new()
+	}
+
+	/// Whether the contract was created
+	pub fn is_success(&self) -> bool {
+		self.address.is_some()
+	}
+}
+
+#[cfg(feature = "create-gas")]
+/// Like [`create`], but gives at most `gas` to the constructor and reports the whole outcome
+///
+/// [`create`]: fn.create.html
+pub fn create_with_gas(gas: u64, endowment: U256, code: &[u8]) -> CreateResult {
+	let mut endowment_arr = [0u8; 32];
+	endowment.to_big_endian(&mut endowment_arr);
+	let mut address = Address::zero();
+	let mut gas_used = 0u64;
+	let status = unsafe {
+		external::gcreate(
+			gas as i64,
+			endowment_arr.as_ptr(),
+			code.as_ptr(),
+			code.len() as u32,
+			address.as_mut_ptr(),
+			&mut gas_used
+		)
+	};
+	CreateResult::from_status(status, address, gas_used)
+}
+
+#[cfg(all(feature = "kip4", feature = "create-gas"))]
+/// Like [`create2`], but gives at most `gas` to the constructor and reports the whole outcome
+///
+/// [`create2`]: fn.create2.html
+pub fn create2_with_gas(gas: u64, endowment: U256, salt: H256, code: &[u8]) -> CreateResult {
+	let mut endowment_arr = [0u8; 32];
+	endowment.to_big_endian(&mut endowment_arr);
+	let mut address = Address::zero();
+	let mut gas_used = 0u64;
+	let status = unsafe {
+		external::gcreate2(
+			gas as i64,
+			endowment_arr.as_ptr(),
+			salt.as_ptr(),
+			code.as_ptr(),
+			code.len() as u32,
+			address.as_mut_ptr(),
+			&mut gas_used
+		)
+	};
+	CreateResult::from_status(status, address, gas_used)
+}
+
 ///	Message-call into an account
 ///
 ///	# Arguments:
@@ -186,24 +292,21 @@ pub fn create2(endowment: U256, salt: H256, code: &[u8]) -> Result<Address, Erro
 ///	# Returns:
 ///
 /// Call is succeed if it returns `Result::Ok(())`
-/// If call returns `Result::Err(Error)` it means tha call was failed due to execution halting
-pub fn call(gas: u64, address: &Address, value: U256, input: &[u8], result: &mut [u8]) -> Result<(), Error> {
+/// If call returns `Result::Err(CallError)` it means tha call was failed due to execution halting,
+/// with the revert payload if the callee reverted
+pub fn call(gas: u64, address: &Address, value: U256, input: &[u8], result: &mut [u8]) -> Result<(), CallError> {
 	let mut value_arr = [0u8; 32];
 	value.to_big_endian(&mut value_arr);
-	unsafe {
-		if external::ccall(
+	call_result(unsafe {
+		external::ccall(
 			gas as i64,
 			address.as_ptr(),
 			value_arr.as_ptr(),
 			input.as_ptr(),
 			input.len() as u32,
 			result.as_mut_ptr(), result.len() as u32
-		) == 0 {
-			Ok(())
-		} else {
-			Err(Error)
-		}
-	}
+		)
+	})
 }
 
 /// Like [`call`], but with code at the given `address`
@@ -212,21 +315,17 @@ pub fn call(gas: u64, address: &Address, value: U256, input: &[u8], result: &mut
 /// different code (i.e. like `DELEGATECALL` EVM instruction).
 ///
 /// [`call`]: fn.call.html
-pub fn call_code(gas: u64, address: &Address, input: &[u8], result: &mut [u8]) -> Result<(), Error> {
-	unsafe {
-		if external::dcall(
+pub fn call_code(gas: u64, address: &Address, input: &[u8], result: &mut [u8]) -> Result<(), CallError> {
+	call_result(unsafe {
+		external::dcall(
 			gas as i64,
 			address.as_ptr(),
 			input.as_ptr(),
 			input.len() as u32,
 			result.as_mut_ptr(),
 			result.len() as u32
-		) == 0 {
-			Ok(())
-		} else {
-			Err(Error)
-		}
-	}
+		)
+	})
 }
 
 /// Like [`call`], but this call and any of it's subcalls are disallowed to modify any storage.
@@ -234,23 +333,176 @@ pub fn call_code(gas: u64, address: &Address, input: &[u8], result: &mut [u8]) -
 /// It will return an error in this case.
 ///
 /// [`call`]: fn.call.html
-pub fn static_call(gas: u64, address: &Address, input: &[u8], result: &mut [u8]) -> Result<(), Error> {
-	unsafe {
-		if external::scall(
+pub fn static_call(gas: u64, address: &Address, input: &[u8], result: &mut [u8]) -> Result<(), CallError> {
+	call_result(unsafe {
+		external::scall(
 			gas as i64,
 			address.as_ptr(),
 			input.as_ptr(),
 			input.len() as u32,
 			result.as_mut_ptr(),
 			result.len() as u32
-		) == 0 {
-			Ok(())
-		} else {
-			Err(Error)
+		)
+	})
+}
+
+#[cfg(feature = "eip211")]
+/// Like [`call`], but returns the whole output of the callee, requires EIP-211.
+///
+/// The output buffer is allocated after the call, with the length reported by the runtime.
+///
+/// [`call`]: fn.call.html
+pub fn call_with_output(gas: u64, address: &Address, value: U256, input: &[u8]) -> Result<pwasm_std::Vec<u8>, CallError> {
+	call(gas, address, value, input, &mut [])?;
+	Ok(return_data())
+}
+
+#[cfg(feature = "eip211")]
+/// Like [`call_code`], but returns the whole output of the callee, requires EIP-211.
+///
+/// [`call_code`]: fn.call_code.html
+pub fn call_code_with_output(gas: u64, address: &Address, input: &[u8]) -> Result<pwasm_std::Vec<u8>, CallError> {
+	call_code(gas, address, input, &mut [])?;
+	Ok(return_data())
+}
+
+#[cfg(feature = "eip211")]
+/// Like [`static_call`], but returns the whole output of the callee, requires EIP-211.
+///
+/// [`static_call`]: fn.static_call.html
+pub fn static_call_with_output(gas: u64, address: &Address, input: &[u8]) -> Result<pwasm_std::Vec<u8>, CallError> {
+	static_call(gas, address, input, &mut [])?;
+	Ok(return_data())
+}
+
+/// Gas given to the recipient of [`transfer`] and [`send`]
+///
+/// Enough to log an event, not to write storage or make further calls.
+///
+/// [`transfer`]: fn.transfer.html
+/// [`send`]: fn.send.html
+pub const CALL_STIPEND: u64 = 2300;
+
+/// Sends `value` Wei to `address` with empty input and [`CALL_STIPEND`] gas
+///
+/// Like Solidity `transfer`, but returns an error instead of reverting the caller.
+///
+/// [`CALL_STIPEND`]: constant.CALL_STIPEND.html
+pub fn transfer(address: &Address, value: U256) -> Result<(), Error> {
+	call(CALL_STIPEND, address, value, &[], &mut [])?;
+	Ok(())
+}
+
+/// Like [`transfer`], but only tells whether the value was sent, like Solidity `send`
+///
+/// [`transfer`]: fn.transfer.html
+pub fn send(address: &Address, value: U256) -> bool {
+	transfer(address, value).is_ok()
+}
+
+#[cfg(feature = "eip211")]
+/// Builder of a message call, unifying [`call`], [`call_code`] and [`static_call`], requires EIP-211.
+///
+/// Gives all [`forwardable_gas`] to the callee unless limited with [`gas`], and returns its
+/// whole output. Without KIP-6, the gas must be set, or the call fails with
+/// [`CallError::Invalid`].
+///
+/// ```ignore
+/// let output = CallBuilder::new(&token).gas(50_000).input(&data).call()?;
+/// ```
+///
+/// [`call`]: fn.call.html
+/// [`call_code`]: fn.call_code.html
+/// [`static_call`]: fn.static_call.html
+/// [`forwardable_gas`]: fn.forwardable_gas.html
+/// [`gas`]: #method.gas
+/// [`CallError::Invalid`]: enum.CallError.html#variant.Invalid
+#[derive(Clone, Debug)]
+pub struct CallBuilder<'a> {
+	address: Address,
+	gas: Option<u64>,
+	value: U256,
+	input: &'a [u8],
+}
+
+#[cfg(feature = "eip211")]
+impl<'a> CallBuilder<'a> {
+	/// Call to `address` with all forwardable gas, no value and empty input
+	pub fn new(address: &Address) -> Self {
+		CallBuilder { address: *address, gas: None, value: U256::zero(), input: &[] }
+	}
+
+	/// Limits the gas given to the callee
+	///
+	/// Combine with [`gas_left`] to keep a reserve for the rest of the execution.
+	///
+	/// [`gas_left`]: fn.gas_left.html
+	pub fn gas(mut self, gas: u64) -> Self {
+		self.gas = Some(gas);
+		self
+	}
+
+	/// Sets the value in Wei sent with [`call`](#method.call)
+	pub fn value(mut self, value: U256) -> Self {
+		self.value = value;
+		self
+	}
+
+	/// Sets the call input
+	pub fn input(mut self, input: &'a [u8]) -> Self {
+		self.input = input;
+		self
+	}
+
+	/// Makes a regular message call, returning the output of the callee
+	pub fn call(&self) -> Result<pwasm_std::Vec<u8>, CallError> {
+		call_with_output(gas_or_forwardable(self.gas)?, &self.address, self.value, self.input)
+	}
+
+	/// Makes a static call, returning the output of the callee
+	///
+	/// Fails with [`CallError::Invalid`] if a value is set, as static calls cannot transfer any.
+	///
+	/// [`CallError::Invalid`]: enum.CallError.html#variant.Invalid
+	pub fn static_call(&self) -> Result<pwasm_std::Vec<u8>, CallError> {
+		if !self.value.is_zero() {
+			return Err(CallError::Invalid);
+		}
+		static_call_with_output(gas_or_forwardable(self.gas)?, &self.address, self.input)
+	}
+
+	/// Runs the code of the callee in the context of the current account (`DELEGATECALL`),
+	/// returning its output
+	///
+	/// The callee sees the sender and value of the current call. Fails with
+	/// [`CallError::Invalid`] if a value is set, as delegate calls cannot transfer any.
+	///
+	/// [`CallError::Invalid`]: enum.CallError.html#variant.Invalid
+	pub fn delegate_call(&self) -> Result<pwasm_std::Vec<u8>, CallError> {
+		if !self.value.is_zero() {
+			return Err(CallError::Invalid);
 		}
+		call_code_with_output(gas_or_forwardable(self.gas)?, &self.address, self.input)
 	}
 }
 
+#[cfg(feature = "eip211")]
+/// Get length of the data returned by the last call, requires EIP-211.
+pub fn return_data_size() -> usize {
+	unsafe { external::return_data_length() as usize }
+}
+
+#[cfg(feature = "eip211")]
+/// Allocates and fetches the data returned by the last call, requires EIP-211.
+pub fn return_data() -> pwasm_std::Vec<u8> {
+	let mut data = pwasm_std::Vec::new();
+	data.resize(return_data_size(), 0);
+	if !data.is_empty() {
+		unsafe { external::fetch_return_data(data.as_mut_ptr()); }
+	}
+	data
+}
+
 /// Returns hash of the given block or H256::zero()
 ///
 /// Only works for 256 most recent blocks excluding current
@@ -263,6 +515,18 @@ pub fn block_hash(block_number: u64) -> H256 {
 	res
 }
 
+/// Like [`block_hash`], but returns `None` instead of zero for blocks whose hash is not available
+///
+/// [`block_hash`]: fn.block_hash.html
+pub fn try_block_hash(number: u64) -> Option<H256> {
+	let current = block_number();
+	if number >= current || current - number > 256 {
+		return None;
+	}
+	let hash = block_hash(number);
+	if hash.is_zero() { None } else { Some(hash) }
+}
+
 /// Get the current block’s beneficiary address (the current miner account address)
 pub fn coinbase() -> Address {
 	unsafe { fetch_address(|x| external::coinbase(x) ) }
@@ -300,6 +564,62 @@ pub fn gas_left() -> u64 {
 	unsafe { external::gasleft() as u64 }
 }
 
+#[cfg(feature = "kip6")]
+/// Get amount of gas that can be given to a call: all gas left but one 64th, like EIP-150
+///
+/// The runtime charges the gas given to a call upfront, so the 64th kept also pays for the call
+/// itself and the code running after it.
+pub fn forwardable_gas() -> u64 {
+	let left = gas_left();
+	left - left / 64
+}
+
+/// Gas given to a call: `gas` if set, otherwise all [`forwardable_gas`]
+///
+/// [`forwardable_gas`]: fn.forwardable_gas.html
+#[cfg(feature = "kip6")]
+pub(crate) fn gas_or_forwardable(gas: Option<u64>) -> Result<u64, CallError> {
+	Ok(gas.unwrap_or_else(forwardable_gas))
+}
+
+/// Gas given to a call: `gas`, which must be set as the gas left is unknown without KIP-6
+#[cfg(not(feature = "kip6"))]
+pub(crate) fn gas_or_forwardable(gas: Option<u64>) -> Result<u64, CallError> {
+	gas.ok_or(CallError::Invalid)
+}
+
+#[cfg(feature = "eip1344")]
+/// Get the chain ID (EIP-1344).
+///
+/// Corresponds to "CHAINID" opcode in EVM
+pub fn chain_id() -> u64 {
+	unsafe { external::chainid() as u64 }
+}
+
+#[cfg(feature = "eip3198")]
+/// Get the base fee per gas of the current block (EIP-1559).
+///
+/// Corresponds to "BASEFEE" opcode in EVM
+pub fn base_fee() -> U256 {
+	unsafe { fetch_u256(|x| external::basefee(x) ) }
+}
+
+#[cfg(feature = "gasprice")]
+/// Get the gas price of the current transaction.
+///
+/// Corresponds to "GASPRICE" opcode in EVM
+pub fn gas_price() -> U256 {
+	unsafe { fetch_u256(|x| external::gasprice(x) ) }
+}
+
+#[cfg(feature = "eip1884")]
+/// Get the balance of the current contract (EIP-1884).
+///
+/// Cheaper than `balance(&address())`. Corresponds to "SELFBALANCE" opcode in EVM
+pub fn self_balance() -> U256 {
+	unsafe { fetch_u256(|x| external::selfbalance(x) ) }
+}
+
 /// Get caller address
 ///
 /// This is the address of the account that is directly responsible for this execution.
@@ -328,31 +648,137 @@ pub fn address() -> Address {
 
 /// Creates log entry with given topics and data.
 ///
-/// There could be only up to 4 topics.
-///
-/// # Panics
+/// There could be only up to 4 topics, more traps the execution; use [`try_log`] when the
+/// number of topics is not known to fit. The [`events`] module builds logs checking this
+/// beforehand.
 ///
-/// If `topics` contains more than 4 elements then this function will trap.
+/// [`try_log`]: fn.try_log.html
+/// [`events`]: events/index.html
 pub fn log(topics: &[H256], data: &[u8]) {
-	unsafe { external::elog(topics.as_ptr() as *const u8, topics.len() as u32, data.as_ptr(), data.len() as u32); }
+	if topics.len() > 4 {
+		trap();
+	}
+	// topics are sent as consecutive 32-byte words, whatever the layout of `H256`
+	let mut topics_buf = [[0u8; 32]; 4];
+	for (topic, word) in topics.iter().zip(topics_buf.iter_mut()) {
+		*word = <[u8; 32]>::from(*topic);
+	}
+	unsafe { external::elog(topics_buf.as_ptr() as *const u8, topics.len() as u32, data.as_ptr(), data.len() as u32); }
+}
+
+/// Like [`log`], but fails instead of logging if more than 4 topics are given
+///
+/// [`log`]: fn.log.html
+pub fn try_log(topics: &[H256], data: &[u8]) -> Result<(), Error> {
+	if topics.len() > 4 {
+		return Err(Error);
+	}
+	log(topics, data);
+	Ok(())
 }
 
 /// Allocates and requests [`call`] arguments (input)
 ///
 /// Input data comes either with external transaction or from [`call`] input value.
+/// With the `input-range` feature, use [`InputReader`] to read only parts of it.
+///
+/// [`InputReader`]: struct.InputReader.html
 pub fn input() -> pwasm_std::Vec<u8> {
-	let len = unsafe { external::input_length() };
-
-	match len {
-		0 => pwasm_std::Vec::new(),
-		non_zero => {
-			let mut data = pwasm_std::Vec::with_capacity(non_zero as usize);
-			unsafe {
-				data.set_len(non_zero as usize);
-				external::fetch_input(data.as_mut_ptr());
-			}
-			data
+	let mut data = pwasm_std::Vec::new();
+	data.resize(unsafe { external::input_length() } as usize, 0);
+	if !data.is_empty() {
+		unsafe { external::fetch_input(data.as_mut_ptr()); }
+	}
+	data
+}
+
+#[cfg(feature = "input-range")]
+/// Reader of the [`call`] input, fetching it piece by piece
+///
+/// Lets decoders read the selector and arguments they need without copying the whole input.
+/// Reads are checked against the input length before reaching the runtime.
+///
+/// [`call`]: fn.call.html
+#[derive(Clone, Debug)]
+pub struct InputReader {
+	len: usize,
+	position: usize,
+}
+
+#[cfg(feature = "input-range")]
+impl InputReader {
+	/// Reader positioned at the start of the input
+	pub fn new() -> Self {
+		InputReader { len: unsafe { external::input_length() } as usize, position: 0 }
+	}
+
+	/// Length of the whole input
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Whether the input is empty
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Offset of the next byte to read
+	pub fn position(&self) -> usize {
+		self.position
+	}
+
+	/// Number of bytes left to read
+	pub fn remaining(&self) -> usize {
+		self.len - self.position
+	}
+
+	/// Moves to the given offset, at most the input length
+	pub fn seek(&mut self, position: usize) -> Result<(), Error> {
+		if position > self.len {
+			return Err(Error);
 		}
+		self.position = position;
+		Ok(())
+	}
+
+	/// Fills `dst` with the input bytes at `offset`, without moving the reader
+	///
+	/// Fails if the input is shorter than `offset + dst.len()`.
+	pub fn read_at(&self, offset: usize, dst: &mut [u8]) -> Result<(), Error> {
+		if offset > self.len || dst.len() > self.len - offset {
+			return Err(Error);
+		}
+		if !dst.is_empty() {
+			unsafe { external::fetch_input_range(offset as u32, dst.len() as u32, dst.as_mut_ptr()); }
+		}
+		Ok(())
+	}
+
+	/// Fills `dst` with the next input bytes
+	pub fn read(&mut self, dst: &mut [u8]) -> Result<(), Error> {
+		self.read_at(self.position, dst)?;
+		self.position += dst.len();
+		Ok(())
+	}
+
+	/// Reads the next 32-byte word
+	pub fn read_word(&mut self) -> Result<[u8; 32], Error> {
+		let mut word = [0u8; 32];
+		self.read(&mut word)?;
+		Ok(word)
+	}
+
+	/// 4-byte function selector at the start of the input, if any
+	pub fn selector(&self) -> Option<[u8; 4]> {
+		let mut selector = [0u8; 4];
+		self.read_at(0, &mut selector).ok().map(|_| selector)
+	}
+}
+
+#[cfg(feature = "input-range")]
+impl Default for InputReader {
+	fn default() -> Self {
+		InputReader::new()
 	}
 }
 
@@ -364,6 +790,58 @@ pub fn ret(data: &[u8]) -> ! {
 	unsafe { external::ret(data.as_ptr(), data.len() as u32); }
 }
 
+#[cfg(feature = "eip140")]
+/// Halts execution, reverting its state changes (EIP-140)
+///
+/// Remaining gas is refunded and `reason` is passed back to the caller, which sees the call fail
+/// with [`CallError::Reverted`].
+///
+/// [`CallError::Reverted`]: enum.CallError.html#variant.Reverted
+pub fn revert(reason: &[u8]) -> ! {
+	unsafe { external::revert(reason.as_ptr(), reason.len() as u32); }
+}
+
+#[cfg(not(feature = "eip140"))]
+/// Halts execution, reverting its state changes
+///
+/// The runtime cannot revert without EIP-140, so this traps: all gas is consumed and `reason`
+/// is not passed back to the caller.
+pub fn revert(_reason: &[u8]) -> ! {
+	trap()
+}
+
+/// Traps the execution, without the formatting of a panic message on wasm
+#[cfg(not(feature = "std"))]
+pub(crate) fn trap() -> ! {
+	unsafe { ::core::intrinsics::abort() }
+}
+
+/// Traps the execution, which the native externalities report as a trap
+#[cfg(feature = "std")]
+pub(crate) fn trap() -> ! {
+	panic!("trap")
+}
+
+/// Halts execution like [`revert`], with `reason` encoded as a Solidity `Error(string)`
+///
+/// Callers read it back with [`CallError::reason`].
+///
+/// [`revert`]: fn.revert.html
+/// [`CallError::reason`]: enum.CallError.html#method.reason
+pub fn revert_with_reason(reason: &str) -> ! {
+	let padded_len = (reason.len() + 31) / 32 * 32;
+	let mut data = pwasm_std::Vec::with_capacity(68 + padded_len);
+	data.extend_from_slice(&CallError::ERROR_SELECTOR);
+	let mut word = [0u8; 32];
+	U256::from(32u64).to_big_endian(&mut word);
+	data.extend_from_slice(&word);
+	U256::from(reason.len() as u64).to_big_endian(&mut word);
+	data.extend_from_slice(&word);
+	data.extend_from_slice(reason.as_bytes());
+	data.resize(68 + padded_len, 0);
+	revert(&data)
+}
+
 unsafe fn fetch_address<F>(f: F) -> Address where F: Fn(*mut u8) {
 	let mut res = Address::zero();
 	f(res.as_mut_ptr());
@@ -0,0 +1,22 @@
+//! Forwarding of the current call to another contract, for routers and facades, requires
+//! EIP-211 and KIP-6.
+
+use pwasm_std::types::Address;
+
+use ext::{self, CallError};
+
+/// Forwards the input and value of the current call to `target` with all
+/// [`forwardable_gas`](../fn.forwardable_gas.html) and returns its exact result.
+///
+/// Reverts with the same reason if `target` reverts.
+///
+/// # Panics
+///
+/// Traps if the call to `target` fails otherwise.
+pub fn forward(target: &Address) -> ! {
+	match ext::call_with_output(ext::forwardable_gas(), target, ext::value(), &ext::input()) {
+		Ok(result) => ext::ret(&result),
+		Err(CallError::Reverted(reason)) => ext::revert(&reason),
+		Err(_) => ext::trap(),
+	}
+}
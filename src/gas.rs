@@ -0,0 +1,134 @@
+//! In-contract gas measurement, requires KIP-6.
+
+use pwasm_std::types::{H256, U256};
+
+use ext::{self, Error};
+
+/// Topic of `GasUsed(bytes32,uint256)`, logged with the label of the meter as the second topic.
+pub const GAS_USED_TOPIC: [u8; 32] = [
+	0x51, 0xa8, 0xbb, 0xee, 0x3b, 0xb6, 0xd7, 0x04, 0x53, 0x2f, 0x7b, 0x52, 0x0e, 0x52, 0xf7, 0xb7,
+	0xdf, 0xa6, 0xa8, 0xce, 0x5d, 0x9d, 0xa8, 0x68, 0x82, 0x4c, 0xc8, 0x55, 0xaf, 0x27, 0xd0, 0x0d,
+];
+
+/// Largest amount of gas a subcall can get out of `available` under the EIP-150 rule, which
+/// keeps 1/64th of it for the caller.
+pub fn all_but_one_64th(available: u64) -> u64 {
+	available - available / 64
+}
+
+/// Snapshot of the remaining gas, for budgeting the rest of the execution.
+///
+/// ```ignore
+/// let meter = Meter::new();
+/// let output = CallBuilder::new(&target).gas(meter.forwardable(CLEANUP_GAS)).call();
+/// meter.require(CLEANUP_GAS)?;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Meter {
+	start: u64,
+}
+
+impl Meter {
+	/// Snapshots the remaining gas.
+	pub fn new() -> Self {
+		Meter { start: ext::gas_left() }
+	}
+
+	/// Gas consumed since the snapshot.
+	pub fn consumed(&self) -> u64 {
+		self.start.saturating_sub(ext::gas_left())
+	}
+
+	/// Gas remaining now.
+	pub fn remaining(&self) -> u64 {
+		ext::gas_left()
+	}
+
+	/// Fails unless at least `min_gas` remains.
+	pub fn require(&self, min_gas: u64) -> Result<(), Error> {
+		if self.remaining() < min_gas { Err(Error) } else { Ok(()) }
+	}
+
+	/// Gas to give to a subcall so that `reserve` gas is left once it returns, even if it
+	/// consumes all it gets.
+	pub fn forwardable(&self, reserve: u64) -> u64 {
+		all_but_one_64th(self.remaining().saturating_sub(reserve))
+	}
+}
+
+impl Default for Meter {
+	fn default() -> Self {
+		Meter::new()
+	}
+}
+
+/// Scope guard measuring the gas used from its creation until it is dropped.
+///
+/// When dropped, the meter logs the gas used if it has a label and reverts with the reason
+/// `gas budget exceeded` if it has a budget that was exceeded.
+///
+/// ```ignore
+/// let _meter = GasMeter::start().with_budget(50_000).with_label(label);
+/// expensive_operation();
+/// ```
+#[derive(Debug)]
+pub struct GasMeter {
+	start: u64,
+	budget: Option<u64>,
+	label: Option<H256>,
+}
+
+impl GasMeter {
+	/// Starts measuring.
+	pub fn start() -> Self {
+		GasMeter { start: ext::gas_left(), budget: None, label: None }
+	}
+
+	/// Reverts on [`check`](#method.check) or when dropped if more than `budget` gas was used.
+	pub fn with_budget(mut self, budget: u64) -> Self {
+		self.budget = Some(budget);
+		self
+	}
+
+	/// Logs `GasUsed(label, used)` when dropped.
+	pub fn with_label(mut self, label: H256) -> Self {
+		self.label = Some(label);
+		self
+	}
+
+	/// Gas used so far.
+	pub fn used(&self) -> u64 {
+		self.start.saturating_sub(ext::gas_left())
+	}
+
+	/// Reverts if the budget is already exceeded.
+	pub fn check(&self) {
+		self.enforce_budget(self.used());
+	}
+
+	fn enforce_budget(&self, used: u64) {
+		if let Some(budget) = self.budget {
+			if used > budget {
+				ext::revert_with_reason("gas budget exceeded");
+			}
+		}
+	}
+}
+
+impl Drop for GasMeter {
+	fn drop(&mut self) {
+		#[cfg(feature = "std")]
+		{
+			if ::std::thread::panicking() {
+				return;
+			}
+		}
+		let used = self.used();
+		if let Some(label) = self.label {
+			let mut data = [0u8; 32];
+			U256::from(used).to_big_endian(&mut data);
+			ext::log(&[H256::from(GAS_USED_TOPIC), label], &data);
+		}
+		self.enforce_budget(used);
+	}
+}
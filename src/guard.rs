@@ -0,0 +1,137 @@
+//! Protection against reentrant calls.
+//!
+//! A [`ReentrancyGuard`] marks a storage slot while the protected code runs, so that a call
+//! back into the contract during that time is rejected. With the `eip1153` feature the slot is
+//! in the transient storage, which is much cheaper to write. Unlike the native backend's
+//! reentrancy detection, which only reports reentrant calls in tests, the guard is enforced on
+//! chain.
+//!
+//! [`ret`] and [`suicide`] never return to drop an entered guard, which would then stay entered
+//! for good: halt after leaving the protected code, or with [`Entered::ret`], which releases
+//! the guard first. Reverts and traps discard the lock with the other state changes.
+//!
+//! ```ignore
+//! fn withdraw(amount: U256) {
+//! 	let balance = non_reentrant(|| {
+//! 		debit(sender(), amount);
+//! 		transfer(&sender(), amount).expect("transfer failed");
+//! 		balance_of(sender())
+//! 	});
+//! 	ret(&balance)
+//! }
+//! ```
+//!
+//! [`ReentrancyGuard`]: struct.ReentrancyGuard.html
+//! [`Entered::ret`]: struct.Entered.html#method.ret
+//! [`ret`]: ../fn.ret.html
+//! [`suicide`]: ../fn.suicide.html
+
+use pwasm_std::types::H256;
+
+use ext::{self, Error};
+
+#[cfg(not(feature = "eip1153"))]
+use storage::{read, write};
+#[cfg(feature = "eip1153")]
+use storage::{tread as read, twrite as write};
+
+/// Storage key of the default guard, `keccak256("pwasm-ethereum.reentrancy-guard")`.
+pub const DEFAULT_KEY: [u8; 32] = [
+	0x7d, 0x18, 0x01, 0x13, 0x38, 0x55, 0x75, 0xe3, 0x78, 0x01, 0xed, 0x31, 0x70, 0x61, 0x65, 0xa3,
+	0xeb, 0xd4, 0x32, 0x7e, 0x99, 0xbe, 0x62, 0xa5, 0xec, 0xfa, 0xa2, 0x7f, 0x52, 0xde, 0x9a, 0xa4,
+];
+
+const ENTERED: [u8; 32] = [
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+];
+
+/// Guard stored in a single slot of the current contract, transient with `eip1153`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReentrancyGuard {
+	key: H256,
+}
+
+impl Default for ReentrancyGuard {
+	fn default() -> Self {
+		ReentrancyGuard::new()
+	}
+}
+
+impl ReentrancyGuard {
+	/// Guard stored under [`DEFAULT_KEY`](constant.DEFAULT_KEY.html).
+	pub fn new() -> Self {
+		ReentrancyGuard::at(H256::from(DEFAULT_KEY))
+	}
+
+	/// Guard stored under `key`, for separate guards per group of functions.
+	pub fn at(key: H256) -> Self {
+		ReentrancyGuard { key: key }
+	}
+
+	/// Whether protected code is currently running.
+	pub fn is_entered(&self) -> bool {
+		read(&self.key) != [0u8; 32]
+	}
+
+	/// Marks the guard as entered until the returned value is dropped.
+	///
+	/// # Errors
+	///
+	/// If the guard is already entered, that is, on a reentrant call.
+	pub fn enter(&self) -> Result<Entered, Error> {
+		if self.is_entered() {
+			return Err(Error);
+		}
+		write(&self.key, &ENTERED);
+		Ok(Entered { key: self.key })
+	}
+
+	/// Runs `f` with the guard entered.
+	///
+	/// # Panics
+	///
+	/// Traps on a reentrant call.
+	pub fn run<F: FnOnce() -> R, R>(&self, f: F) -> R {
+		let _entered = match self.enter() {
+			Ok(entered) => entered,
+			Err(Error) => ext::trap(),
+		};
+		f()
+	}
+}
+
+/// Entered [`ReentrancyGuard`](struct.ReentrancyGuard.html), released when dropped.
+#[derive(Debug)]
+pub struct Entered {
+	key: H256,
+}
+
+impl Entered {
+	/// Releases the guard, then halts like [`ret`](../fn.ret.html).
+	pub fn ret(self, data: &[u8]) -> ! {
+		drop(self);
+		ext::ret(data)
+	}
+}
+
+impl Drop for Entered {
+	fn drop(&mut self) {
+		#[cfg(feature = "std")]
+		{
+			if ::std::thread::panicking() {
+				return;
+			}
+		}
+		write(&self.key, &[0u8; 32]);
+	}
+}
+
+/// Runs `f` under the default [`ReentrancyGuard`](struct.ReentrancyGuard.html).
+///
+/// # Panics
+///
+/// Traps on a reentrant call.
+pub fn non_reentrant<F: FnOnce() -> R, R>(f: F) -> R {
+	ReentrancyGuard::new().run(f)
+}
@@ -0,0 +1,72 @@
+//! Keccak-256 hashing and contract address derivation.
+//!
+//! Requires either the `keccak` feature, hashing in the runtime through its `keccak` extern,
+//! or the `tiny-keccak` feature, hashing in wasm for runtimes that do not provide it.
+
+use pwasm_std::types::{H256, Address};
+
+#[cfg(not(feature = "tiny-keccak"))]
+use backend::external::keccak;
+
+/// Keccak-256 hash of `data`.
+#[cfg(not(feature = "tiny-keccak"))]
+pub fn keccak256(data: &[u8]) -> H256 {
+	let mut hash = H256::zero();
+	unsafe {
+		keccak(data.as_ptr(), data.len() as u32, hash.as_mut_ptr());
+	}
+	hash
+}
+
+/// Keccak-256 hash of `data`.
+#[cfg(feature = "tiny-keccak")]
+pub fn keccak256(data: &[u8]) -> H256 {
+	H256::from(::tiny_keccak::keccak256(data))
+}
+
+fn address_of(hash: H256) -> Address {
+	let mut address = [0u8; 20];
+	address.copy_from_slice(&<[u8; 32]>::from(hash)[12..]);
+	Address::from(address)
+}
+
+/// Address of the contract created by `sender` with `CREATE` at the given account `nonce`,
+/// `keccak256(rlp([sender, nonce]))[12..]`.
+pub fn create_address(sender: &Address, nonce: u64) -> Address {
+	let mut rlp = [0u8; 1 + 21 + 9];
+	rlp[1] = 0x80 + 20;
+	rlp[2..22].copy_from_slice(&<[u8; 20]>::from(*sender));
+	let len = match nonce {
+		0 => {
+			rlp[22] = 0x80;
+			23
+		},
+		1...0x7f => {
+			rlp[22] = nonce as u8;
+			23
+		},
+		_ => {
+			let bytes = 8 - nonce.leading_zeros() as usize / 8;
+			rlp[22] = 0x80 + bytes as u8;
+			for i in 0..bytes {
+				rlp[23 + i] = (nonce >> (8 * (bytes - 1 - i))) as u8;
+			}
+			23 + bytes
+		},
+	};
+	rlp[0] = 0xc0 + (len - 1) as u8;
+	address_of(keccak256(&rlp[..len]))
+}
+
+/// Address of the contract created by `sender` with `CREATE2` (EIP-1014),
+/// `keccak256(0xff || sender || salt || code_hash)[12..]`.
+///
+/// `code_hash` is the [`keccak256`](fn.keccak256.html) of the creation code.
+pub fn create2_address(sender: &Address, salt: &H256, code_hash: &H256) -> Address {
+	let mut preimage = [0u8; 1 + 20 + 32 + 32];
+	preimage[0] = 0xff;
+	preimage[1..21].copy_from_slice(&<[u8; 20]>::from(*sender));
+	preimage[21..53].copy_from_slice(&<[u8; 32]>::from(*salt));
+	preimage[53..].copy_from_slice(&<[u8; 32]>::from(*code_hash));
+	address_of(keccak256(&preimage))
+}
@@ -0,0 +1,184 @@
+//! Interface identification (ERC-165) and semantic versioning of contracts.
+//!
+//! A contract declares the interfaces it implements and its version in storage and answers
+//! `supportsInterface(bytes4)` and `interfaceVersion()` through [`handle`](fn.handle.html).
+//! Callers check a target with [`require_compatible`](fn.require_compatible.html) before
+//! relying on its ABI.
+
+use pwasm_std::types::{H256, Address};
+
+use ext::{self, Error};
+
+/// Selector of `supportsInterface(bytes4)`, also the ERC-165 interface id.
+pub const SUPPORTS_INTERFACE: [u8; 4] = [0x01, 0xff, 0xc9, 0xa7];
+/// Selector of `interfaceVersion()`.
+pub const INTERFACE_VERSION: [u8; 4] = [0x1d, 0x8f, 0xfa, 0x4d];
+/// Interface id that no contract may support, as required by ERC-165.
+pub const INVALID_INTERFACE: [u8; 4] = [0xff; 4];
+/// Gas given to the queries of [`implements`] and [`version_of`], the limit ERC-165 sets for
+/// `supportsInterface(bytes4)`.
+///
+/// [`implements`]: fn.implements.html
+/// [`version_of`]: fn.version_of.html
+pub const QUERY_GAS: u64 = 30_000;
+
+/// Storage key of the version.
+const VERSION_KEY: [u8; 32] = [0xfe; 32];
+
+/// Storage key of the flag of interface `id`: `0xfd` bytes followed by the id.
+fn interface_key(id: [u8; 4]) -> H256 {
+	let mut key = [0xfd; 32];
+	key[28..].copy_from_slice(&id);
+	H256::from(key)
+}
+
+/// Semantic version of a contract interface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Version {
+	/// Incremented on incompatible changes.
+	pub major: u32,
+	/// Incremented on backwards compatible additions.
+	pub minor: u32,
+	/// Incremented on fixes.
+	pub patch: u32,
+}
+
+impl Version {
+	/// Creates a version.
+	pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+		Version { major: major, minor: minor, patch: patch }
+	}
+
+	/// Whether a contract at this version can be used by callers written against `required`.
+	pub fn is_compatible(&self, required: &Version) -> bool {
+		self.major == required.major && self >= required
+	}
+
+	/// Version as a 32-byte word, with `major`, `minor` and `patch` in its last 12 bytes.
+	pub fn to_word(&self) -> [u8; 32] {
+		let mut word = [0u8; 32];
+		for (i, part) in [self.major, self.minor, self.patch].iter().enumerate() {
+			word[20 + 4 * i] = (part >> 24) as u8;
+			word[21 + 4 * i] = (part >> 16) as u8;
+			word[22 + 4 * i] = (part >> 8) as u8;
+			word[23 + 4 * i] = *part as u8;
+		}
+		word
+	}
+
+	/// Version from a 32-byte word in the layout of [`to_word`](#method.to_word).
+	pub fn from_word(word: &[u8; 32]) -> Self {
+		let part = |i: usize| {
+			(word[20 + 4 * i] as u32) << 24
+				| (word[21 + 4 * i] as u32) << 16
+				| (word[22 + 4 * i] as u32) << 8
+				| word[23 + 4 * i] as u32
+		};
+		Version::new(part(0), part(1), part(2))
+	}
+}
+
+/// Stores the version of the current contract.
+pub fn set_version(version: &Version) {
+	::write(&H256::from(VERSION_KEY), &version.to_word());
+}
+
+/// Version of the current contract.
+pub fn version() -> Version {
+	Version::from_word(&::read(&H256::from(VERSION_KEY)))
+}
+
+/// Declares that the current contract implements interface `id`.
+///
+/// ERC-165 itself is always supported and [`INVALID_INTERFACE`](constant.INVALID_INTERFACE.html)
+/// never is, so registering either has no effect.
+pub fn register_interface(id: [u8; 4]) {
+	let mut flag = [0u8; 32];
+	flag[31] = 1;
+	::write(&interface_key(id), &flag);
+}
+
+/// Whether the current contract implements interface `id`.
+pub fn supports_interface(id: [u8; 4]) -> bool {
+	match id {
+		SUPPORTS_INTERFACE => true,
+		INVALID_INTERFACE => false,
+		id => ::read(&interface_key(id))[31] == 1,
+	}
+}
+
+/// Answers `supportsInterface(bytes4)` and `interfaceVersion()` calls, returning on any other input.
+pub fn handle(input: &[u8]) {
+	if input.len() < 4 {
+		return;
+	}
+	match [input[0], input[1], input[2], input[3]] {
+		SUPPORTS_INTERFACE if input.len() >= 36 => {
+			let mut result = [0u8; 32];
+			result[31] = supports_interface([input[4], input[5], input[6], input[7]]) as u8;
+			ext::ret(&result)
+		},
+		INTERFACE_VERSION => ext::ret(&version().to_word()),
+		_ => {},
+	}
+}
+
+/// Whether the contract at `target` implements interface `id`, as queried by ERC-165.
+pub fn implements(target: &Address, id: [u8; 4]) -> bool {
+	let query = |id: [u8; 4]| {
+		let mut input = [0u8; 36];
+		input[..4].copy_from_slice(&SUPPORTS_INTERFACE);
+		input[4..8].copy_from_slice(&id);
+		let mut result = [0u8; 32];
+		ext::static_call(QUERY_GAS, target, &input, &mut result).is_ok() && result[31] == 1
+	};
+	query(SUPPORTS_INTERFACE) && !query(INVALID_INTERFACE) && query(id)
+}
+
+/// Version of the contract at `target`.
+///
+/// # Errors
+///
+/// Returns [`Error`](../struct.Error.html) if the target does not answer `interfaceVersion()`,
+/// which calls to accounts without code succeed without doing. Without the `eip211` and
+/// `extcode` features this cannot be detected, so a zero version is also an error.
+pub fn version_of(target: &Address) -> Result<Version, Error> {
+	let mut result = [0u8; 32];
+	ext::static_call(QUERY_GAS, target, &INTERFACE_VERSION, &mut result)?;
+	if !answered(target, &result) {
+		return Err(Error);
+	}
+	Ok(Version::from_word(&result))
+}
+
+/// Whether a successful query of `target` returned a word.
+#[cfg(feature = "eip211")]
+fn answered(_target: &Address, _result: &[u8; 32]) -> bool {
+	ext::return_data_size() >= 32
+}
+
+#[cfg(all(feature = "extcode", not(feature = "eip211")))]
+fn answered(target: &Address, _result: &[u8; 32]) -> bool {
+	ext::code_size(target) != 0
+}
+
+#[cfg(not(any(feature = "eip211", feature = "extcode")))]
+fn answered(_target: &Address, result: &[u8; 32]) -> bool {
+	*result != [0u8; 32]
+}
+
+/// Checks that the contract at `target` can be used by a caller written against `min_version`.
+///
+/// # Panics
+///
+/// Reverts with a reason if the target reports no version or an incompatible one.
+pub fn require_compatible(target: &Address, min_version: &Version) -> Version {
+	let version = match version_of(target) {
+		Ok(version) => version,
+		Err(Error) => ext::revert_with_reason("interface: no version"),
+	};
+	if !version.is_compatible(min_version) {
+		ext::revert_with_reason("interface: incompatible version");
+	}
+	version
+}
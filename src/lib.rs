@@ -1,11 +1,65 @@
 //! Externs library to interact with Ethereum-like network
 
 #![cfg_attr(not(feature="std"), no_std)]
+#![cfg_attr(not(feature="std"), feature(core_intrinsics))]
 
 extern crate pwasm_std;
+#[cfg(feature = "bench")]
+extern crate criterion;
+#[cfg(feature = "wallet")]
+extern crate secp256k1;
+#[cfg(feature = "tiny-keccak")]
+extern crate tiny_keccak;
+#[cfg(feature = "mock-precompiles")]
+extern crate sha2;
+#[cfg(feature = "mock-precompiles")]
+extern crate ripemd160;
+#[cfg(feature = "mock-precompiles")]
+extern crate num_bigint;
+#[cfg(feature = "mock-precompiles")]
+extern crate substrate_bn as bn;
 
+mod backend;
 mod ext;
 mod storage;
 
+pub mod abi;
+#[cfg(feature = "alloc-stats")]
+pub mod alloc_stats;
+pub mod batch;
+pub mod breaker;
+pub mod dispatch;
+#[cfg(any(feature = "keccak", feature = "tiny-keccak"))]
+pub mod eip712;
+#[cfg(feature = "std")]
+pub mod estimate;
+pub mod events;
+#[cfg(all(feature = "eip211", feature = "kip6"))]
+pub mod forward;
+#[cfg(feature = "kip6")]
+pub mod gas;
+pub mod guard;
+#[cfg(any(feature = "keccak", feature = "tiny-keccak"))]
+pub mod hash;
+pub mod interface;
+pub mod library;
+#[cfg(feature = "native")]
+pub mod native;
+pub mod precompiles;
+pub mod proxy;
+#[cfg(any(feature = "keccak", feature = "tiny-keccak"))]
+pub mod registry;
+#[cfg(any(feature = "keccak", feature = "tiny-keccak"))]
+pub mod signed;
+#[cfg(feature = "native")]
+pub mod test;
+#[cfg(any(feature = "keccak", feature = "tiny-keccak"))]
+pub mod typed_storage;
+#[cfg(feature = "std")]
+pub mod validate;
+#[cfg(feature = "wallet")]
+pub mod wallet;
+
 pub use ext::*;
 pub use storage::*;
+pub use pwasm_std::types::{H256, U256, Address};
@@ -0,0 +1,129 @@
+//! Library contracts, whose code runs in the context of the calling contract.
+//!
+//! A library is linked by address, either given directly or resolved from a storage slot set
+//! at deploy time, and called with `DELEGATECALL` semantics (see [`call_code`]). Typed stubs
+//! for its functions are declared with the [`library!`] macro.
+//!
+//! [`call_code`]: ../fn.call_code.html
+//! [`library!`]: ../macro.library.html
+
+use pwasm_std::{
+	self,
+	types::{H256, Address}
+};
+
+use ext::{call_code, gas_or_forwardable, CallError};
+
+/// Linked library contract.
+///
+/// Calls are given all [`forwardable_gas`] unless limited with [`with_gas`]. Without KIP-6,
+/// the gas must be set.
+///
+/// [`forwardable_gas`]: ../fn.forwardable_gas.html
+/// [`with_gas`]: #method.with_gas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Library {
+	address: Address,
+	gas: Option<u64>,
+}
+
+impl Library {
+	/// Library deployed at `address`.
+	pub fn at(address: Address) -> Self {
+		Library { address: address, gas: None }
+	}
+
+	/// Library whose address is stored in the contract storage under `key`.
+	pub fn from_storage(key: &H256) -> Self {
+		let value = ::read(key);
+		let mut address = [0u8; 20];
+		address.copy_from_slice(&value[12..]);
+		Library::at(Address::from(address))
+	}
+
+	/// Same library, with calls limited to `gas`.
+	pub fn with_gas(self, gas: u64) -> Self {
+		Library { gas: Some(gas), ..self }
+	}
+
+	/// Address of the library.
+	pub fn address(&self) -> Address {
+		self.address
+	}
+
+	/// Stores the library address in the contract storage under `key`, for [`from_storage`](#method.from_storage).
+	pub fn link(&self, key: &H256) {
+		let mut value = [0u8; 32];
+		value[12..].copy_from_slice(&<[u8; 20]>::from(self.address));
+		::write(key, &value);
+	}
+
+	/// Calls the library with raw `input`, filling `result` with the returned data.
+	pub fn call(&self, input: &[u8], result: &mut [u8]) -> Result<(), CallError> {
+		call_code(gas_or_forwardable(self.gas)?, &self.address, input, result)
+	}
+
+	/// Calls the library function `selector` with 32-byte word arguments, returning the first returned word.
+	pub fn invoke(&self, selector: [u8; 4], args: &[[u8; 32]]) -> Result<[u8; 32], CallError> {
+		let mut input = pwasm_std::Vec::with_capacity(4 + 32 * args.len());
+		input.extend_from_slice(&selector);
+		for arg in args {
+			input.extend_from_slice(arg);
+		}
+		let mut result = [0u8; 32];
+		self.call(&input, &mut result)?;
+		Ok(result)
+	}
+}
+
+/// Declares a typed stub of a library contract.
+///
+/// Each function is given its 4-byte selector, takes 32-byte word arguments and returns the
+/// first word returned by the library.
+///
+/// ```ignore
+/// library! {
+/// 	/// Safe arithmetic library.
+/// 	pub struct SafeMath {
+/// 		/// Sum of `a` and `b`, trapping on overflow.
+/// 		fn add(a, b) = [0x77, 0x16, 0x02, 0xf7];
+/// 	}
+/// }
+///
+/// let sum = SafeMath::at(library_address).add(a, b)?;
+/// ```
+#[macro_export]
+macro_rules! library {
+	(
+		$(#[$attr:meta])*
+		pub struct $name:ident {
+			$(
+				$(#[$fn_attr:meta])*
+				fn $method:ident($($arg:ident),*) = $selector:expr;
+			)*
+		}
+	) => {
+		$(#[$attr])*
+		#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+		pub struct $name(pub $crate::library::Library);
+
+		impl $name {
+			/// Stub of the library deployed at `address`.
+			pub fn at(address: $crate::Address) -> Self {
+				$name($crate::library::Library::at(address))
+			}
+
+			/// Stub of the library whose address is stored under `key`.
+			pub fn from_storage(key: &$crate::H256) -> Self {
+				$name($crate::library::Library::from_storage(key))
+			}
+
+			$(
+				$(#[$fn_attr])*
+				pub fn $method(&self, $($arg: [u8; 32]),*) -> Result<[u8; 32], $crate::CallError> {
+					self.0.invoke($selector, &[$($arg),*])
+				}
+			)*
+		}
+	};
+}
@@ -0,0 +1,69 @@
+//! Benchmarking on top of the native backend with [criterion](https://docs.rs/criterion).
+//!
+//! A [`Fixture`] captures a fully configured state once; every iteration restores it
+//! before running the measured code, and the restore is excluded from the measurement.
+//!
+//! ```ignore
+//! #[macro_use]
+//! extern crate criterion;
+//! extern crate pwasm_ethereum;
+//!
+//! use criterion::Criterion;
+//! use pwasm_ethereum::native::{self, bench::Fixture};
+//!
+//! fn transfer(c: &mut Criterion) {
+//!     native::with_externalities(|ext| ext.register(token_address(), token::call));
+//!     let fixture = Fixture::capture();
+//!     fixture.bench_transact(c, "transfer", token_address(), 0.into(), transfer_input());
+//! }
+//!
+//! criterion_group!(benches, transfer);
+//! criterion_main!(benches);
+//! ```
+//!
+//! [`Fixture`]: struct.Fixture.html
+
+use criterion::Criterion;
+use pwasm_std::types::{U256, Address};
+
+use super::{coverage, execute, transact, with_externalities, Externalities};
+
+/// Reusable initial state for benchmark iterations.
+#[derive(Clone)]
+pub struct Fixture {
+	state: Externalities,
+}
+
+impl Fixture {
+	/// Creates a fixture from the given state.
+	pub fn new(state: Externalities) -> Self {
+		Fixture { state: state }
+	}
+
+	/// Creates a fixture from the current state of this thread.
+	pub fn capture() -> Self {
+		Fixture::new(with_externalities(|ext| ext.clone()))
+	}
+
+	/// Makes the captured state the current state of this thread.
+	pub fn restore(&self) {
+		with_externalities(|ext| ext.clone_from(&self.state));
+	}
+
+	/// Benchmarks `f` run by [`execute`](../fn.execute.html) on a fresh copy of the fixture.
+	pub fn bench_execute<F>(&self, c: &mut Criterion, name: &str, f: F) where F: Fn() + 'static {
+		let fixture = self.clone();
+		coverage::set_enabled(false);
+		c.bench_function(name, move |b| b.iter_with_setup(|| fixture.restore(), |_| execute(&f)));
+	}
+
+	/// Benchmarks a [`transact`](../fn.transact.html) call on a fresh copy of the fixture.
+	pub fn bench_transact(&self, c: &mut Criterion, name: &str, address: Address, value: U256, input: Vec<u8>) {
+		let fixture = self.clone();
+		coverage::set_enabled(false);
+		c.bench_function(name, move |b| b.iter_with_setup(
+			|| fixture.restore(),
+			|_| transact(address, value, input.clone()),
+		));
+	}
+}
@@ -0,0 +1,169 @@
+//! Extern and selector coverage.
+//!
+//! Coverage is accumulated process-wide, so that a whole test suite running on several threads
+//! contributes to a single report. Inspect it with [`collect`], typically from the last test or
+//! a custom test harness.
+//!
+//! [`collect`]: fn.collect.html
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Mutex, Once, ONCE_INIT};
+use std::sync::atomic::{AtomicBool, Ordering, ATOMIC_BOOL_INIT};
+
+use pwasm_std::types::Address;
+
+/// Names of all externs the crate can import from the runtime.
+pub const EXTERNS: &[&str] = &[
+	"ccall",
+	"dcall",
+	"scall",
+	"blockhash",
+	"balance",
+	"extcodesize",
+	"extcodecopy",
+	"extcodehash",
+	"coinbase",
+	"timestamp",
+	"blocknumber",
+	"difficulty",
+	"gaslimit",
+	"gasleft",
+	"chainid",
+	"basefee",
+	"gasprice",
+	"selfbalance",
+	"sender",
+	"address",
+	"value",
+	"origin",
+	"elog",
+	"create",
+	"create2",
+	"gcreate",
+	"gcreate2",
+	"suicide",
+	"ret",
+	"revert",
+	"input_length",
+	"fetch_input",
+	"fetch_input_range",
+	"return_data_length",
+	"fetch_return_data",
+	"storage_read",
+	"storage_write",
+	"storage_read_multi",
+	"storage_write_multi",
+	"transient_storage_read",
+	"transient_storage_write",
+];
+
+/// Coverage report.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Coverage {
+	/// Number of invocations per extern.
+	pub externs: BTreeMap<&'static str, u64>,
+	/// Number of calls per 4-byte input selector, per called code address.
+	pub selectors: BTreeMap<Address, BTreeMap<[u8; 4], u64>>,
+}
+
+impl Coverage {
+	/// Externs from [`EXTERNS`] that were never invoked.
+	///
+	/// [`EXTERNS`]: constant.EXTERNS.html
+	pub fn unused_externs(&self) -> Vec<&'static str> {
+		EXTERNS.iter().cloned().filter(|name| !self.externs.contains_key(name)).collect()
+	}
+
+	/// Whether `selector` was called on `address`.
+	pub fn is_selector_covered(&self, address: &Address, selector: [u8; 4]) -> bool {
+		self.selectors.get(address).map_or(false, |s| s.contains_key(&selector))
+	}
+
+	/// Selectors from `expected` that were never called on `address`.
+	pub fn uncovered_selectors(&self, address: &Address, expected: &[[u8; 4]]) -> Vec<[u8; 4]> {
+		expected.iter().cloned().filter(|s| !self.is_selector_covered(address, *s)).collect()
+	}
+}
+
+impl fmt::Display for Coverage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "externs: {}/{} used", self.externs.len(), EXTERNS.len())?;
+		for (name, count) in &self.externs {
+			writeln!(f, "  {:<14} {}", name, count)?;
+		}
+		let unused = self.unused_externs();
+		if !unused.is_empty() {
+			writeln!(f, "unused externs: {}", unused.join(", "))?;
+		}
+		writeln!(f, "selectors:")?;
+		for (address, selectors) in &self.selectors {
+			f.write_str("  0x")?;
+			for byte in <[u8; 20]>::from(*address).iter() {
+				write!(f, "{:02x}", byte)?;
+			}
+			f.write_str("\n")?;
+			for (selector, count) in selectors {
+				writeln!(f, "    0x{:02x}{:02x}{:02x}{:02x} {}", selector[0], selector[1], selector[2], selector[3], count)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+static DISABLED: AtomicBool = ATOMIC_BOOL_INIT;
+
+fn global() -> &'static Mutex<Coverage> {
+	static INIT: Once = ONCE_INIT;
+	static mut COVERAGE: Option<Mutex<Coverage>> = None;
+	unsafe {
+		INIT.call_once(|| COVERAGE = Some(Mutex::new(Coverage::default())));
+		COVERAGE.as_ref().expect("initialized above; qed")
+	}
+}
+
+fn with_coverage<F: FnOnce(&mut Coverage)>(f: F) {
+	// a test panicking while holding the lock must not hide the coverage of the others
+	let mut coverage = match global().lock() {
+		Ok(guard) => guard,
+		Err(poisoned) => poisoned.into_inner(),
+	};
+	f(&mut coverage)
+}
+
+/// Returns coverage accumulated so far by all threads.
+pub fn collect() -> Coverage {
+	let mut result = Coverage::default();
+	with_coverage(|coverage| result = coverage.clone());
+	result
+}
+
+/// Enables or disables recording; it is enabled by default.
+///
+/// Recording takes a process-wide lock on every extern invocation, which benchmarks may
+/// want to avoid.
+pub fn set_enabled(enabled: bool) {
+	DISABLED.store(!enabled, Ordering::Relaxed);
+}
+
+/// Clears accumulated coverage.
+pub fn reset() {
+	with_coverage(|coverage| *coverage = Coverage::default());
+}
+
+pub(crate) fn record_extern(name: &'static str) {
+	if DISABLED.load(Ordering::Relaxed) {
+		return;
+	}
+	with_coverage(|coverage| *coverage.externs.entry(name).or_insert(0) += 1);
+}
+
+pub(crate) fn record_call(address: Address, input: &[u8]) {
+	if input.len() < 4 || DISABLED.load(Ordering::Relaxed) {
+		return;
+	}
+	let selector = [input[0], input[1], input[2], input[3]];
+	with_coverage(|coverage| {
+		*coverage.selectors.entry(address).or_insert_with(BTreeMap::new).entry(selector).or_insert(0) += 1;
+	});
+}
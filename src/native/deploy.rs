@@ -0,0 +1,105 @@
+//! Deployment of native contracts.
+
+use std::rc::Rc;
+
+use pwasm_std::types::{H256, U256, Address};
+
+use registry::Resolver;
+use Error;
+
+use super::{run, transact, with_externalities, CallKind, CallTrace, Entry, Frame, Log, Outcome};
+
+/// Native counterpart of a compiled contract: its `deploy` and `call` exports.
+#[derive(Clone)]
+pub struct Code {
+	/// Constructor, run once on deployment.
+	pub deploy: Entry,
+	/// Runtime entry point, run on every call.
+	pub call: Entry,
+}
+
+impl Code {
+	/// Creates code from the constructor and the runtime entry point.
+	pub fn new<D, C>(deploy: D, call: C) -> Self where D: Fn() + 'static, C: Fn() + 'static {
+		Code { deploy: Rc::new(deploy), call: Rc::new(call) }
+	}
+}
+
+/// Handle to a deployed contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Contract {
+	address: Address,
+}
+
+impl Contract {
+	/// Handle to a contract already deployed at `address`.
+	pub fn at(address: Address) -> Self {
+		Contract { address: address }
+	}
+
+	/// Handle to the contract registered under `name`, looked up through `resolver`.
+	pub fn resolve(resolver: &mut Resolver, name: &H256) -> Result<Self, Error> {
+		resolver.resolve(name).map(Contract::at)
+	}
+
+	/// Address of the contract.
+	pub fn address(&self) -> Address {
+		self.address
+	}
+
+	/// Calls the contract from the transaction origin.
+	pub fn call(&self, input: Vec<u8>) -> Outcome {
+		transact(self.address, U256::zero(), input)
+	}
+
+	/// Calls the contract from the transaction origin, sending `value` along.
+	pub fn call_with_value(&self, value: U256, input: Vec<u8>) -> Outcome {
+		transact(self.address, value, input)
+	}
+
+	/// Balance of the contract.
+	pub fn balance(&self) -> U256 {
+		with_externalities(|ext| ext.balance(&self.address))
+	}
+
+	/// Storage value of the contract.
+	pub fn storage_at(&self, key: &H256) -> [u8; 32] {
+		with_externalities(|ext| ext.storage_at(&self.address, key))
+	}
+
+	/// Logs emitted by the contract so far.
+	pub fn logs(&self) -> Vec<Log> {
+		with_externalities(|ext| ext.logs.iter().filter(|log| log.address == self.address).cloned().collect())
+	}
+}
+
+/// Deploys `code` from the transaction origin.
+///
+/// The constructor runs with `constructor_args` as input and `value` as endowment. On success
+/// the contract gets the next deterministic address, its runtime entry point is registered there
+/// and the data returned by the constructor is stored as its code bytes. Otherwise the
+/// constructor outcome is returned.
+pub fn deploy_contract(code: Code, constructor_args: Vec<u8>, value: U256) -> Result<Contract, Outcome> {
+	let frame = with_externalities(|ext| Frame {
+		address: ext.next_address(0xc0),
+		sender: ext.origin,
+		value: value,
+		input: constructor_args,
+		gas: ext.frame().gas,
+		is_static: false,
+	});
+	let address = frame.address;
+	let trace = CallTrace::new(CallKind::Create, frame.sender, address, value, frame.gas, frame.input.clone());
+	let Code { deploy, call } = code;
+	match run(frame, true, trace, move || deploy()).0 {
+		Outcome::Return(data) => {
+			with_externalities(|ext| {
+				let account = ext.account_mut(address);
+				account.code = data;
+				account.entry = Some(call);
+			});
+			Ok(Contract::at(address))
+		},
+		outcome => Err(outcome),
+	}
+}
@@ -0,0 +1,118 @@
+//! Harness for differential testing against a reference EVM.
+//!
+//! A scenario is a list of [`Step`]s run both by the native backend, through [`transact`], and
+//! by a [`Reference`] implementation, usually an EVM executing the Solidity original of the
+//! ported contract. After every step the outcome, the return or revert data, the logs and the
+//! storage of the called account must agree.
+//!
+//! This module is only the harness: no EVM is bundled, so tests implement [`Reference`] over
+//! the one they already depend on.
+//!
+//! [`Step`]: struct.Step.html
+//! [`transact`]: fn.transact.html
+//! [`Reference`]: trait.Reference.html
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use pwasm_std::types::{H256, U256, Address};
+
+use super::{transact, with_externalities, Log, Outcome};
+
+/// Top-level call of a scenario.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+	/// Called account, at the same address in both backends.
+	pub address: Address,
+	/// Value sent.
+	pub value: U256,
+	/// Call input.
+	pub input: Vec<u8>,
+}
+
+/// Observable effects of a step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+	/// Whether the call succeeded.
+	pub success: bool,
+	/// Return data, or revert data of failed calls; empty for traps.
+	pub data: Vec<u8>,
+	/// Logs emitted, in order.
+	pub logs: Vec<Log>,
+	/// Non-zero storage of the called account after the call.
+	pub storage: BTreeMap<H256, [u8; 32]>,
+}
+
+/// Backend the native execution is compared with.
+pub trait Reference {
+	/// Runs `step` on top of the effects of the previous steps.
+	fn run(&mut self, step: &Step) -> Observation;
+}
+
+/// First difference found between the native backend and the reference.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mismatch {
+	/// Index of the step.
+	pub step: usize,
+	/// Differing part of the observation: `success`, `data`, `logs` or `storage`.
+	pub field: &'static str,
+	/// Observation of the native backend.
+	pub native: Observation,
+	/// Observation of the reference.
+	pub reference: Observation,
+}
+
+impl fmt::Display for Mismatch {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"step {}: {} differs\nnative: {:?}\nreference: {:?}",
+			self.step, self.field, self.native, self.reference
+		)
+	}
+}
+
+/// Runs `step` through [`transact`](fn.transact.html), observing its effects.
+pub fn observe(step: &Step) -> Observation {
+	let logs = with_externalities(|ext| ext.logs.len());
+	let outcome = transact(step.address, step.value, step.input.clone());
+	with_externalities(|ext| Observation {
+		success: outcome.is_success(),
+		data: match outcome {
+			Outcome::Trap(_) => Vec::new(),
+			ref outcome => outcome.data().to_vec(),
+		},
+		logs: ext.logs[logs..].to_vec(),
+		storage: ext.accounts.get(&step.address)
+			.map(|account| account.storage.iter()
+				.filter(|&(_, value)| *value != [0u8; 32])
+				.map(|(key, value)| (*key, *value))
+				.collect())
+			.unwrap_or_default(),
+	})
+}
+
+/// Runs `steps` in both backends, stopping at the first mismatch.
+///
+/// # Errors
+///
+/// The first observation of the native backend which differs from the reference.
+pub fn compare<R: Reference>(steps: &[Step], reference: &mut R) -> Result<(), Mismatch> {
+	for (i, step) in steps.iter().enumerate() {
+		let native = observe(step);
+		let expected = reference.run(step);
+		let field = if native.success != expected.success {
+			"success"
+		} else if native.data != expected.data {
+			"data"
+		} else if native.logs != expected.logs {
+			"logs"
+		} else if native.storage != expected.storage {
+			"storage"
+		} else {
+			continue;
+		};
+		return Err(Mismatch { step: i, field: field, native: native, reference: expected });
+	}
+	Ok(())
+}
@@ -0,0 +1,178 @@
+//! Gas schedules of the native gas simulation.
+//!
+//! Only host operations are metered: wasm instructions run natively and are free.
+
+/// Gas prices of host operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GasSchedule {
+	/// Name of the schedule.
+	pub name: String,
+	/// Environment getters (`sender`, `timestamp`, `gasleft`, ...).
+	pub base: u64,
+	/// Copying input, per started 32-byte word.
+	pub copy_word: u64,
+	/// Storage read.
+	pub sload: u64,
+	/// Storage write of a non-zero value into an empty slot.
+	pub sstore_set: u64,
+	/// Any other storage write.
+	pub sstore_reset: u64,
+	/// Balance of an account.
+	pub balance: u64,
+	/// Balance of the current account (EIP-1884).
+	pub self_balance: u64,
+	/// Size, copy or hash of the code of an account.
+	pub extcode: u64,
+	/// Hash of a recent block.
+	pub blockhash: u64,
+	/// Base cost of a message call.
+	pub call: u64,
+	/// Additional cost of a call transferring value.
+	pub call_value: u64,
+	/// Gas given to the callee on top of the forwarded gas when value is transferred.
+	pub call_stipend: u64,
+	/// Whether calls asking for more than all but 1/64th of the remaining gas get only that
+	/// (EIP-150), instead of running out of gas.
+	pub call_all_but_one_64th: bool,
+	/// Transient storage read or write (EIP-1153).
+	pub transient: u64,
+	/// Contract creation.
+	pub create: u64,
+	/// Base cost of a log.
+	pub log: u64,
+	/// Cost per log topic.
+	pub log_topic: u64,
+	/// Cost per byte of log data.
+	pub log_data: u64,
+	/// Account destruction.
+	pub suicide: u64,
+	/// Whether storage and account accesses are priced by warmth (EIP-2929).
+	///
+	/// When set, `cold_sload`, `cold_account_access` and `warm_access` replace `sload`,
+	/// `balance`, `extcode` and `call`, and a cold storage write costs `cold_sload` on top of its price.
+	pub access_lists: bool,
+	/// First access to a storage slot in a transaction.
+	pub cold_sload: u64,
+	/// First access to an account in a transaction.
+	pub cold_account_access: u64,
+	/// Any later access to a storage slot or an account.
+	pub warm_access: u64,
+}
+
+impl GasSchedule {
+	/// Frontier prices.
+	pub fn frontier() -> Self {
+		GasSchedule {
+			name: "frontier".into(),
+			base: 2,
+			copy_word: 3,
+			sload: 50,
+			sstore_set: 20000,
+			sstore_reset: 5000,
+			balance: 20,
+			self_balance: 5,
+			extcode: 20,
+			blockhash: 20,
+			call: 40,
+			call_value: 9000,
+			call_stipend: 2300,
+			call_all_but_one_64th: false,
+			transient: 100,
+			create: 32000,
+			log: 375,
+			log_topic: 375,
+			log_data: 8,
+			suicide: 0,
+			access_lists: false,
+			cold_sload: 0,
+			cold_account_access: 0,
+			warm_access: 0,
+		}
+	}
+
+	/// Byzantium prices, as used by the Kovan wasm (KIP) chains.
+	pub fn byzantium() -> Self {
+		GasSchedule {
+			name: "byzantium".into(),
+			sload: 200,
+			balance: 400,
+			extcode: 700,
+			call: 700,
+			call_all_but_one_64th: true,
+			suicide: 5000,
+			..GasSchedule::frontier()
+		}
+	}
+
+	/// Istanbul prices.
+	pub fn istanbul() -> Self {
+		GasSchedule {
+			name: "istanbul".into(),
+			sload: 800,
+			balance: 700,
+			..GasSchedule::byzantium()
+		}
+	}
+
+	/// Berlin prices, with cold and warm accesses.
+	pub fn berlin() -> Self {
+		GasSchedule {
+			name: "berlin".into(),
+			sstore_reset: 2900,
+			access_lists: true,
+			cold_sload: 2100,
+			cold_account_access: 2600,
+			warm_access: 100,
+			..GasSchedule::istanbul()
+		}
+	}
+
+	/// Built-in schedule with the given name: `frontier`, `byzantium`, `kip`, `istanbul` or `berlin`.
+	pub fn named(name: &str) -> Option<Self> {
+		match name {
+			"frontier" => Some(GasSchedule::frontier()),
+			"byzantium" => Some(GasSchedule::byzantium()),
+			// the wasm runtime charges the requested gas in full, without the EIP-150 cap
+			"kip" => Some(GasSchedule { name: "kip".into(), call_all_but_one_64th: false, ..GasSchedule::byzantium() }),
+			"istanbul" => Some(GasSchedule::istanbul()),
+			"berlin" => Some(GasSchedule::berlin()),
+			_ => None,
+		}
+	}
+
+	/// Cost of a storage read, `cold` if the slot was not accessed yet in the transaction.
+	pub fn sload_cost(&self, cold: bool) -> u64 {
+		match (self.access_lists, cold) {
+			(false, _) => self.sload,
+			(true, true) => self.cold_sload,
+			(true, false) => self.warm_access,
+		}
+	}
+
+	/// Cost of a storage write, `set` if it stores a non-zero value into an empty slot.
+	pub fn sstore_cost(&self, set: bool, cold: bool) -> u64 {
+		let price = if set { self.sstore_set } else { self.sstore_reset };
+		if self.access_lists && cold { price + self.cold_sload } else { price }
+	}
+
+	/// Cost of an account access priced `price` without access lists.
+	pub fn account_access_cost(&self, price: u64, cold: bool) -> u64 {
+		match (self.access_lists, cold) {
+			(false, _) => price,
+			(true, true) => self.cold_account_access,
+			(true, false) => self.warm_access,
+		}
+	}
+
+	/// Gas forwarded to a subcall requesting `requested` out of `available`, `None` if the
+	/// caller runs out of gas.
+	pub fn forwarded_gas(&self, requested: u64, available: u64) -> Option<u64> {
+		if self.call_all_but_one_64th {
+			Some(::std::cmp::min(requested, available - available / 64))
+		} else if requested <= available {
+			Some(requested)
+		} else {
+			None
+		}
+	}
+}
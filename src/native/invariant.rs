@@ -0,0 +1,79 @@
+//! Invariants checked between calls.
+
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
+
+use super::{CallTrace, Externalities};
+
+#[derive(Clone)]
+pub(crate) struct Invariant {
+	name: String,
+	check: Rc<dyn Fn(&Externalities) -> bool>,
+}
+
+/// Invariant found not to hold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvariantViolation {
+	/// Name the invariant was added with.
+	pub name: String,
+	/// Call after which it did not hold.
+	pub trace: CallTrace,
+}
+
+impl fmt::Display for InvariantViolation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "invariant `{}` violated after:\n{}", self.name, self.trace)
+	}
+}
+
+impl Externalities {
+	/// Adds `check` as the invariant `name` of the chain state.
+	///
+	/// Invariants are evaluated after every outermost [`execute`] or [`transact`], and after
+	/// every nested call which reverts or traps, once the state is restored. If one does not
+	/// hold, the outermost call panics with the trace of the call after which it was violated.
+	///
+	/// `check` is given the externalities directly, so it must not use [`with_externalities`].
+	///
+	/// ```ignore
+	/// ext.add_invariant("total supply", move |ext| sum_of_balances(ext) == total_supply(ext));
+	/// ```
+	///
+	/// [`execute`]: fn.execute.html
+	/// [`transact`]: fn.transact.html
+	/// [`with_externalities`]: fn.with_externalities.html
+	pub fn add_invariant<F>(&mut self, name: &str, check: F)
+		where F: Fn(&Externalities) -> bool + 'static
+	{
+		self.invariants.push(Invariant { name: name.to_string(), check: Rc::new(check) });
+	}
+
+	/// Removes all invariants added with [`add_invariant`](#method.add_invariant).
+	pub fn clear_invariants(&mut self) {
+		self.invariants.clear();
+	}
+
+	/// Records the invariants not holding after the call whose trace was finished last.
+	pub(crate) fn check_invariants(&mut self) {
+		let violated: Vec<String> = self.invariants.iter()
+			.filter(|invariant| !(invariant.check)(self))
+			.map(|invariant| invariant.name.clone())
+			.collect();
+		if violated.is_empty() {
+			return;
+		}
+		let trace = match self.pending.last() {
+			Some(parent) => parent.subcalls.last(),
+			None => self.calls.last(),
+		}.expect("checked after finish_trace; qed").clone();
+		for name in violated {
+			self.violations.push(InvariantViolation { name: name, trace: trace.clone() });
+		}
+	}
+
+	/// Takes the invariant violations recorded so far.
+	pub(crate) fn take_violations(&mut self) -> Vec<InvariantViolation> {
+		mem::replace(&mut self.violations, Vec::new())
+	}
+}
@@ -0,0 +1,195 @@
+//! JSON export of call traces and state dumps.
+//!
+//! The format is stable: objects always carry the same keys in the same order, byte strings
+//! and hashes are `0x`-prefixed lowercase hex, values in Wei are decimal strings and gas is a
+//! JSON number.
+
+use std::fmt::Write;
+
+use pwasm_std::types::Address;
+
+use super::{AccountDump, CallTrace, Change, Log, Outcome, StateDiff, StateDump, StorageOp};
+
+impl CallTrace {
+	/// Serializes the call tree to JSON.
+	pub fn to_json(&self) -> String {
+		let mut out = String::new();
+		write_call(&mut out, self);
+		out
+	}
+}
+
+/// Serializes a list of call trees to a JSON array.
+pub fn to_json(calls: &[CallTrace]) -> String {
+	let mut out = String::new();
+	write_list(&mut out, calls, write_call);
+	out
+}
+
+impl StateDump {
+	/// Serializes the dump to a JSON object keyed by address.
+	pub fn to_json(&self) -> String {
+		let mut out = String::from("{");
+		for (i, (address, account)) in self.accounts.iter().enumerate() {
+			if i > 0 {
+				out.push(',');
+			}
+			write_address(&mut out, address);
+			out.push(':');
+			write_account(&mut out, account);
+		}
+		out.push('}');
+		out
+	}
+}
+
+impl StateDiff {
+	/// Serializes the changes to a JSON array.
+	pub fn to_json(&self) -> String {
+		let mut out = String::new();
+		write_list(&mut out, &self.changes, write_change);
+		out
+	}
+}
+
+fn write_account(out: &mut String, account: &AccountDump) {
+	let _ = write!(out, "{{\"balance\":\"{}\",\"code\":", account.balance);
+	write_bytes(out, &account.code);
+	out.push_str(",\"storage\":{");
+	for (i, (key, value)) in account.storage.iter().enumerate() {
+		if i > 0 {
+			out.push(',');
+		}
+		write_bytes(out, &<[u8; 32]>::from(*key));
+		out.push(':');
+		write_bytes(out, value);
+	}
+	out.push_str("}}");
+}
+
+fn write_change(out: &mut String, change: &Change) {
+	match *change {
+		Change::Balance { ref address, ref before, ref after } => {
+			out.push_str("{\"type\":\"balance\",\"address\":");
+			write_address(out, address);
+			let _ = write!(out, ",\"before\":\"{}\",\"after\":\"{}\"", before, after);
+		},
+		Change::Code { ref address, ref before, ref after } => {
+			out.push_str("{\"type\":\"code\",\"address\":");
+			write_address(out, address);
+			out.push_str(",\"before\":");
+			write_bytes(out, before);
+			out.push_str(",\"after\":");
+			write_bytes(out, after);
+		},
+		Change::Storage { ref address, ref key, ref before, ref after } => {
+			out.push_str("{\"type\":\"storage\",\"address\":");
+			write_address(out, address);
+			out.push_str(",\"key\":");
+			write_bytes(out, &<[u8; 32]>::from(*key));
+			out.push_str(",\"before\":");
+			write_bytes(out, before);
+			out.push_str(",\"after\":");
+			write_bytes(out, after);
+		},
+	}
+	out.push('}');
+}
+
+fn write_call(out: &mut String, call: &CallTrace) {
+	out.push_str("{\"kind\":");
+	write_str(out, &call.kind.to_string());
+	out.push_str(",\"caller\":");
+	write_address(out, &call.caller);
+	out.push_str(",\"target\":");
+	write_address(out, &call.target);
+	let _ = write!(out, ",\"value\":\"{}\",\"gas\":{},\"gas_used\":{},\"input\":", call.value, call.gas, call.gas_used);
+	write_bytes(out, &call.input);
+	out.push_str(",\"outcome\":");
+	match call.outcome {
+		Outcome::Return(ref data) => {
+			out.push_str("{\"type\":\"return\",\"data\":");
+			write_bytes(out, data);
+		},
+		Outcome::Suicide(ref refund) => {
+			out.push_str("{\"type\":\"suicide\",\"refund\":");
+			write_address(out, refund);
+		},
+		Outcome::Revert(ref data) => {
+			out.push_str("{\"type\":\"revert\",\"data\":");
+			write_bytes(out, data);
+		},
+		Outcome::Trap(ref message) => {
+			out.push_str("{\"type\":\"trap\",\"message\":");
+			write_str(out, message);
+		},
+	}
+	out.push_str("},\"storage\":");
+	write_list(out, &call.storage, write_storage_op);
+	out.push_str(",\"logs\":");
+	write_list(out, &call.logs, write_log);
+	out.push_str(",\"subcalls\":");
+	write_list(out, &call.subcalls, write_call);
+	out.push('}');
+}
+
+fn write_storage_op(out: &mut String, op: &StorageOp) {
+	let (name, key, value) = match *op {
+		StorageOp::Read { ref key, ref value } => ("read", key, value),
+		StorageOp::Write { ref key, ref value } => ("write", key, value),
+	};
+	let _ = write!(out, "{{\"op\":\"{}\",\"key\":", name);
+	write_bytes(out, &<[u8; 32]>::from(*key));
+	out.push_str(",\"value\":");
+	write_bytes(out, value);
+	out.push('}');
+}
+
+fn write_log(out: &mut String, log: &Log) {
+	out.push_str("{\"address\":");
+	write_address(out, &log.address);
+	out.push_str(",\"topics\":");
+	write_list(out, &log.topics, |out, topic| write_bytes(out, &<[u8; 32]>::from(*topic)));
+	out.push_str(",\"data\":");
+	write_bytes(out, &log.data);
+	out.push('}');
+}
+
+fn write_list<T, F: Fn(&mut String, &T)>(out: &mut String, items: &[T], f: F) {
+	out.push('[');
+	for (i, item) in items.iter().enumerate() {
+		if i > 0 {
+			out.push(',');
+		}
+		f(out, item);
+	}
+	out.push(']');
+}
+
+fn write_address(out: &mut String, address: &Address) {
+	write_bytes(out, &<[u8; 20]>::from(*address));
+}
+
+fn write_bytes(out: &mut String, bytes: &[u8]) {
+	out.push_str("\"0x");
+	for byte in bytes {
+		let _ = write!(out, "{:02x}", byte);
+	}
+	out.push('"');
+}
+
+fn write_str(out: &mut String, s: &str) {
+	out.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			c if (c as u32) < 0x20 => { let _ = write!(out, "\\u{:04x}", c as u32); },
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+}
@@ -0,0 +1,1317 @@
+//! Native externalities backend.
+//!
+//! With the `native` feature enabled every extern of this crate is served by an in-memory
+//! model of the chain instead of the wasm host. Contracts then compile for the host target
+//! and their entry points can be driven from plain `cargo test`, debuggers and coverage tools.
+//!
+//! The state lives in a thread-local [`Externalities`] value which tests configure with
+//! [`with_externalities`] and drive with [`execute`] or [`transact`]. Every message call made
+//! along the way is recorded as a [`CallTrace`] tree in `Externalities::calls`, which can be
+//! pretty-printed or exported as JSON with [`to_json`]. `Externalities::dump` copies the account
+//! state, and [`diff`] lists the balance, code and storage changes between two such dumps.
+//! A [`GasSnapshot`] fails tests whose gas usage regresses from a recorded snapshot file, and
+//! `Externalities::add_invariant` checks properties of the state between calls.
+//!
+//! [`Externalities`]: struct.Externalities.html
+//! [`with_externalities`]: fn.with_externalities.html
+//! [`execute`]: fn.execute.html
+//! [`transact`]: fn.transact.html
+//! [`CallTrace`]: struct.CallTrace.html
+//! [`to_json`]: fn.to_json.html
+//! [`diff`]: fn.diff.html
+//! [`GasSnapshot`]: struct.GasSnapshot.html
+
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod coverage;
+mod deploy;
+pub mod differential;
+mod gas;
+mod invariant;
+mod json;
+#[cfg(feature = "mock-precompiles")]
+mod precompiles;
+mod reentrancy;
+mod snapshot;
+mod state;
+mod trace;
+
+pub use self::deploy::{deploy_contract, Code, Contract};
+pub use self::gas::GasSchedule;
+pub use self::invariant::InvariantViolation;
+pub use self::json::to_json;
+pub use self::reentrancy::{non_reentrant, Reentrancy, ReentrancyPolicy};
+pub use self::snapshot::GasSnapshot;
+pub use self::state::{diff, AccountDump, Change, StateDiff, StateDump};
+pub use self::trace::{CallKind, CallTrace, Calls, StorageOp};
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use pwasm_std::types::{H256, U256, Address};
+
+use events::DecodeEvent;
+use hash::keccak256;
+
+use self::invariant::Invariant;
+
+/// Gas available to the top-level frame and block gas limit by default.
+pub const DEFAULT_GAS: u64 = 8_000_000;
+
+/// Number of recent block hashes available to contracts.
+pub const BLOCKHASH_WINDOW: u64 = 256;
+
+/// Chain id used by default (Ethereum mainnet).
+pub const DEFAULT_CHAIN_ID: u64 = 1;
+
+/// Trap message of calls running out of gas.
+pub const OUT_OF_GAS: &str = "out of gas";
+
+/// Native contract entry point, run in place of the wasm `call` export.
+pub type Entry = Rc<dyn Fn()>;
+
+/// Account state.
+#[derive(Clone, Default)]
+pub struct Account {
+	/// Balance in Wei.
+	pub balance: U256,
+	/// Storage slots; absent keys read as zero.
+	pub storage: HashMap<H256, [u8; 32]>,
+	/// Code bytes, as passed to `create` or set by the test.
+	pub code: Vec<u8>,
+	/// Native entry point executed when the account is called.
+	pub entry: Option<Entry>,
+}
+
+/// Block context.
+#[derive(Clone, Debug)]
+pub struct Block {
+	/// Block number.
+	pub number: u64,
+	/// Block timestamp.
+	pub timestamp: u64,
+	/// Beneficiary address.
+	pub coinbase: Address,
+	/// Block difficulty, or the `PREVRANDAO` value on post-merge chains.
+	pub difficulty: U256,
+	/// Block gas limit.
+	pub gas_limit: U256,
+	/// Base fee per gas (EIP-1559).
+	pub base_fee: U256,
+	/// Known hashes of previous blocks; other blocks hash to zero.
+	///
+	/// Only the 256 most recent blocks, excluding the current one, are visible to contracts.
+	pub hashes: HashMap<u64, H256>,
+}
+
+impl Default for Block {
+	fn default() -> Self {
+		Block {
+			number: 0,
+			timestamp: 0,
+			coinbase: Address::zero(),
+			difficulty: U256::zero(),
+			gas_limit: U256::from(DEFAULT_GAS),
+			base_fee: U256::zero(),
+			hashes: HashMap::new(),
+		}
+	}
+}
+
+impl Block {
+	/// Mines `blocks` blocks, `seconds` apart.
+	///
+	/// Every mined block gets a synthetic hash derived from its number and hashes older than
+	/// the 256-block window are dropped.
+	pub fn advance(&mut self, blocks: u64, seconds: u64) {
+		for _ in 0..blocks {
+			let mut hash = [0u8; 32];
+			hash[0] = 0xbb;
+			for i in 0..8 {
+				hash[24 + i] = (self.number >> (56 - 8 * i)) as u8;
+			}
+			self.hashes.insert(self.number, H256::from(hash));
+			self.number += 1;
+			self.timestamp += seconds;
+		}
+		let number = self.number;
+		self.hashes.retain(|n, _| n + BLOCKHASH_WINDOW >= number);
+	}
+
+	/// Hash of block `number` as seen by contracts.
+	pub fn hash(&self, number: u64) -> H256 {
+		if number >= self.number || number + BLOCKHASH_WINDOW < self.number {
+			return H256::zero();
+		}
+		self.hashes.get(&number).cloned().unwrap_or_else(H256::zero)
+	}
+}
+
+/// Block increments applied before every outermost call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AutoMine {
+	/// Blocks mined per call.
+	pub blocks: u64,
+	/// Seconds between mined blocks.
+	pub seconds: u64,
+}
+
+/// Execution context of a single (sub)call.
+#[derive(Clone, Debug)]
+pub struct Frame {
+	/// Address of the executing account.
+	pub address: Address,
+	/// Direct caller.
+	pub sender: Address,
+	/// Value deposited with the call.
+	pub value: U256,
+	/// Call input.
+	pub input: Vec<u8>,
+	/// Gas available to the call.
+	pub gas: u64,
+	/// Whether state modifications are disallowed.
+	pub is_static: bool,
+}
+
+impl Default for Frame {
+	fn default() -> Self {
+		Frame {
+			address: Address::zero(),
+			sender: Address::zero(),
+			value: U256::zero(),
+			input: Vec::new(),
+			gas: DEFAULT_GAS,
+			is_static: false,
+		}
+	}
+}
+
+/// Log entry emitted during execution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Log {
+	/// Address of the emitting account.
+	pub address: Address,
+	/// Log topics.
+	pub topics: Vec<H256>,
+	/// Log data.
+	pub data: Vec<u8>,
+}
+
+type LogCallback = Rc<dyn Fn(&Log)>;
+
+#[derive(Clone)]
+struct Subscription {
+	address: Option<Address>,
+	topic: Option<H256>,
+	callback: LogCallback,
+}
+
+impl Subscription {
+	fn matches(&self, log: &Log) -> bool {
+		self.address.map_or(true, |address| address == log.address)
+			&& self.topic.map_or(true, |topic| log.topics.first() == Some(&topic))
+	}
+}
+
+/// How an execution ended.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+	/// Execution returned the given data (empty if it just finished).
+	Return(Vec<u8>),
+	/// Account was destroyed and its balance sent to the given address.
+	Suicide(Address),
+	/// Execution reverted with the given reason; state changes were discarded.
+	Revert(Vec<u8>),
+	/// Execution trapped with the given message; state changes were discarded.
+	Trap(String),
+}
+
+impl Outcome {
+	/// Whether execution finished without reverting or trapping.
+	pub fn is_success(&self) -> bool {
+		match *self {
+			Outcome::Revert(_) | Outcome::Trap(_) => false,
+			_ => true,
+		}
+	}
+
+	/// Returned data or revert reason, empty for any other outcome.
+	pub fn data(&self) -> &[u8] {
+		match *self {
+			Outcome::Return(ref data) | Outcome::Revert(ref data) => data,
+			_ => &[],
+		}
+	}
+}
+
+/// Non-local exits used to implement diverging externs.
+enum Halt {
+	Return(Vec<u8>),
+	Suicide(Address),
+	#[cfg(feature = "eip140")]
+	Revert(Vec<u8>),
+}
+
+struct Snapshot {
+	accounts: HashMap<Address, Account>,
+	logs: usize,
+	accessed_accounts: HashSet<Address>,
+	accessed_slots: HashSet<(Address, H256)>,
+	transient: HashMap<(Address, H256), [u8; 32]>,
+}
+
+/// Complete mocked chain state.
+#[derive(Clone)]
+pub struct Externalities {
+	/// Chain id (EIP-155).
+	pub chain_id: u64,
+	/// Origin of the current transaction.
+	pub origin: Address,
+	/// Gas price of the current transaction.
+	pub gas_price: U256,
+	/// Current block.
+	pub block: Block,
+	/// Whether and how blocks are mined automatically before every outermost call.
+	pub auto_mine: Option<AutoMine>,
+	/// Prices of host operations; gas is not metered when `None`.
+	pub gas_schedule: Option<GasSchedule>,
+	/// All known accounts.
+	pub accounts: HashMap<Address, Account>,
+	/// Logs emitted so far.
+	pub logs: Vec<Log>,
+	/// Recorded trees of calls, one per outermost call.
+	pub calls: Vec<CallTrace>,
+	/// How calls re-entering a non-reentrant section are handled.
+	pub reentrancy: ReentrancyPolicy,
+	/// Accounts whose every call is treated as a non-reentrant section.
+	pub non_reentrant: HashSet<Address>,
+	/// Detected reentrant calls.
+	pub reentrancies: Vec<Reentrancy>,
+	subscriptions: Vec<Subscription>,
+	invariants: Vec<Invariant>,
+	violations: Vec<InvariantViolation>,
+	frames: Vec<Frame>,
+	pending: Vec<CallTrace>,
+	sections: Vec<Address>,
+	accessed_accounts: HashSet<Address>,
+	accessed_slots: HashSet<(Address, H256)>,
+	transient: HashMap<(Address, H256), [u8; 32]>,
+	return_data: Vec<u8>,
+	created: u64,
+}
+
+impl Default for Externalities {
+	fn default() -> Self {
+		Externalities {
+			chain_id: DEFAULT_CHAIN_ID,
+			origin: Address::zero(),
+			gas_price: U256::zero(),
+			block: Block::default(),
+			auto_mine: None,
+			gas_schedule: None,
+			accounts: HashMap::new(),
+			logs: Vec::new(),
+			calls: Vec::new(),
+			reentrancy: ReentrancyPolicy::default(),
+			non_reentrant: HashSet::new(),
+			reentrancies: Vec::new(),
+			subscriptions: Vec::new(),
+			invariants: Vec::new(),
+			violations: Vec::new(),
+			frames: vec![Frame::default()],
+			pending: Vec::new(),
+			sections: Vec::new(),
+			accessed_accounts: HashSet::new(),
+			accessed_slots: HashSet::new(),
+			transient: HashMap::new(),
+			return_data: Vec::new(),
+			created: 0,
+		}
+	}
+}
+
+impl Externalities {
+	/// Context of the innermost running call, or of the top-level frame when idle.
+	pub fn frame(&self) -> &Frame {
+		self.frames.last().expect("top-level frame is never popped; qed")
+	}
+
+	/// Mutable context of the innermost running call.
+	pub fn frame_mut(&mut self) -> &mut Frame {
+		self.frames.last_mut().expect("top-level frame is never popped; qed")
+	}
+
+	/// Balance of the given account.
+	pub fn balance(&self, address: &Address) -> U256 {
+		self.accounts.get(address).map(|a| a.balance).unwrap_or_else(U256::zero)
+	}
+
+	/// Sets balance of the given account.
+	pub fn set_balance(&mut self, address: Address, balance: U256) {
+		self.accounts.entry(address).or_insert_with(Account::default).balance = balance;
+	}
+
+	/// Code of the given account; empty for accounts without code.
+	pub fn code(&self, address: &Address) -> &[u8] {
+		self.accounts.get(address).map(|a| &a.code[..]).unwrap_or(&[])
+	}
+
+	/// Keccak-256 hash of the code of the given account, or zero if the account does not exist.
+	pub fn code_hash(&self, address: &Address) -> H256 {
+		match self.accounts.get(address) {
+			Some(account) => keccak256(&account.code),
+			None => H256::zero(),
+		}
+	}
+
+	/// Storage value of the given account.
+	pub fn storage_at(&self, address: &Address, key: &H256) -> [u8; 32] {
+		self.accounts.get(address)
+			.and_then(|a| a.storage.get(key).cloned())
+			.unwrap_or([0u8; 32])
+	}
+
+	/// Sets storage value of the given account.
+	pub fn set_storage(&mut self, address: Address, key: H256, value: [u8; 32]) {
+		self.accounts.entry(address).or_insert_with(Account::default).storage.insert(key, value);
+	}
+
+	/// Logs emitted so far with the `topic0` of event `E`, decoded, in emission order.
+	///
+	/// # Panics
+	///
+	/// If such a log does not decode as `E`.
+	pub fn decoded_logs<E: DecodeEvent>(&self) -> Vec<E> {
+		let topic0 = E::topic0();
+		self.logs.iter()
+			.filter(|log| log.topics.first() == Some(&topic0))
+			.map(|log| E::decode(&log.topics, &log.data)
+				.unwrap_or_else(|| panic!("log of {:?} with topic0 {:?} does not decode", log.address, topic0)))
+			.collect()
+	}
+
+	/// Registers `callback` to be run for every log matching the given emitter and first topic.
+	///
+	/// `None` matches any emitter or topic. Callbacks run once the outermost [`execute`] or
+	/// [`transact`] has finished, for the logs it emitted that were not discarded, so they act
+	/// like off-chain observers and may freely use [`with_externalities`] and [`transact`].
+	///
+	/// [`execute`]: fn.execute.html
+	/// [`transact`]: fn.transact.html
+	/// [`with_externalities`]: fn.with_externalities.html
+	pub fn subscribe<F>(&mut self, address: Option<Address>, topic: Option<H256>, callback: F)
+		where F: Fn(&Log) + 'static
+	{
+		self.subscriptions.push(Subscription {
+			address: address,
+			topic: topic,
+			callback: Rc::new(callback),
+		});
+	}
+
+	/// Removes all callbacks registered with [`subscribe`](#method.subscribe).
+	pub fn clear_subscriptions(&mut self) {
+		self.subscriptions.clear();
+	}
+
+	/// Registers native `entry` as the code of the given account.
+	pub fn register<F: Fn() + 'static>(&mut self, address: Address, entry: F) {
+		self.accounts.entry(address).or_insert_with(Account::default).entry = Some(Rc::new(entry));
+	}
+
+	fn account_mut(&mut self, address: Address) -> &mut Account {
+		self.accounts.entry(address).or_insert_with(Account::default)
+	}
+
+	fn transfer(&mut self, from: Address, to: Address, value: U256) -> bool {
+		if value.is_zero() {
+			return true;
+		}
+		let from_balance = self.balance(&from);
+		if from_balance < value {
+			return false;
+		}
+		self.account_mut(from).balance = from_balance - value;
+		let to_balance = self.balance(&to);
+		self.account_mut(to).balance = to_balance + value;
+		true
+	}
+
+	fn snapshot(&self) -> Snapshot {
+		Snapshot {
+			accounts: self.accounts.clone(),
+			logs: self.logs.len(),
+			accessed_accounts: self.accessed_accounts.clone(),
+			accessed_slots: self.accessed_slots.clone(),
+			transient: self.transient.clone(),
+		}
+	}
+
+	fn revert_to(&mut self, snapshot: Snapshot) {
+		self.accounts = snapshot.accounts;
+		self.logs.truncate(snapshot.logs);
+		self.accessed_accounts = snapshot.accessed_accounts;
+		self.accessed_slots = snapshot.accessed_slots;
+		self.transient = snapshot.transient;
+	}
+
+	/// Marks `address` as accessed in the current transaction, returning whether it was cold.
+	fn access_account(&mut self, address: Address) -> bool {
+		self.accessed_accounts.insert(address)
+	}
+
+	/// Marks storage `key` of `address` as accessed in the current transaction, returning whether it was cold.
+	fn access_slot(&mut self, address: Address, key: H256) -> bool {
+		self.accessed_slots.insert((address, key))
+	}
+
+	/// Charges `gas` from the current frame, trapping when it runs out.
+	fn charge(&mut self, gas: u64) {
+		if self.gas_schedule.is_none() {
+			return;
+		}
+		let frame = self.frame_mut();
+		if frame.gas < gas {
+			frame.gas = 0;
+			panic!(OUT_OF_GAS);
+		}
+		frame.gas -= gas;
+	}
+
+	fn charge_with<F: FnOnce(&GasSchedule) -> u64>(&mut self, cost: F) {
+		let gas = match self.gas_schedule {
+			Some(ref schedule) => cost(schedule),
+			None => return,
+		};
+		self.charge(gas);
+	}
+
+	fn require_mutable(&self) {
+		if self.frame().is_static {
+			panic!("state modification in static context");
+		}
+	}
+
+	fn enter_allowed(&mut self, frame: &Frame) -> bool {
+		if self.reentrancy == ReentrancyPolicy::Ignore {
+			return true;
+		}
+		let address = frame.address;
+		let running = self.frames[1..].iter().any(|f| f.address == address);
+		let guarded = self.non_reentrant.contains(&address) || self.sections.contains(&address);
+		if !running || !guarded {
+			return true;
+		}
+		let mut path: Vec<Address> = self.frames[1..].iter().map(|f| f.address).collect();
+		path.push(address);
+		self.reentrancies.push(Reentrancy { address: address, path: path });
+		self.reentrancy != ReentrancyPolicy::Fail
+	}
+
+	fn notifications(&self, from: usize) -> Vec<(LogCallback, Log)> {
+		let mut notifications = Vec::new();
+		for log in self.logs.iter().skip(from) {
+			for subscription in &self.subscriptions {
+				if subscription.matches(log) {
+					notifications.push((subscription.callback.clone(), log.clone()));
+				}
+			}
+		}
+		notifications
+	}
+
+	fn finish_trace(&mut self, outcome: Outcome, gas_used: u64) {
+		let mut trace = self.pending.pop().expect("finish_trace is paired with a pushed trace; qed");
+		trace.outcome = outcome;
+		trace.gas_used = gas_used;
+		match self.pending.last_mut() {
+			Some(parent) => parent.subcalls.push(trace),
+			None => self.calls.push(trace),
+		}
+	}
+
+	fn next_address(&mut self, tag: u8) -> Address {
+		self.created += 1;
+		let mut address = [0u8; 20];
+		address[0] = tag;
+		for i in 0..8 {
+			address[12 + i] = (self.created >> (56 - 8 * i)) as u8;
+		}
+		Address::from(address)
+	}
+}
+
+thread_local! {
+	static EXTERNALITIES: RefCell<Externalities> = RefCell::new(Externalities::default());
+}
+
+/// Gives access to the externalities of the current thread.
+///
+/// Must not be called from within `f` itself.
+pub fn with_externalities<F, R>(f: F) -> R where F: FnOnce(&mut Externalities) -> R {
+	EXTERNALITIES.with(|ext| f(&mut ext.borrow_mut()))
+}
+
+/// Replaces the externalities of the current thread with defaults.
+pub fn reset() {
+	with_externalities(|ext| *ext = Externalities::default());
+}
+
+/// Runs `f` in the current top-level frame, as if it was the code of the frame's account.
+///
+/// State changes are discarded if `f` traps (panics).
+pub fn execute<F: FnOnce()>(f: F) -> Outcome {
+	let frame = with_externalities(|ext| ext.frame().clone());
+	let trace = CallTrace::new(CallKind::Execute, frame.sender, frame.address, frame.value, frame.gas, frame.input.clone());
+	run(frame, false, trace, f).0
+}
+
+/// Performs a top-level message call from the transaction origin to a registered account.
+pub fn transact(address: Address, value: U256, input: Vec<u8>) -> Outcome {
+	let (frame, entry) = with_externalities(|ext| {
+		let frame = Frame {
+			address: address,
+			sender: ext.origin,
+			value: value,
+			input: input,
+			gas: ext.frame().gas,
+			is_static: false,
+		};
+		(frame, ext.accounts.get(&address).and_then(|a| a.entry.clone()))
+	});
+	let trace = CallTrace::new(CallKind::Transact, frame.sender, address, value, frame.gas, frame.input.clone());
+	run(frame, true, trace, move || if let Some(entry) = entry { entry() }).0
+}
+
+/// Runs `f` in a new `frame`, returning its outcome and the gas it has left.
+fn run<F: FnOnce()>(frame: Frame, transfer: bool, trace: CallTrace, f: F) -> (Outcome, u64) {
+	let address = frame.address;
+	let gas = frame.gas;
+	let reentrant = trace.kind != CallKind::DelegateCall;
+	coverage::record_call(trace.target, &trace.input);
+	let (snapshot, reentrancies) = with_externalities(|ext| {
+		if ext.frames.len() == 1 {
+			if let Some(auto_mine) = ext.auto_mine {
+				ext.block.advance(auto_mine.blocks, auto_mine.seconds);
+			}
+			ext.accessed_accounts.clear();
+			ext.accessed_slots.clear();
+			ext.access_account(frame.sender);
+			ext.access_account(frame.address);
+		}
+		ext.pending.push(trace);
+		let reentrancies = ext.reentrancies.len();
+		if reentrant && !ext.enter_allowed(&frame) {
+			return (Err(format!("reentrant call: {}", ext.reentrancies[reentrancies])), reentrancies);
+		}
+		let snapshot = ext.snapshot();
+		if transfer && !ext.transfer(frame.sender, frame.address, frame.value) {
+			return (Err("insufficient balance".into()), reentrancies);
+		}
+		ext.frames.push(frame);
+		(Ok(snapshot), reentrancies)
+	});
+	let snapshot = match snapshot {
+		Ok(snapshot) => snapshot,
+		Err(message) => {
+			let outcome = Outcome::Trap(message);
+			with_externalities(|ext| ext.finish_trace(outcome.clone(), 0));
+			return (outcome, gas);
+		},
+	};
+
+	let outcome = match panic::catch_unwind(AssertUnwindSafe(f)) {
+		Ok(()) => Outcome::Return(Vec::new()),
+		Err(payload) => outcome_of(payload),
+	};
+
+	let logs = snapshot.logs;
+	let (gas_left, reentrancies, violations, notifications) = with_externalities(|ext| {
+		let frame = ext.frames.pop().expect("frame pushed above; qed");
+		let gas_left = match outcome {
+			Outcome::Revert(_) => {
+				ext.revert_to(snapshot);
+				frame.gas
+			},
+			Outcome::Trap(_) => {
+				ext.revert_to(snapshot);
+				0
+			},
+			Outcome::Suicide(refund) => {
+				let balance = ext.accounts.remove(&address).map(|a| a.balance).unwrap_or_else(U256::zero);
+				if refund != address {
+					let refund_balance = ext.balance(&refund);
+					ext.account_mut(refund).balance = refund_balance + balance;
+				}
+				frame.gas
+			},
+			Outcome::Return(_) => frame.gas,
+		};
+		ext.finish_trace(outcome.clone(), gas - gas_left);
+		let top_level = ext.frames.len() == 1;
+		let reverted = match outcome {
+			Outcome::Revert(_) | Outcome::Trap(_) => true,
+			_ => false,
+		};
+		if top_level || reverted {
+			ext.check_invariants();
+		}
+		if !top_level {
+			return (gas_left, Vec::new(), Vec::new(), Vec::new());
+		}
+		ext.transient.clear();
+		let reentrancies = match ext.reentrancy {
+			ReentrancyPolicy::Fail => ext.reentrancies[reentrancies..].to_vec(),
+			_ => Vec::new(),
+		};
+		(gas_left, reentrancies, ext.take_violations(), ext.notifications(logs))
+	});
+	if let Some(reentrancy) = reentrancies.first() {
+		panic!("reentrant call: {}", reentrancy);
+	}
+	if let Some(violation) = violations.first() {
+		panic!("{}", violation);
+	}
+	for (callback, log) in notifications {
+		callback(&log);
+	}
+	(outcome, gas_left)
+}
+
+fn outcome_of(payload: Box<dyn Any + Send>) -> Outcome {
+	let payload = match payload.downcast::<Halt>() {
+		Ok(halt) => return match *halt {
+			Halt::Return(data) => Outcome::Return(data),
+			Halt::Suicide(refund) => Outcome::Suicide(refund),
+			#[cfg(feature = "eip140")]
+			Halt::Revert(data) => Outcome::Revert(data),
+		},
+		Err(payload) => payload,
+	};
+	let message = match payload.downcast::<String>() {
+		Ok(message) => *message,
+		Err(payload) => payload.downcast_ref::<&str>().map(|s| s.to_string()).unwrap_or_default(),
+	};
+	Outcome::Trap(message)
+}
+
+/// Native counterparts of the runtime imports, with identical signatures.
+pub(crate) mod external {
+	use std::panic;
+	use std::ptr;
+	use std::slice;
+
+	use pwasm_std::types::{H256, U256, Address};
+
+	use hash::{create2_address, keccak256};
+
+	use super::{coverage, with_externalities, run, CallKind, CallTrace, Frame, Halt, Log, Outcome, StorageOp, OUT_OF_GAS};
+
+	unsafe fn read_bytes(ptr: *const u8, len: u32) -> Vec<u8> {
+		slice::from_raw_parts(ptr, len as usize).to_vec()
+	}
+
+	unsafe fn read_address(ptr: *const u8) -> Address {
+		let mut res = [0u8; 20];
+		res.copy_from_slice(slice::from_raw_parts(ptr, 20));
+		Address::from(res)
+	}
+
+	unsafe fn read_h256(ptr: *const u8) -> H256 {
+		let mut res = [0u8; 32];
+		res.copy_from_slice(slice::from_raw_parts(ptr, 32));
+		H256::from(res)
+	}
+
+	unsafe fn read_u256(ptr: *const u8) -> U256 {
+		U256::from_big_endian(slice::from_raw_parts(ptr, 32))
+	}
+
+	unsafe fn write_address(address: Address, dest: *mut u8) {
+		let bytes: [u8; 20] = address.into();
+		ptr::copy_nonoverlapping(bytes.as_ptr(), dest, 20);
+	}
+
+	unsafe fn write_u256(value: U256, dest: *mut u8) {
+		let mut bytes = [0u8; 32];
+		value.to_big_endian(&mut bytes);
+		ptr::copy_nonoverlapping(bytes.as_ptr(), dest, 32);
+	}
+
+	fn subcall(
+		kind: CallKind,
+		gas: i64,
+		code_address: Address,
+		frame: Frame,
+		result_ptr: *mut u8,
+		result_len: u32,
+	) -> i32 {
+		let (caller, entry, gas) = with_externalities(|ext| {
+			let transfers_value = kind == CallKind::Call && !frame.value.is_zero();
+			let cold = ext.access_account(code_address);
+			let gas = match ext.gas_schedule.clone() {
+				Some(schedule) => {
+					let call = schedule.account_access_cost(schedule.call, cold);
+					ext.charge(call + if transfers_value { schedule.call_value } else { 0 });
+					let forwarded = match schedule.forwarded_gas(gas as u64, ext.frame().gas) {
+						Some(forwarded) => forwarded,
+						None => {
+							ext.frame_mut().gas = 0;
+							panic!(OUT_OF_GAS);
+						},
+					};
+					ext.frame_mut().gas -= forwarded;
+					forwarded + if transfers_value { schedule.call_stipend } else { 0 }
+				},
+				None => gas as u64,
+			};
+			(ext.frame().address, ext.accounts.get(&code_address).and_then(|a| a.entry.clone()), gas)
+		});
+		let frame = Frame { gas: gas, ..frame };
+		let trace = CallTrace::new(kind, caller, code_address, frame.value, frame.gas, frame.input.clone());
+		let transfer = kind == CallKind::Call;
+		let (outcome, gas_left) = run(frame, transfer, trace, move || if let Some(entry) = entry { entry() });
+		let data = outcome.data().to_vec();
+		with_externalities(|ext| {
+			if ext.gas_schedule.is_some() {
+				ext.frame_mut().gas += gas_left;
+			}
+			ext.return_data = data.clone();
+		});
+		match outcome {
+			Outcome::Trap(ref message) if message == OUT_OF_GAS => return failure_status(-2),
+			Outcome::Trap(_) => return -1,
+			_ => {},
+		}
+		let len = ::std::cmp::min(data.len(), result_len as usize);
+		unsafe { ptr::copy_nonoverlapping(data.as_ptr(), result_ptr, len); }
+		if outcome.is_success() { 0 } else { failure_status(1) }
+	}
+
+	/// Status of a failed call: with EIP-211, `1` for a revert and `-2` for running out of gas.
+	#[cfg(feature = "eip211")]
+	fn failure_status(status: i32) -> i32 {
+		status
+	}
+
+	/// Status of a failed call: like the Parity runtime, `-1` whatever the failure.
+	#[cfg(not(feature = "eip211"))]
+	fn failure_status(_status: i32) -> i32 {
+		-1
+	}
+
+	pub unsafe fn ccall(
+		gas: i64,
+		address: *const u8,
+		val_ptr: *const u8,
+		input_ptr: *const u8,
+		input_len: u32,
+		result_ptr: *mut u8,
+		result_len: u32,
+	) -> i32 {
+		coverage::record_extern("ccall");
+		let address = read_address(address);
+		let value = read_u256(val_ptr);
+		let frame = with_externalities(|ext| {
+			if !value.is_zero() {
+				ext.require_mutable();
+			}
+			Frame {
+				address: address,
+				sender: ext.frame().address,
+				value: value,
+				input: read_bytes(input_ptr, input_len),
+				gas: 0,
+				is_static: ext.frame().is_static,
+			}
+		});
+		subcall(CallKind::Call, gas, address, frame, result_ptr, result_len)
+	}
+
+	pub unsafe fn dcall(
+		gas: i64,
+		address: *const u8,
+		input_ptr: *const u8,
+		input_len: u32,
+		result_ptr: *mut u8,
+		result_len: u32,
+	) -> i32 {
+		coverage::record_extern("dcall");
+		let frame = with_externalities(|ext| Frame {
+			input: read_bytes(input_ptr, input_len),
+			..ext.frame().clone()
+		});
+		subcall(CallKind::DelegateCall, gas, read_address(address), frame, result_ptr, result_len)
+	}
+
+	pub unsafe fn scall(
+		gas: i64,
+		address: *const u8,
+		input_ptr: *const u8,
+		input_len: u32,
+		result_ptr: *mut u8,
+		result_len: u32,
+	) -> i32 {
+		coverage::record_extern("scall");
+		let address = read_address(address);
+		let frame = with_externalities(|ext| Frame {
+			address: address,
+			sender: ext.frame().address,
+			value: U256::zero(),
+			input: read_bytes(input_ptr, input_len),
+			gas: 0,
+			is_static: true,
+		});
+		subcall(CallKind::StaticCall, gas, address, frame, result_ptr, result_len)
+	}
+
+	pub unsafe fn blockhash(number: i64, dest: *mut u8) {
+		coverage::record_extern("blockhash");
+		let hash = with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.blockhash);
+			ext.block.hash(number as u64)
+		});
+		let bytes: [u8; 32] = hash.into();
+		ptr::copy_nonoverlapping(bytes.as_ptr(), dest, 32);
+	}
+
+	pub unsafe fn balance(address: *const u8, dest: *mut u8) {
+		coverage::record_extern("balance");
+		let address = read_address(address);
+		write_u256(with_externalities(|ext| {
+			let cold = ext.access_account(address);
+			ext.charge_with(|schedule| schedule.account_access_cost(schedule.balance, cold));
+			ext.balance(&address)
+		}), dest);
+	}
+
+	#[cfg(feature = "extcode")]
+	pub unsafe fn extcodesize(address: *const u8) -> u32 {
+		coverage::record_extern("extcodesize");
+		let address = read_address(address);
+		with_externalities(|ext| {
+			let cold = ext.access_account(address);
+			ext.charge_with(|schedule| schedule.account_access_cost(schedule.extcode, cold));
+			ext.code(&address).len() as u32
+		})
+	}
+
+	#[cfg(feature = "extcode")]
+	pub unsafe fn extcodecopy(address: *const u8, dest: *mut u8, len: u32) {
+		coverage::record_extern("extcodecopy");
+		let address = read_address(address);
+		with_externalities(|ext| {
+			let cold = ext.access_account(address);
+			let len = ::std::cmp::min(ext.code(&address).len(), len as usize);
+			let words = (len as u64 + 31) / 32;
+			ext.charge_with(|schedule| schedule.account_access_cost(schedule.extcode, cold) + words * schedule.copy_word);
+			ptr::copy_nonoverlapping(ext.code(&address).as_ptr(), dest, len);
+		});
+	}
+
+	#[cfg(feature = "extcode")]
+	pub unsafe fn extcodehash(address: *const u8, dest: *mut u8) {
+		coverage::record_extern("extcodehash");
+		let address = read_address(address);
+		let hash = with_externalities(|ext| {
+			let cold = ext.access_account(address);
+			ext.charge_with(|schedule| schedule.account_access_cost(schedule.extcode, cold));
+			ext.code_hash(&address)
+		});
+		let bytes: [u8; 32] = hash.into();
+		ptr::copy_nonoverlapping(bytes.as_ptr(), dest, 32);
+	}
+
+	pub unsafe fn coinbase(dest: *mut u8) {
+		coverage::record_extern("coinbase");
+		write_address(with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.block.coinbase
+		}), dest);
+	}
+
+	pub unsafe fn timestamp() -> i64 {
+		coverage::record_extern("timestamp");
+		with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.block.timestamp as i64
+		})
+	}
+
+	pub unsafe fn blocknumber() -> i64 {
+		coverage::record_extern("blocknumber");
+		with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.block.number as i64
+		})
+	}
+
+	pub unsafe fn difficulty(dest: *mut u8) {
+		coverage::record_extern("difficulty");
+		write_u256(with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.block.difficulty
+		}), dest);
+	}
+
+	pub unsafe fn gaslimit(dest: *mut u8) {
+		coverage::record_extern("gaslimit");
+		write_u256(with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.block.gas_limit
+		}), dest);
+	}
+
+	#[cfg(feature = "kip6")]
+	pub unsafe fn gasleft() -> i64 {
+		coverage::record_extern("gasleft");
+		with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.frame().gas as i64
+		})
+	}
+
+	#[cfg(feature = "eip1344")]
+	pub unsafe fn chainid() -> i64 {
+		coverage::record_extern("chainid");
+		with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.chain_id as i64
+		})
+	}
+
+	#[cfg(feature = "eip3198")]
+	pub unsafe fn basefee(dest: *mut u8) {
+		coverage::record_extern("basefee");
+		write_u256(with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.block.base_fee
+		}), dest);
+	}
+
+	#[cfg(feature = "gasprice")]
+	pub unsafe fn gasprice(dest: *mut u8) {
+		coverage::record_extern("gasprice");
+		write_u256(with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.gas_price
+		}), dest);
+	}
+
+	#[cfg(feature = "eip1884")]
+	pub unsafe fn selfbalance(dest: *mut u8) {
+		coverage::record_extern("selfbalance");
+		write_u256(with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.self_balance);
+			let address = ext.frame().address;
+			ext.balance(&address)
+		}), dest);
+	}
+
+	pub unsafe fn sender(dest: *mut u8) {
+		coverage::record_extern("sender");
+		write_address(with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.frame().sender
+		}), dest);
+	}
+
+	pub unsafe fn address(dest: *mut u8) {
+		coverage::record_extern("address");
+		write_address(with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.frame().address
+		}), dest);
+	}
+
+	pub unsafe fn value(dest: *mut u8) {
+		coverage::record_extern("value");
+		write_u256(with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.frame().value
+		}), dest);
+	}
+
+	pub unsafe fn origin(dest: *mut u8) {
+		coverage::record_extern("origin");
+		write_address(with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.origin
+		}), dest);
+	}
+
+	pub unsafe fn elog(
+		topic_ptr: *const u8,
+		topic_count: u32,
+		data_ptr: *const u8,
+		data_len: u32
+	) {
+		coverage::record_extern("elog");
+		if topic_count > 4 {
+			panic!("too many log topics: {}", topic_count);
+		}
+		let topics = (0..topic_count as usize).map(|i| read_h256(topic_ptr.add(32 * i))).collect();
+		let data = read_bytes(data_ptr, data_len);
+		with_externalities(|ext| {
+			ext.require_mutable();
+			ext.charge_with(|schedule| {
+				schedule.log + topic_count as u64 * schedule.log_topic + data_len as u64 * schedule.log_data
+			});
+			let log = Log { address: ext.frame().address, topics: topics, data: data };
+			if let Some(trace) = ext.pending.last_mut() {
+				trace.logs.push(log.clone());
+			}
+			ext.logs.push(log);
+		});
+	}
+
+	/// Deploys `code` at the next sequential address, or at its `CREATE2` address with a `salt`.
+	fn deploy(salt: Option<H256>, endowment: U256, code: Vec<u8>, result_ptr: *mut u8) -> i32 {
+		let address = with_externalities(|ext| {
+			ext.require_mutable();
+			ext.charge_with(|schedule| schedule.create);
+			ext.return_data.clear();
+			let creator = ext.frame().address;
+			let address = match salt {
+				Some(salt) => create2_address(&creator, &salt, &keccak256(&code)),
+				None => ext.next_address(0xc0),
+			};
+			ext.access_account(address);
+			if ext.accounts.get(&address).map_or(false, |a| !a.code.is_empty() || a.entry.is_some()) {
+				// the same code cannot be created twice with the same salt
+				return None;
+			}
+			if !ext.transfer(creator, address, endowment) {
+				return None;
+			}
+			ext.account_mut(address).code = code;
+			Some(address)
+		});
+		match address {
+			Some(address) => {
+				unsafe { write_address(address, result_ptr); }
+				0
+			},
+			None => 1,
+		}
+	}
+
+	pub unsafe fn create(
+		endowment: *const u8,
+		code_ptr: *const u8,
+		code_len: u32,
+		result_ptr: *mut u8
+	) -> i32 {
+		coverage::record_extern("create");
+		deploy(None, read_u256(endowment), read_bytes(code_ptr, code_len), result_ptr)
+	}
+
+	#[cfg(feature = "kip4")]
+	pub unsafe fn create2(
+		endowment: *const u8,
+		salt: *const u8,
+		code_ptr: *const u8,
+		code_len: u32,
+		result_ptr: *mut u8
+	) -> i32 {
+		coverage::record_extern("create2");
+		deploy(Some(read_h256(salt)), read_u256(endowment), read_bytes(code_ptr, code_len), result_ptr)
+	}
+
+	#[cfg(feature = "create-gas")]
+	pub unsafe fn gcreate(
+		_gas: i64,
+		endowment: *const u8,
+		code_ptr: *const u8,
+		code_len: u32,
+		result_ptr: *mut u8,
+		gas_used_ptr: *mut u64
+	) -> i32 {
+		coverage::record_extern("gcreate");
+		// constructors do not run natively, so they use no gas and cannot revert
+		*gas_used_ptr = 0;
+		match deploy(None, read_u256(endowment), read_bytes(code_ptr, code_len), result_ptr) {
+			0 => 0,
+			_ => -1,
+		}
+	}
+
+	#[cfg(all(feature = "kip4", feature = "create-gas"))]
+	pub unsafe fn gcreate2(
+		_gas: i64,
+		endowment: *const u8,
+		salt: *const u8,
+		code_ptr: *const u8,
+		code_len: u32,
+		result_ptr: *mut u8,
+		gas_used_ptr: *mut u64
+	) -> i32 {
+		coverage::record_extern("gcreate2");
+		*gas_used_ptr = 0;
+		match deploy(Some(read_h256(salt)), read_u256(endowment), read_bytes(code_ptr, code_len), result_ptr) {
+			0 => 0,
+			_ => -1,
+		}
+	}
+
+	pub unsafe fn suicide(refund: *const u8) -> ! {
+		coverage::record_extern("suicide");
+		let refund = read_address(refund);
+		with_externalities(|ext| {
+			ext.require_mutable();
+			ext.charge_with(|schedule| schedule.suicide);
+		});
+		panic::resume_unwind(Box::new(Halt::Suicide(refund)))
+	}
+
+	pub unsafe fn ret(ptr: *const u8, len: u32) -> ! {
+		coverage::record_extern("ret");
+		panic::resume_unwind(Box::new(Halt::Return(read_bytes(ptr, len))))
+	}
+
+	#[cfg(feature = "eip140")]
+	pub unsafe fn revert(ptr: *const u8, len: u32) -> ! {
+		coverage::record_extern("revert");
+		panic::resume_unwind(Box::new(Halt::Revert(read_bytes(ptr, len))))
+	}
+
+	pub unsafe fn input_length() -> u32 {
+		coverage::record_extern("input_length");
+		with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.frame().input.len() as u32
+		})
+	}
+
+	pub unsafe fn fetch_input(dst: *mut u8) {
+		coverage::record_extern("fetch_input");
+		with_externalities(|ext| {
+			let words = (ext.frame().input.len() as u64 + 31) / 32;
+			ext.charge_with(|schedule| schedule.base + words * schedule.copy_word);
+			let input = &ext.frame().input;
+			ptr::copy_nonoverlapping(input.as_ptr(), dst, input.len());
+		});
+	}
+
+	#[cfg(feature = "input-range")]
+	pub unsafe fn fetch_input_range(offset: u32, len: u32, dst: *mut u8) {
+		coverage::record_extern("fetch_input_range");
+		with_externalities(|ext| {
+			let words = (len as u64 + 31) / 32;
+			ext.charge_with(|schedule| schedule.base + words * schedule.copy_word);
+			let input = &ext.frame().input;
+			let (offset, len) = (offset as usize, len as usize);
+			if offset > input.len() || len > input.len() - offset {
+				panic!("input range {}..{} out of bounds of {} bytes", offset, offset + len, input.len());
+			}
+			ptr::copy_nonoverlapping(input[offset..].as_ptr(), dst, len);
+		});
+	}
+
+	#[cfg(feature = "eip211")]
+	pub unsafe fn return_data_length() -> u32 {
+		coverage::record_extern("return_data_length");
+		with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.base);
+			ext.return_data.len() as u32
+		})
+	}
+
+	#[cfg(feature = "eip211")]
+	pub unsafe fn fetch_return_data(dst: *mut u8) {
+		coverage::record_extern("fetch_return_data");
+		with_externalities(|ext| {
+			let words = (ext.return_data.len() as u64 + 31) / 32;
+			ext.charge_with(|schedule| schedule.base + words * schedule.copy_word);
+			ptr::copy_nonoverlapping(ext.return_data.as_ptr(), dst, ext.return_data.len());
+		});
+	}
+
+	fn read_slot(key: H256) -> [u8; 32] {
+		with_externalities(|ext| {
+			let address = ext.frame().address;
+			let cold = ext.access_slot(address, key);
+			ext.charge_with(|schedule| schedule.sload_cost(cold));
+			let value = ext.storage_at(&address, &key);
+			if let Some(trace) = ext.pending.last_mut() {
+				trace.storage.push(StorageOp::Read { key: key, value: value });
+			}
+			value
+		})
+	}
+
+	fn write_slot(key: H256, value: [u8; 32]) {
+		with_externalities(|ext| {
+			ext.require_mutable();
+			let address = ext.frame().address;
+			let set = ext.storage_at(&address, &key) == [0u8; 32] && value != [0u8; 32];
+			let cold = ext.access_slot(address, key);
+			ext.charge_with(|schedule| schedule.sstore_cost(set, cold));
+			ext.set_storage(address, key, value);
+			if let Some(trace) = ext.pending.last_mut() {
+				trace.storage.push(StorageOp::Write { key: key, value: value });
+			}
+		});
+	}
+
+	pub unsafe fn storage_read(key: *const u8, dst: *mut u8) {
+		coverage::record_extern("storage_read");
+		let value = read_slot(read_h256(key));
+		ptr::copy_nonoverlapping(value.as_ptr(), dst, 32);
+	}
+
+	pub unsafe fn storage_write(key: *const u8, src: *const u8) {
+		coverage::record_extern("storage_write");
+		let mut value = [0u8; 32];
+		value.copy_from_slice(slice::from_raw_parts(src, 32));
+		write_slot(read_h256(key), value);
+	}
+
+	#[cfg(feature = "eip1153")]
+	pub unsafe fn transient_storage_read(key: *const u8, dst: *mut u8) {
+		coverage::record_extern("transient_storage_read");
+		let key = read_h256(key);
+		let value = with_externalities(|ext| {
+			ext.charge_with(|schedule| schedule.transient);
+			let address = ext.frame().address;
+			ext.transient.get(&(address, key)).cloned().unwrap_or([0u8; 32])
+		});
+		ptr::copy_nonoverlapping(value.as_ptr(), dst, 32);
+	}
+
+	#[cfg(feature = "eip1153")]
+	pub unsafe fn transient_storage_write(key: *const u8, src: *const u8) {
+		coverage::record_extern("transient_storage_write");
+		let key = read_h256(key);
+		let mut value = [0u8; 32];
+		value.copy_from_slice(slice::from_raw_parts(src, 32));
+		with_externalities(|ext| {
+			ext.require_mutable();
+			ext.charge_with(|schedule| schedule.transient);
+			let address = ext.frame().address;
+			ext.transient.insert((address, key), value);
+		});
+	}
+
+	#[cfg(feature = "storage-batch")]
+	pub unsafe fn storage_read_multi(keys: *const u8, count: u32, dst: *mut u8) {
+		coverage::record_extern("storage_read_multi");
+		for i in 0..count as usize {
+			let value = read_slot(read_h256(keys.add(32 * i)));
+			ptr::copy_nonoverlapping(value.as_ptr(), dst.add(32 * i), 32);
+		}
+	}
+
+	#[cfg(feature = "storage-batch")]
+	pub unsafe fn storage_write_multi(entries: *const u8, count: u32) {
+		coverage::record_extern("storage_write_multi");
+		for i in 0..count as usize {
+			let mut value = [0u8; 32];
+			value.copy_from_slice(slice::from_raw_parts(entries.add(64 * i + 32), 32));
+			write_slot(read_h256(entries.add(64 * i)), value);
+		}
+	}
+}
@@ -0,0 +1,176 @@
+//! Software implementations of the precompiled contracts.
+//!
+//! [`Externalities::register_precompiles`] installs them at addresses `0x01` to `0x08` like
+//! any registered contract, so the [`precompiles`] wrappers work in tests. Gas is not charged,
+//! and an input the precompile rejects traps, which fails the call as on chain. `blake2f` is
+//! not provided.
+//!
+//! [`Externalities::register_precompiles`]: struct.Externalities.html#method.register_precompiles
+//! [`precompiles`]: ../precompiles/index.html
+
+use std::cmp;
+
+use bn::{self, Group};
+use num_bigint::BigUint;
+use ripemd160::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use pwasm_std::types::H256;
+
+use precompiles::{self, BN128_ADD, BN128_MUL, BN128_PAIRING, ECRECOVER, IDENTITY, MODEXP, RIPEMD160, SHA256};
+use signed::Signature;
+use wallet;
+
+use super::Externalities;
+
+/// Largest length of a `modexp` operand, far above what any chain can pay for.
+const MAX_MODEXP_LEN: usize = 1 << 20;
+
+type Precompile = fn(&[u8]) -> Result<Vec<u8>, &'static str>;
+
+impl Externalities {
+	/// Registers the software precompiles at their addresses.
+	pub fn register_precompiles(&mut self) {
+		let all: [(u8, Precompile); 8] = [
+			(ECRECOVER, ecrecover),
+			(SHA256, sha256),
+			(RIPEMD160, ripemd160),
+			(IDENTITY, identity),
+			(MODEXP, modexp),
+			(BN128_ADD, bn128_add),
+			(BN128_MUL, bn128_mul),
+			(BN128_PAIRING, bn128_pairing),
+		];
+		for &(index, precompile) in all.iter() {
+			self.register(precompiles::address(index), move || {
+				match precompile(&::input()) {
+					Ok(output) => ::ret(&output),
+					Err(message) => panic!("precompile 0x{:02x}: {}", index, message),
+				}
+			});
+		}
+	}
+}
+
+/// `len` bytes of `input` from `offset`, extended with zeros as precompiles read their input.
+fn padded(input: &[u8], offset: usize, len: usize) -> Vec<u8> {
+	let mut bytes = vec![0u8; len];
+	if offset < input.len() {
+		let available = cmp::min(len, input.len() - offset);
+		bytes[..available].copy_from_slice(&input[offset..offset + available]);
+	}
+	bytes
+}
+
+fn word(input: &[u8], offset: usize) -> H256 {
+	let mut word = [0u8; 32];
+	word.copy_from_slice(&padded(input, offset, 32));
+	H256::from(word)
+}
+
+fn ecrecover(input: &[u8]) -> Result<Vec<u8>, &'static str> {
+	let v = padded(input, 32, 32);
+	if v[..31].iter().any(|&byte| byte != 0) || (v[31] != 27 && v[31] != 28) {
+		return Ok(Vec::new());
+	}
+	let signature = Signature { v: v[31], r: word(input, 64), s: word(input, 96) };
+	Ok(match wallet::recover(&word(input, 0), &signature) {
+		Some(signer) => {
+			let mut output = vec![0u8; 32];
+			output[12..].copy_from_slice(&<[u8; 20]>::from(signer));
+			output
+		},
+		None => Vec::new(),
+	})
+}
+
+fn sha256(input: &[u8]) -> Result<Vec<u8>, &'static str> {
+	Ok(Sha256::digest(input).to_vec())
+}
+
+fn ripemd160(input: &[u8]) -> Result<Vec<u8>, &'static str> {
+	let mut output = vec![0u8; 32];
+	output[12..].copy_from_slice(&Ripemd160::digest(input));
+	Ok(output)
+}
+
+fn identity(input: &[u8]) -> Result<Vec<u8>, &'static str> {
+	Ok(input.to_vec())
+}
+
+fn modexp(input: &[u8]) -> Result<Vec<u8>, &'static str> {
+	let length = |offset: usize| -> Result<usize, &'static str> {
+		let word = padded(input, offset, 32);
+		let mut len = 0usize;
+		for &byte in &word {
+			len = len.checked_mul(256).and_then(|len| len.checked_add(byte as usize))
+				.filter(|&len| len <= MAX_MODEXP_LEN)
+				.ok_or("operand too long")?;
+		}
+		Ok(len)
+	};
+	let (base_len, exponent_len, modulus_len) = (length(0)?, length(32)?, length(64)?);
+	let mut output = vec![0u8; modulus_len];
+	let modulus = padded(input, 96 + base_len + exponent_len, modulus_len);
+	if modulus.iter().all(|&byte| byte == 0) {
+		return Ok(output);
+	}
+	let base = BigUint::from_bytes_be(&padded(input, 96, base_len));
+	let exponent = BigUint::from_bytes_be(&padded(input, 96 + base_len, exponent_len));
+	let result = base.modpow(&exponent, &BigUint::from_bytes_be(&modulus)).to_bytes_be();
+	output[modulus_len - result.len()..].copy_from_slice(&result);
+	Ok(output)
+}
+
+fn read_fq(input: &[u8], offset: usize) -> Result<bn::Fq, &'static str> {
+	bn::Fq::from_slice(&padded(input, offset, 32)).map_err(|_| "invalid field element")
+}
+
+fn read_g1(input: &[u8], offset: usize) -> Result<bn::G1, &'static str> {
+	let (x, y) = (read_fq(input, offset)?, read_fq(input, offset + 32)?);
+	if x == bn::Fq::zero() && y == bn::Fq::zero() {
+		return Ok(bn::G1::zero());
+	}
+	bn::AffineG1::new(x, y).map(Into::into).map_err(|_| "point not on curve")
+}
+
+fn read_g2(input: &[u8], offset: usize) -> Result<bn::G2, &'static str> {
+	// coordinates are encoded imaginary part first
+	let x = bn::Fq2::new(read_fq(input, offset + 32)?, read_fq(input, offset)?);
+	let y = bn::Fq2::new(read_fq(input, offset + 96)?, read_fq(input, offset + 64)?);
+	if x == bn::Fq2::zero() && y == bn::Fq2::zero() {
+		return Ok(bn::G2::zero());
+	}
+	bn::AffineG2::new(x, y).map(Into::into).map_err(|_| "point not on curve")
+}
+
+fn write_g1(point: bn::G1) -> Vec<u8> {
+	let mut output = vec![0u8; 64];
+	if let Some(point) = bn::AffineG1::from_jacobian(point) {
+		point.x().to_big_endian(&mut output[..32]).expect("32 bytes fit a field element; qed");
+		point.y().to_big_endian(&mut output[32..]).expect("32 bytes fit a field element; qed");
+	}
+	output
+}
+
+fn bn128_add(input: &[u8]) -> Result<Vec<u8>, &'static str> {
+	Ok(write_g1(read_g1(input, 0)? + read_g1(input, 64)?))
+}
+
+fn bn128_mul(input: &[u8]) -> Result<Vec<u8>, &'static str> {
+	let scalar = bn::Fr::from_slice(&padded(input, 64, 32)).map_err(|_| "invalid scalar")?;
+	Ok(write_g1(read_g1(input, 0)? * scalar))
+}
+
+fn bn128_pairing(input: &[u8]) -> Result<Vec<u8>, &'static str> {
+	if input.len() % 192 != 0 {
+		return Err("input is not a list of point pairs");
+	}
+	let mut product = bn::Gt::one();
+	for pair in input.chunks(192) {
+		product = product * bn::pairing(read_g1(pair, 0)?, read_g2(pair, 64)?);
+	}
+	let mut output = vec![0u8; 32];
+	output[31] = (product == bn::Gt::one()) as u8;
+	Ok(output)
+}
@@ -0,0 +1,73 @@
+//! Reentrancy detection.
+
+use std::fmt;
+
+use pwasm_std::types::Address;
+
+use super::with_externalities;
+
+/// What to do when a call re-enters a contract inside a non-reentrant section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReentrancyPolicy {
+	/// Reentrancy is not checked.
+	Ignore,
+	/// Reentrancy is recorded in `Externalities::reentrancies` and execution continues.
+	Flag,
+	/// The reentrant call traps and the outermost [`execute`] or [`transact`] panics.
+	///
+	/// [`execute`]: fn.execute.html
+	/// [`transact`]: fn.transact.html
+	Fail,
+}
+
+impl Default for ReentrancyPolicy {
+	fn default() -> Self {
+		ReentrancyPolicy::Ignore
+	}
+}
+
+/// Detected reentrant call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reentrancy {
+	/// Re-entered account.
+	pub address: Address,
+	/// Addresses of the executing accounts, outermost first, ending with the re-entered one.
+	pub path: Vec<Address>,
+}
+
+impl fmt::Display for Reentrancy {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for (i, address) in self.path.iter().enumerate() {
+			if i > 0 {
+				f.write_str(" -> ")?;
+			}
+			f.write_str("0x")?;
+			for byte in <[u8; 20]>::from(*address).iter() {
+				write!(f, "{:02x}", byte)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Marks `f` as a non-reentrant section of the currently executing account.
+///
+/// Calls re-entering the account while `f` runs are handled according to
+/// `Externalities::reentrancy`. Contracts can wrap their guarded code in this function
+/// under `#[cfg(feature = "native")]`.
+pub fn non_reentrant<F: FnOnce() -> R, R>(f: F) -> R {
+	struct Section;
+
+	impl Drop for Section {
+		fn drop(&mut self) {
+			with_externalities(|ext| { ext.sections.pop(); });
+		}
+	}
+
+	with_externalities(|ext| {
+		let address = ext.frame().address;
+		ext.sections.push(address);
+	});
+	let _section = Section;
+	f()
+}
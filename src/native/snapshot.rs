@@ -0,0 +1,121 @@
+//! Gas snapshots guarding against gas regressions.
+//!
+//! A snapshot file holds one `name gas` line per measurement, sorted by name. Tests check their
+//! measurements against it and fail when one uses more gas than recorded, beyond a tolerance.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use super::with_externalities;
+
+/// Named gas measurements checked against, and saved to, a snapshot file.
+///
+/// ```ignore
+/// let mut snapshot = GasSnapshot::open("gas-snapshot")?.tolerance(2);
+/// native::transact(token, U256::zero(), transfer_input);
+/// snapshot.last_call("transfer");
+/// snapshot.save()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct GasSnapshot {
+	path: PathBuf,
+	tolerance: u64,
+	update: bool,
+	recorded: BTreeMap<String, u64>,
+	measured: BTreeMap<String, u64>,
+}
+
+impl GasSnapshot {
+	/// Snapshot recorded at `path`, empty if the file does not exist.
+	///
+	/// # Errors
+	///
+	/// If the file cannot be read or a line is not a name followed by an amount of gas.
+	pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let path = path.as_ref().to_path_buf();
+		let contents = match fs::read_to_string(&path) {
+			Ok(contents) => contents,
+			Err(ref e) if e.kind() == ErrorKind::NotFound => String::new(),
+			Err(e) => return Err(e),
+		};
+		let mut recorded = BTreeMap::new();
+		for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+			let mut parts = line.rsplitn(2, ' ');
+			let gas = parts.next().and_then(|gas| gas.parse().ok());
+			match (parts.next(), gas) {
+				(Some(name), Some(gas)) => { recorded.insert(name.to_string(), gas); },
+				_ => return Err(io::Error::new(ErrorKind::InvalidData, format!("bad gas snapshot line: {}", line))),
+			}
+		}
+		Ok(GasSnapshot {
+			path: path,
+			tolerance: 0,
+			update: false,
+			recorded: recorded,
+			measured: BTreeMap::new(),
+		})
+	}
+
+	/// Allows measurements to exceed the recorded gas by up to `percent` percent.
+	pub fn tolerance(mut self, percent: u64) -> Self {
+		self.tolerance = percent;
+		self
+	}
+
+	/// Accepts regressions, so that [`save`](#method.save) records the new measurements.
+	pub fn update(mut self, update: bool) -> Self {
+		self.update = update;
+		self
+	}
+
+	/// Gas recorded for `name` in the file.
+	pub fn recorded(&self, name: &str) -> Option<u64> {
+		self.recorded.get(name).cloned()
+	}
+
+	/// Checks and keeps the measurement `gas` of `name`.
+	///
+	/// # Panics
+	///
+	/// If `gas` exceeds the recorded gas by more than the tolerance, unless updating.
+	pub fn check(&mut self, name: &str, gas: u64) {
+		if let Some(recorded) = self.recorded(name) {
+			let limit = recorded + recorded * self.tolerance / 100;
+			if gas > limit && !self.update {
+				panic!(
+					"gas regression in {}: {} recorded, {} used ({}% tolerance) in {}",
+					name, recorded, gas, self.tolerance, self.path.display()
+				);
+			}
+		}
+		self.measured.insert(name.to_string(), gas);
+	}
+
+	/// Checks the gas used by the last top-level call as the measurement of `name`.
+	///
+	/// # Panics
+	///
+	/// If no call has been made yet, or as [`check`](#method.check).
+	pub fn last_call(&mut self, name: &str) {
+		let gas = with_externalities(|ext| ext.calls.last().map(|call| call.gas_used))
+			.expect("last_call needs a recorded top-level call");
+		self.check(name, gas)
+	}
+
+	/// Writes the recorded entries, replaced by the checked measurements, back to the file.
+	///
+	/// # Errors
+	///
+	/// If the file cannot be written.
+	pub fn save(&self) -> io::Result<()> {
+		let mut entries = self.recorded.clone();
+		entries.extend(self.measured.iter().map(|(name, gas)| (name.clone(), *gas)));
+		let mut contents = String::new();
+		for (name, gas) in entries {
+			contents.push_str(&format!("{} {}\n", name, gas));
+		}
+		fs::write(&self.path, contents)
+	}
+}
@@ -0,0 +1,171 @@
+//! Dumps of the account state and differences between them.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use pwasm_std::types::{H256, U256, Address};
+
+use super::Externalities;
+
+/// Balance, code and non-zero storage of an account.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountDump {
+	/// Balance in Wei.
+	pub balance: U256,
+	/// Code bytes.
+	pub code: Vec<u8>,
+	/// Storage slots holding a non-zero value.
+	pub storage: BTreeMap<H256, [u8; 32]>,
+}
+
+impl AccountDump {
+	fn is_empty(&self) -> bool {
+		self.balance.is_zero() && self.code.is_empty() && self.storage.is_empty()
+	}
+}
+
+/// Copy of the account state, ordered by address.
+///
+/// Accounts without balance, code or non-zero storage are left out, as they cannot be told
+/// apart from accounts which do not exist.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StateDump {
+	/// Non-empty accounts.
+	pub accounts: BTreeMap<Address, AccountDump>,
+}
+
+/// Change of a single piece of account state between two dumps.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+	/// Balance of `address` changed.
+	Balance {
+		/// Account.
+		address: Address,
+		/// Balance before.
+		before: U256,
+		/// Balance after.
+		after: U256,
+	},
+	/// Code of `address` changed.
+	Code {
+		/// Account.
+		address: Address,
+		/// Code before.
+		before: Vec<u8>,
+		/// Code after.
+		after: Vec<u8>,
+	},
+	/// Storage slot `key` of `address` changed.
+	Storage {
+		/// Account.
+		address: Address,
+		/// Storage key.
+		key: H256,
+		/// Value before.
+		before: [u8; 32],
+		/// Value after.
+		after: [u8; 32],
+	},
+}
+
+/// Changes between two [`StateDump`](struct.StateDump.html)s, ordered by address then key.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StateDiff {
+	/// Changes, balance and code of an account first, then its storage.
+	pub changes: Vec<Change>,
+}
+
+impl StateDiff {
+	/// Whether nothing changed.
+	pub fn is_empty(&self) -> bool {
+		self.changes.is_empty()
+	}
+}
+
+impl Externalities {
+	/// Copy of the current account state.
+	pub fn dump(&self) -> StateDump {
+		let mut accounts = BTreeMap::new();
+		for (address, account) in &self.accounts {
+			let dump = AccountDump {
+				balance: account.balance,
+				code: account.code.clone(),
+				storage: account.storage.iter()
+					.filter(|&(_, value)| *value != [0u8; 32])
+					.map(|(key, value)| (*key, *value))
+					.collect(),
+			};
+			if !dump.is_empty() {
+				accounts.insert(*address, dump);
+			}
+		}
+		StateDump { accounts: accounts }
+	}
+}
+
+/// Changes turning state `before` into state `after`.
+pub fn diff(before: &StateDump, after: &StateDump) -> StateDiff {
+	let empty = AccountDump::default();
+	let addresses: BTreeSet<&Address> = before.accounts.keys().chain(after.accounts.keys()).collect();
+	let mut changes = Vec::new();
+	for address in addresses {
+		let old = before.accounts.get(address).unwrap_or(&empty);
+		let new = after.accounts.get(address).unwrap_or(&empty);
+		if old.balance != new.balance {
+			changes.push(Change::Balance { address: *address, before: old.balance, after: new.balance });
+		}
+		if old.code != new.code {
+			changes.push(Change::Code { address: *address, before: old.code.clone(), after: new.code.clone() });
+		}
+		let keys: BTreeSet<&H256> = old.storage.keys().chain(new.storage.keys()).collect();
+		for key in keys {
+			let old_value = old.storage.get(key).cloned().unwrap_or([0u8; 32]);
+			let new_value = new.storage.get(key).cloned().unwrap_or([0u8; 32]);
+			if old_value != new_value {
+				changes.push(Change::Storage { address: *address, key: *key, before: old_value, after: new_value });
+			}
+		}
+	}
+	StateDiff { changes: changes }
+}
+
+impl fmt::Display for Change {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Change::Balance { ref address, ref before, ref after } => {
+				write_hex(f, &<[u8; 20]>::from(*address))?;
+				write!(f, " balance {} -> {}", before, after)
+			},
+			Change::Code { ref address, ref before, ref after } => {
+				write_hex(f, &<[u8; 20]>::from(*address))?;
+				write!(f, " code {} bytes -> {} bytes", before.len(), after.len())
+			},
+			Change::Storage { ref address, ref key, ref before, ref after } => {
+				write_hex(f, &<[u8; 20]>::from(*address))?;
+				f.write_str(" [")?;
+				write_hex(f, &<[u8; 32]>::from(*key))?;
+				f.write_str("] ")?;
+				write_hex(f, before)?;
+				f.write_str(" -> ")?;
+				write_hex(f, after)
+			},
+		}
+	}
+}
+
+impl fmt::Display for StateDiff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for change in &self.changes {
+			writeln!(f, "{}", change)?;
+		}
+		Ok(())
+	}
+}
+
+fn write_hex(f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+	f.write_str("0x")?;
+	for byte in bytes {
+		write!(f, "{:02x}", byte)?;
+	}
+	Ok(())
+}
@@ -0,0 +1,184 @@
+//! Call graph recording.
+
+use std::fmt;
+
+use pwasm_std::types::{H256, U256, Address};
+
+use super::{Log, Outcome};
+
+/// Kind of a message call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallKind {
+	/// Code run directly by [`execute`](fn.execute.html).
+	Execute,
+	/// Top-level call performed by [`transact`](fn.transact.html).
+	Transact,
+	/// Constructor run by [`deploy_contract`](fn.deploy_contract.html).
+	Create,
+	/// Regular call (`CALL`).
+	Call,
+	/// Call with the code of another account (`DELEGATECALL`).
+	DelegateCall,
+	/// Read-only call (`STATICCALL`).
+	StaticCall,
+}
+
+impl fmt::Display for CallKind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match *self {
+			CallKind::Execute => "EXECUTE",
+			CallKind::Transact => "TRANSACT",
+			CallKind::Create => "CREATE",
+			CallKind::Call => "CALL",
+			CallKind::DelegateCall => "DELEGATECALL",
+			CallKind::StaticCall => "STATICCALL",
+		})
+	}
+}
+
+/// Storage access made by a call.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StorageOp {
+	/// Slot `key` was read and contained `value`.
+	Read {
+		/// Storage key.
+		key: H256,
+		/// Value read.
+		value: [u8; 32],
+	},
+	/// `value` was written to slot `key`.
+	Write {
+		/// Storage key.
+		key: H256,
+		/// Value written.
+		value: [u8; 32],
+	},
+}
+
+/// Recorded message call together with all of its subcalls.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallTrace {
+	/// Kind of the call.
+	pub kind: CallKind,
+	/// Account performing the call.
+	pub caller: Address,
+	/// Account whose code is executed.
+	pub target: Address,
+	/// Value sent with the call.
+	pub value: U256,
+	/// Gas given to the call.
+	pub gas: u64,
+	/// Gas used by the call, including its subcalls.
+	pub gas_used: u64,
+	/// Call input.
+	pub input: Vec<u8>,
+	/// How the call ended.
+	pub outcome: Outcome,
+	/// Storage accesses made by the call itself, in order.
+	pub storage: Vec<StorageOp>,
+	/// Logs emitted by the call itself, including ones later discarded by a trap.
+	pub logs: Vec<Log>,
+	/// Subcalls in the order they were made.
+	pub subcalls: Vec<CallTrace>,
+}
+
+impl CallTrace {
+	pub(crate) fn new(kind: CallKind, caller: Address, target: Address, value: U256, gas: u64, input: Vec<u8>) -> Self {
+		CallTrace {
+			kind: kind,
+			caller: caller,
+			target: target,
+			value: value,
+			gas: gas,
+			gas_used: 0,
+			input: input,
+			outcome: Outcome::Return(Vec::new()),
+			storage: Vec::new(),
+			logs: Vec::new(),
+			subcalls: Vec::new(),
+		}
+	}
+
+	/// Iterates over this call and all nested subcalls in depth-first order.
+	pub fn iter(&self) -> Calls {
+		Calls { stack: vec![self] }
+	}
+
+	/// Depth-first search for the first call matching `predicate`.
+	pub fn find<P: Fn(&CallTrace) -> bool>(&self, predicate: P) -> Option<&CallTrace> {
+		self.iter().find(|call| predicate(call))
+	}
+
+	/// Number of calls in the tree, including this one.
+	pub fn call_count(&self) -> usize {
+		self.iter().count()
+	}
+
+	/// Maximum nesting depth of the tree; a call without subcalls has depth 1.
+	pub fn depth(&self) -> usize {
+		1 + self.subcalls.iter().map(|call| call.depth()).max().unwrap_or(0)
+	}
+
+	fn fmt_indented(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+		for _ in 0..indent {
+			f.write_str("  ")?;
+		}
+		write!(f, "{} ", self.kind)?;
+		write_hex(f, &<[u8; 20]>::from(self.caller))?;
+		f.write_str(" -> ")?;
+		write_hex(f, &<[u8; 20]>::from(self.target))?;
+		write!(f, " value={} gas={} gas_used={} input=", self.value, self.gas, self.gas_used)?;
+		write_hex(f, &self.input)?;
+		f.write_str(" => ")?;
+		match self.outcome {
+			Outcome::Return(ref data) => {
+				f.write_str("RETURN ")?;
+				write_hex(f, data)?;
+			},
+			Outcome::Suicide(refund) => {
+				f.write_str("SUICIDE ")?;
+				write_hex(f, &<[u8; 20]>::from(refund))?;
+			},
+			Outcome::Revert(ref data) => {
+				f.write_str("REVERT ")?;
+				write_hex(f, data)?;
+			},
+			Outcome::Trap(ref message) => write!(f, "TRAP {:?}", message)?,
+		}
+		f.write_str("\n")?;
+		for call in &self.subcalls {
+			call.fmt_indented(f, indent + 1)?;
+		}
+		Ok(())
+	}
+}
+
+/// Pretty-prints the call tree, one call per line, subcalls indented.
+impl fmt::Display for CallTrace {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.fmt_indented(f, 0)
+	}
+}
+
+/// Depth-first iterator over a call tree.
+pub struct Calls<'a> {
+	stack: Vec<&'a CallTrace>,
+}
+
+impl<'a> Iterator for Calls<'a> {
+	type Item = &'a CallTrace;
+
+	fn next(&mut self) -> Option<&'a CallTrace> {
+		let call = self.stack.pop()?;
+		self.stack.extend(call.subcalls.iter().rev());
+		Some(call)
+	}
+}
+
+fn write_hex(f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+	f.write_str("0x")?;
+	for byte in bytes {
+		write!(f, "{:02x}", byte)?;
+	}
+	Ok(())
+}
@@ -0,0 +1,205 @@
+//! Typed wrappers of the precompiled contracts at addresses `0x01` to `0x09`.
+//!
+//! Each precompile is given what it costs under the Byzantium prices, with the EIP-2565
+//! minimum for `modexp`; unused gas is returned. Most later forks lowered these prices, but
+//! not all of them: EIP-7883 raises the price of `modexp`, which then runs out of gas on
+//! runtimes applying it. Call [`address`] directly with a larger gas limit there. A precompile
+//! rejecting its input fails the call like running out of gas does, which is reported as a
+//! [`CallError`].
+//!
+//! The native externalities only implement precompiles with the `mock-precompiles` feature,
+//! once installed by `Externalities::register_precompiles`. Otherwise a call to their address
+//! runs no code and returns no data, so the wrappers see zeros, unless tests register mocks at
+//! [`address`].
+//!
+//! [`CallError`]: ../enum.CallError.html
+//! [`address`]: fn.address.html
+
+#[cfg(not(feature = "std"))]
+use core::cmp;
+#[cfg(feature = "std")]
+use std::cmp;
+
+use pwasm_std::{
+	self,
+	types::{H256, U256, Address}
+};
+
+use ext::{self, CallError};
+
+/// Index of the `ecrecover` precompile.
+pub const ECRECOVER: u8 = 0x01;
+/// Index of the `sha256` precompile.
+pub const SHA256: u8 = 0x02;
+/// Index of the `ripemd160` precompile.
+pub const RIPEMD160: u8 = 0x03;
+/// Index of the `identity` precompile.
+pub const IDENTITY: u8 = 0x04;
+/// Index of the `modexp` precompile (EIP-198).
+pub const MODEXP: u8 = 0x05;
+/// Index of the `bn128_add` precompile (EIP-196).
+pub const BN128_ADD: u8 = 0x06;
+/// Index of the `bn128_mul` precompile (EIP-196).
+pub const BN128_MUL: u8 = 0x07;
+/// Index of the `bn128_pairing` precompile (EIP-197).
+pub const BN128_PAIRING: u8 = 0x08;
+/// Index of the `blake2f` precompile (EIP-152).
+pub const BLAKE2F: u8 = 0x09;
+
+/// Address of the precompile with the given index.
+pub fn address(index: u8) -> Address {
+	let mut address = [0u8; 20];
+	address[19] = index;
+	Address::from(address)
+}
+
+fn call(index: u8, gas: u64, input: &[u8], result: &mut [u8]) -> Result<(), CallError> {
+	ext::static_call(gas, &address(index), input, result)
+}
+
+/// Cost of a precompile charging `base` plus `per_word` for every 32-byte word of `input`.
+fn linear_cost(base: u64, per_word: u64, input: &[u8]) -> u64 {
+	base + (input.len() as u64 + 31) / 32 * per_word
+}
+
+/// Cost of `modexp` (EIP-198), at least the minimum of EIP-2565.
+fn modexp_cost(base_len: usize, exponent: &[u8], modulus_len: usize) -> u64 {
+	let len = cmp::max(base_len, modulus_len) as u64;
+	let complexity = if len <= 64 {
+		len * len
+	} else if len <= 1024 {
+		len * len / 4 + 96 * len - 3072
+	} else {
+		(len * len / 16 + 480 * len).saturating_sub(199_680)
+	};
+	// bit length of the exponent, minus one, counting only its first 32 bytes
+	let head = &exponent[..cmp::min(exponent.len(), 32)];
+	let head_bits = match head.iter().position(|&byte| byte != 0) {
+		Some(i) => (head.len() - i) as u64 * 8 - head[i].leading_zeros() as u64 - 1,
+		None => 0,
+	};
+	let adjusted_len = (exponent.len().saturating_sub(32) as u64).saturating_mul(8).saturating_add(head_bits);
+	cmp::max(200, complexity.saturating_mul(cmp::max(adjusted_len, 1)) / 20)
+}
+
+/// Address that signed `hash` with the signature `(v, r, s)`, if the signature is valid.
+pub fn ecrecover(hash: &H256, v: u8, r: &H256, s: &H256) -> Option<Address> {
+	let mut input = [0u8; 128];
+	input[..32].copy_from_slice(&<[u8; 32]>::from(*hash));
+	input[63] = v;
+	input[64..96].copy_from_slice(&<[u8; 32]>::from(*r));
+	input[96..].copy_from_slice(&<[u8; 32]>::from(*s));
+	let mut result = [0u8; 32];
+	call(ECRECOVER, 3000, &input, &mut result).ok()?;
+	let mut signer = [0u8; 20];
+	signer.copy_from_slice(&result[12..]);
+	let signer = Address::from(signer);
+	if signer.is_zero() { None } else { Some(signer) }
+}
+
+/// SHA-256 hash of `data`.
+///
+/// # Errors
+///
+/// If the runtime does not provide the precompile or the call runs out of gas.
+pub fn sha256(data: &[u8]) -> Result<H256, CallError> {
+	let mut result = [0u8; 32];
+	call(SHA256, linear_cost(60, 12, data), data, &mut result)?;
+	Ok(H256::from(result))
+}
+
+/// RIPEMD-160 hash of `data`.
+///
+/// # Errors
+///
+/// If the runtime does not provide the precompile or the call runs out of gas.
+pub fn ripemd160(data: &[u8]) -> Result<[u8; 20], CallError> {
+	let mut result = [0u8; 32];
+	call(RIPEMD160, linear_cost(600, 120, data), data, &mut result)?;
+	let mut hash = [0u8; 20];
+	hash.copy_from_slice(&result[12..]);
+	Ok(hash)
+}
+
+/// `base ^ exponent % modulus`, as a big-endian number as long as `modulus`.
+///
+/// # Errors
+///
+/// If the runtime does not provide the precompile or the call runs out of gas, which it does
+/// on runtimes pricing `modexp` after EIP-7883.
+pub fn modexp(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Result<pwasm_std::Vec<u8>, CallError> {
+	let mut input = pwasm_std::Vec::with_capacity(96 + base.len() + exponent.len() + modulus.len());
+	for len in &[base.len(), exponent.len(), modulus.len()] {
+		let mut word = [0u8; 32];
+		U256::from(*len as u64).to_big_endian(&mut word);
+		input.extend_from_slice(&word);
+	}
+	input.extend_from_slice(base);
+	input.extend_from_slice(exponent);
+	input.extend_from_slice(modulus);
+	let mut result = pwasm_std::Vec::new();
+	result.resize(modulus.len(), 0);
+	call(MODEXP, modexp_cost(base.len(), exponent, modulus.len()), &input, &mut result)?;
+	Ok(result)
+}
+
+/// Sum of two points of the alt_bn128 curve, each given as its `(x, y)` coordinates.
+///
+/// # Errors
+///
+/// If a point is not on the curve.
+pub fn bn128_add(a: &[u8; 64], b: &[u8; 64]) -> Result<[u8; 64], CallError> {
+	let mut input = [0u8; 128];
+	input[..64].copy_from_slice(a);
+	input[64..].copy_from_slice(b);
+	let mut result = [0u8; 64];
+	call(BN128_ADD, 500, &input, &mut result)?;
+	Ok(result)
+}
+
+/// Product of a point of the alt_bn128 curve and a scalar.
+///
+/// # Errors
+///
+/// If the point is not on the curve.
+pub fn bn128_mul(point: &[u8; 64], scalar: &U256) -> Result<[u8; 64], CallError> {
+	let mut input = [0u8; 96];
+	input[..64].copy_from_slice(point);
+	scalar.to_big_endian(&mut input[64..]);
+	let mut result = [0u8; 64];
+	call(BN128_MUL, 40_000, &input, &mut result)?;
+	Ok(result)
+}
+
+/// Whether the pairing check holds for the given `(G1, G2)` point pairs, each encoded in 192
+/// bytes.
+///
+/// # Errors
+///
+/// If a point is invalid.
+pub fn bn128_pairing(pairs: &[[u8; 192]]) -> Result<bool, CallError> {
+	let mut input = pwasm_std::Vec::with_capacity(192 * pairs.len());
+	for pair in pairs {
+		input.extend_from_slice(&pair[..]);
+	}
+	let mut result = [0u8; 32];
+	call(BN128_PAIRING, 100_000 + 80_000 * pairs.len() as u64, &input, &mut result)?;
+	Ok(result[31] == 1)
+}
+
+/// BLAKE2b compression function `F` with `rounds` rounds, returning the new state vector.
+///
+/// # Errors
+///
+/// If the runtime rejects the input.
+pub fn blake2f(rounds: u32, h: &[u8; 64], m: &[u8; 128], t: &[u8; 16], last_block: bool) -> Result<[u8; 64], CallError> {
+	let mut input = [0u8; 213];
+	input[..4].copy_from_slice(&[(rounds >> 24) as u8, (rounds >> 16) as u8, (rounds >> 8) as u8, rounds as u8]);
+	input[4..68].copy_from_slice(h);
+	input[68..196].copy_from_slice(m);
+	input[196..212].copy_from_slice(t);
+	input[212] = last_block as u8;
+	let mut result = [0u8; 64];
+	call(BLAKE2F, rounds as u64, &input[..], &mut result)?;
+	Ok(result)
+}
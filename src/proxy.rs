@@ -0,0 +1,105 @@
+//! Upgradeable proxies, with the storage layout of EIP-1967.
+//!
+//! The proxy keeps the address of its implementation and of its admin in the standard slots,
+//! so block explorers and tools recognize it, and runs every call with the code of the
+//! implementation through [`delegate_to`]. Authorizing upgrades is up to the proxy.
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub fn call() {
+//! 	if sender() == proxy::admin() && is_upgrade(&input()) {
+//! 		proxy::set_implementation(&new_implementation(&input()));
+//! 		ret(&[]);
+//! 	}
+//! 	proxy::delegate_to(&proxy::implementation())
+//! }
+//! ```
+//!
+//! [`delegate_to`]: fn.delegate_to.html
+
+use pwasm_std::types::{H256, Address};
+
+use ext;
+#[cfg(all(feature = "eip211", feature = "kip6"))]
+use ext::CallError;
+
+/// Slot of the implementation address, `keccak256("eip1967.proxy.implementation") - 1`.
+pub const IMPLEMENTATION_SLOT: [u8; 32] = [
+	0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+	0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+];
+
+/// Slot of the admin address, `keccak256("eip1967.proxy.admin") - 1`.
+pub const ADMIN_SLOT: [u8; 32] = [
+	0xb5, 0x31, 0x27, 0x68, 0x4a, 0x56, 0x8b, 0x31, 0x73, 0xae, 0x13, 0xb9, 0xf8, 0xa6, 0x01, 0x6e,
+	0x24, 0x3e, 0x63, 0xb6, 0xe8, 0xee, 0x11, 0x78, 0xd6, 0xa7, 0x17, 0x85, 0x0b, 0x5d, 0x61, 0x03,
+];
+
+/// Topic of `Upgraded(address)`, logged with the new implementation as the second topic.
+pub const UPGRADED_TOPIC: [u8; 32] = [
+	0xbc, 0x7c, 0xd7, 0x5a, 0x20, 0xee, 0x27, 0xfd, 0x9a, 0xde, 0xba, 0xb3, 0x20, 0x41, 0xf7, 0x55,
+	0x21, 0x4d, 0xbc, 0x6b, 0xff, 0xa9, 0x0c, 0xc0, 0x22, 0x5b, 0x39, 0xda, 0x2e, 0x5c, 0x2d, 0x3b,
+];
+
+/// Topic of `AdminChanged(address,address)`, logged with the previous and the new admin as data.
+pub const ADMIN_CHANGED_TOPIC: [u8; 32] = [
+	0x7e, 0x64, 0x4d, 0x79, 0x42, 0x2f, 0x17, 0xc0, 0x1e, 0x48, 0x94, 0xb5, 0xf4, 0xf5, 0x88, 0xd3,
+	0x31, 0xeb, 0xfa, 0x28, 0x65, 0x3d, 0x42, 0xae, 0x83, 0x2d, 0xc5, 0x9e, 0x38, 0xc9, 0x79, 0x8f,
+];
+
+fn address_word(address: &Address) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	word[12..].copy_from_slice(&<[u8; 20]>::from(*address));
+	word
+}
+
+fn read_address(slot: [u8; 32]) -> Address {
+	let value = ::read(&H256::from(slot));
+	let mut address = [0u8; 20];
+	address.copy_from_slice(&value[12..]);
+	Address::from(address)
+}
+
+/// Current implementation, zero if none was set.
+pub fn implementation() -> Address {
+	read_address(IMPLEMENTATION_SLOT)
+}
+
+/// Sets the implementation and logs `Upgraded(implementation)`.
+pub fn set_implementation(implementation: &Address) {
+	::write(&H256::from(IMPLEMENTATION_SLOT), &address_word(implementation));
+	ext::log(&[H256::from(UPGRADED_TOPIC), H256::from(address_word(implementation))], &[]);
+}
+
+/// Current admin, zero if none was set.
+pub fn admin() -> Address {
+	read_address(ADMIN_SLOT)
+}
+
+/// Sets the admin and logs `AdminChanged(previous, admin)`.
+pub fn set_admin(admin: &Address) {
+	let mut data = [0u8; 64];
+	data[..32].copy_from_slice(&address_word(&self::admin()));
+	data[32..].copy_from_slice(&address_word(admin));
+	::write(&H256::from(ADMIN_SLOT), &address_word(admin));
+	ext::log(&[H256::from(ADMIN_CHANGED_TOPIC)], &data);
+}
+
+#[cfg(all(feature = "eip211", feature = "kip6"))]
+/// Runs the current call with the code of `implementation`, with all
+/// [`forwardable_gas`](../fn.forwardable_gas.html), and returns its exact result, requires
+/// EIP-211 and KIP-6.
+///
+/// The implementation sees the sender, value and storage of the proxy. Reverts with the same
+/// data if the implementation reverts.
+///
+/// # Panics
+///
+/// Traps if the implementation fails otherwise.
+pub fn delegate_to(implementation: &Address) -> ! {
+	match ext::call_code_with_output(ext::forwardable_gas(), implementation, &ext::input()) {
+		Ok(result) => ext::ret(&result),
+		Err(CallError::Reverted(data)) => ext::revert(&data),
+		Err(_) => ext::trap(),
+	}
+}
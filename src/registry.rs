@@ -0,0 +1,209 @@
+//! Registry of contract addresses by name hash.
+//!
+//! The contract side keeps `name → address` entries in the storage of the current contract and
+//! only lets its admin change them, logging every change. Other contracts look entries up
+//! through a caching [`Resolver`](struct.Resolver.html).
+//!
+//! Calls use the Solidity ABI: `resolve(bytes32)`, `register(bytes32,address)` and
+//! `setAdmin(address)`, with addresses as right-aligned 32-byte words.
+
+use pwasm_std::{
+	self,
+	types::{H256, Address}
+};
+
+use ext::{self, gas_or_forwardable, Error};
+use hash::keccak256;
+
+/// Selector of `resolve(bytes32)`.
+pub const RESOLVE: [u8; 4] = [0x5c, 0x23, 0xbd, 0xf5];
+/// Selector of `register(bytes32,address)`.
+pub const REGISTER: [u8; 4] = [0xd2, 0x20, 0x57, 0xa9];
+/// Selector of `setAdmin(address)`.
+pub const SET_ADMIN: [u8; 4] = [0x70, 0x4b, 0x6c, 0x02];
+
+/// Topic of `Registered(bytes32,address)`, logged with the name as the second topic.
+pub const REGISTERED_TOPIC: [u8; 32] = [
+	0x7d, 0x91, 0x7f, 0xcb, 0xc9, 0xa2, 0x9a, 0x97, 0x05, 0xff, 0x99, 0x36, 0xff, 0xa5, 0x99, 0x50,
+	0x0e, 0x4f, 0xd9, 0x02, 0xe4, 0x48, 0x6b, 0xae, 0x31, 0x74, 0x14, 0xfe, 0x96, 0x7b, 0x30, 0x7c,
+];
+/// Topic of `AdminChanged(address)`.
+pub const ADMIN_CHANGED_TOPIC: [u8; 32] = [
+	0x7c, 0xe7, 0xec, 0x0b, 0x50, 0x37, 0x8f, 0xb6, 0xc0, 0x18, 0x6f, 0xfb, 0x5f, 0x48, 0x32, 0x5f,
+	0x65, 0x93, 0xfc, 0xb4, 0xca, 0x43, 0x86, 0xf2, 0x18, 0x61, 0xaf, 0x31, 0x29, 0x18, 0x8f, 0x5c,
+];
+
+/// Storage key of the admin address.
+const ADMIN_KEY: [u8; 32] = [0xff; 32];
+
+const ENTRY_PREFIX: &[u8] = b"registry-entry";
+
+/// Storage key of the entry of `name`, `keccak256("registry-entry" || name)`, so that names
+/// cannot collide with the admin key or other storage of the contract.
+fn entry_key(name: &H256) -> H256 {
+	let mut preimage = [0u8; 46];
+	preimage[..14].copy_from_slice(ENTRY_PREFIX);
+	preimage[14..].copy_from_slice(&<[u8; 32]>::from(*name));
+	keccak256(&preimage)
+}
+
+fn to_word(address: &Address) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	word[12..].copy_from_slice(&<[u8; 20]>::from(*address));
+	word
+}
+
+fn to_address(word: &[u8]) -> Address {
+	let mut address = [0u8; 20];
+	address.copy_from_slice(&word[12..32]);
+	Address::from(address)
+}
+
+fn to_h256(word: &[u8]) -> H256 {
+	let mut hash = [0u8; 32];
+	hash.copy_from_slice(&word[..32]);
+	H256::from(hash)
+}
+
+/// Sets the admin of the registry, to be called from the constructor.
+pub fn init(admin: &Address) {
+	::write(&H256::from(ADMIN_KEY), &to_word(admin));
+}
+
+/// Admin of the registry.
+pub fn admin() -> Address {
+	to_address(&::read(&H256::from(ADMIN_KEY)))
+}
+
+/// Address registered under `name`, or zero.
+pub fn resolve(name: &H256) -> Address {
+	to_address(&::read(&entry_key(name)))
+}
+
+/// Registers `address` under `name` and logs `Registered`.
+///
+/// # Errors
+///
+/// Returns [`Error`](../struct.Error.html) if the sender is not the admin.
+pub fn register(name: &H256, address: &Address) -> Result<(), Error> {
+	if ext::sender() != admin() {
+		return Err(Error);
+	}
+	::write(&entry_key(name), &to_word(address));
+	ext::log(&[H256::from(REGISTERED_TOPIC), *name], &to_word(address));
+	Ok(())
+}
+
+/// Hands the registry over to `new_admin` and logs `AdminChanged`.
+///
+/// # Errors
+///
+/// Returns [`Error`](../struct.Error.html) if the sender is not the admin.
+pub fn set_admin(new_admin: &Address) -> Result<(), Error> {
+	if ext::sender() != admin() {
+		return Err(Error);
+	}
+	init(new_admin);
+	ext::log(&[H256::from(ADMIN_CHANGED_TOPIC)], &to_word(new_admin));
+	Ok(())
+}
+
+/// Handles a call to the registry contract.
+///
+/// # Panics
+///
+/// Reverts with a reason on an unknown selector, malformed arguments, including address words
+/// with non-zero upper bytes, or an unauthorized change.
+pub fn dispatch(input: &[u8]) -> ! {
+	if input.len() < 4 {
+		ext::revert_with_reason("registry: missing selector");
+	}
+	let (selector, args) = input.split_at(4);
+	let word = |i: usize| {
+		if args.len() < 32 * (i + 1) {
+			ext::revert_with_reason("registry: missing argument");
+		}
+		&args[32 * i..32 * (i + 1)]
+	};
+	let address = |i: usize| {
+		let word = word(i);
+		if word[..12].iter().any(|b| *b != 0) {
+			ext::revert_with_reason("registry: bad address");
+		}
+		to_address(word)
+	};
+	match [selector[0], selector[1], selector[2], selector[3]] {
+		RESOLVE => ext::ret(&to_word(&resolve(&to_h256(word(0))))),
+		REGISTER => {
+			if register(&to_h256(word(0)), &address(1)).is_err() {
+				ext::revert_with_reason("registry: sender is not the admin");
+			}
+			ext::ret(&[])
+		},
+		SET_ADMIN => {
+			if set_admin(&address(0)).is_err() {
+				ext::revert_with_reason("registry: sender is not the admin");
+			}
+			ext::ret(&[])
+		},
+		_ => ext::revert_with_reason("registry: unknown selector"),
+	}
+}
+
+/// Client of a registry contract, caching resolved addresses.
+///
+/// Queries are given all [`forwardable_gas`] unless limited with [`with_gas`]. Without KIP-6,
+/// the gas must be set.
+///
+/// [`forwardable_gas`]: ../fn.forwardable_gas.html
+/// [`with_gas`]: #method.with_gas
+#[derive(Clone, Debug)]
+pub struct Resolver {
+	registry: Address,
+	gas: Option<u64>,
+	cache: pwasm_std::Vec<(H256, Address)>,
+}
+
+impl Resolver {
+	/// Resolver using the registry deployed at `registry`.
+	pub fn new(registry: Address) -> Self {
+		Resolver { registry: registry, gas: None, cache: pwasm_std::Vec::new() }
+	}
+
+	/// Same resolver, with queries limited to `gas`.
+	pub fn with_gas(self, gas: u64) -> Self {
+		Resolver { gas: Some(gas), ..self }
+	}
+
+	/// Address of the registry.
+	pub fn registry(&self) -> Address {
+		self.registry
+	}
+
+	/// Address registered under `name`, queried once and cached afterwards.
+	///
+	/// # Errors
+	///
+	/// Returns [`Error`](../struct.Error.html) if the query fails or nothing is registered.
+	pub fn resolve(&mut self, name: &H256) -> Result<Address, Error> {
+		if let Some(&(_, address)) = self.cache.iter().find(|entry| entry.0 == *name) {
+			return Ok(address);
+		}
+		let mut input = [0u8; 36];
+		input[..4].copy_from_slice(&RESOLVE);
+		input[4..].copy_from_slice(&<[u8; 32]>::from(*name));
+		let mut result = [0u8; 32];
+		ext::static_call(gas_or_forwardable(self.gas)?, &self.registry, &input, &mut result)?;
+		let address = to_address(&result);
+		if address.is_zero() {
+			return Err(Error);
+		}
+		self.cache.push((*name, address));
+		Ok(address)
+	}
+
+	/// Drops the cached address of `name`.
+	pub fn invalidate(&mut self, name: &H256) {
+		self.cache.retain(|entry| entry.0 != *name);
+	}
+}
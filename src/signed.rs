@@ -0,0 +1,162 @@
+//! Execution of calls authorized off-chain with a signature and relayed by anyone.
+//!
+//! The signer signs the EIP-712 struct `SignedCall(bytes data,uint256 nonce,uint256 deadline)`
+//! under the domain of the contract. [`execute_signed`] checks the deadline, the nonce of the
+//! signer and the signature, then calls the contract itself with the signer appended to the
+//! call data, as in ERC-2771. The called code gets the signer back with [`signed_sender`].
+//!
+//! Other calls of the contract to itself, made by batches, proxies or forwarders, could append
+//! any address as well. The relayed input is therefore marked while the call runs, in a storage
+//! slot which is transient with the `eip1153` feature, and [`signed_sender`] only trusts the
+//! input it finds marked.
+//!
+//! [`execute_signed`]: fn.execute_signed.html
+//! [`signed_sender`]: fn.signed_sender.html
+
+#[cfg(feature = "kip6")]
+use pwasm_std::Vec;
+use pwasm_std::types::{H256, U256, Address};
+
+use eip712;
+use ext::{self, Error};
+use hash::keccak256;
+use precompiles;
+#[cfg(not(feature = "eip1153"))]
+use storage::{read as read_marker, write as write_marker};
+#[cfg(feature = "eip1153")]
+use storage::{tread as read_marker, twrite as write_marker};
+
+/// Type of a signed call in EIP-712 notation.
+pub const SIGNED_CALL_TYPE: &str = "SignedCall(bytes data,uint256 nonce,uint256 deadline)";
+
+/// Prefix of the storage keys of signer nonces.
+const NONCE_PREFIX: &[u8] = b"signed-call-nonce";
+
+/// Preimage of the storage key marking the input of the running signed call.
+const RELAYED_KEY: &[u8] = b"signed-call-relayed";
+
+/// Recoverable secp256k1 signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+	/// Recovery id, `27` or `28`.
+	pub v: u8,
+	/// `r` component.
+	pub r: H256,
+	/// `s` component.
+	pub s: H256,
+}
+
+impl Signature {
+	/// Signature from the 65-byte `r || s || v` layout.
+	pub fn from_bytes(bytes: &[u8; 65]) -> Self {
+		let mut r = [0u8; 32];
+		let mut s = [0u8; 32];
+		r.copy_from_slice(&bytes[..32]);
+		s.copy_from_slice(&bytes[32..64]);
+		Signature { v: bytes[64], r: r.into(), s: s.into() }
+	}
+
+	/// Signature in the 65-byte `r || s || v` layout.
+	pub fn to_bytes(&self) -> [u8; 65] {
+		let mut bytes = [0u8; 65];
+		bytes[..32].copy_from_slice(&<[u8; 32]>::from(self.r));
+		bytes[32..64].copy_from_slice(&<[u8; 32]>::from(self.s));
+		bytes[64] = self.v;
+		bytes
+	}
+}
+
+/// Address that signed `hash`, recovered by the `ecrecover` precompile.
+pub fn ecrecover(hash: &H256, signature: &Signature) -> Option<Address> {
+	precompiles::ecrecover(hash, signature.v, &signature.r, &signature.s)
+}
+
+fn nonce_key(signer: &Address) -> H256 {
+	let mut preimage = [0u8; 37];
+	preimage[..17].copy_from_slice(NONCE_PREFIX);
+	preimage[17..].copy_from_slice(&<[u8; 20]>::from(*signer));
+	keccak256(&preimage)
+}
+
+/// Nonce the next call signed by `signer` has to carry.
+pub fn nonce_of(signer: &Address) -> U256 {
+	U256::from_big_endian(&::read(&nonce_key(signer)))
+}
+
+/// Hash signed to authorize `call` under the domain with `domain_separator`.
+pub fn call_hash(domain_separator: &H256, call: &[u8], nonce: U256, deadline: u64) -> H256 {
+	let mut encoded = [0u8; 4 * 32];
+	encoded[..32].copy_from_slice(&<[u8; 32]>::from(eip712::type_hash(SIGNED_CALL_TYPE)));
+	encoded[32..64].copy_from_slice(&<[u8; 32]>::from(keccak256(call)));
+	nonce.to_big_endian(&mut encoded[64..96]);
+	U256::from(deadline).to_big_endian(&mut encoded[96..]);
+	eip712::typed_data_hash(domain_separator, &keccak256(&encoded))
+}
+
+/// Signer of an authorized call.
+///
+/// # Errors
+///
+/// Returns [`Error`](../struct.Error.html) if the deadline has passed, the signature is
+/// invalid or the nonce is not the next one of the signer.
+pub fn verify(domain_separator: &H256, call: &[u8], nonce: U256, deadline: u64, signature: &Signature) -> Result<Address, Error> {
+	if ext::timestamp() > deadline {
+		return Err(Error);
+	}
+	let signer = ecrecover(&call_hash(domain_separator, call, nonce, deadline), signature).ok_or(Error)?;
+	if nonce_of(&signer) != nonce {
+		return Err(Error);
+	}
+	Ok(signer)
+}
+
+#[cfg(feature = "kip6")]
+/// Verifies an authorized call, consumes its nonce and executes it with all
+/// [`forwardable_gas`](../fn.forwardable_gas.html), filling `result`, requires KIP-6.
+///
+/// Returns the signer on success.
+///
+/// # Errors
+///
+/// Returns [`Error`](../struct.Error.html) if verification fails, or if the call itself fails;
+/// in the latter case the nonce stays consumed.
+pub fn execute_signed(
+	domain_separator: &H256,
+	call: &[u8],
+	nonce: U256,
+	deadline: u64,
+	signature: &Signature,
+	result: &mut [u8],
+) -> Result<Address, Error> {
+	let signer = verify(domain_separator, call, nonce, deadline, signature)?;
+	let mut next = [0u8; 32];
+	(nonce + U256::one()).to_big_endian(&mut next);
+	::write(&nonce_key(&signer), &next);
+
+	let mut input = Vec::with_capacity(call.len() + 20);
+	input.extend_from_slice(call);
+	input.extend_from_slice(&<[u8; 20]>::from(signer));
+	let relayed = keccak256(RELAYED_KEY);
+	write_marker(&relayed, &<[u8; 32]>::from(keccak256(&input)));
+	let outcome = ext::call(ext::forwardable_gas(), &ext::address(), U256::zero(), &input, result);
+	write_marker(&relayed, &[0u8; 32]);
+	outcome?;
+	Ok(signer)
+}
+
+/// Signer and original input of a call made by [`execute_signed`](fn.execute_signed.html).
+///
+/// Returns `None` unless the contract is calling itself with the input relayed by a running
+/// [`execute_signed`](fn.execute_signed.html), so the appended signer can be trusted.
+pub fn signed_sender(input: &[u8]) -> Option<(Address, &[u8])> {
+	if input.len() < 20 || ext::sender() != ext::address() {
+		return None;
+	}
+	if read_marker(&keccak256(RELAYED_KEY)) != <[u8; 32]>::from(keccak256(input)) {
+		return None;
+	}
+	let (call, signer) = input.split_at(input.len() - 20);
+	let mut address = [0u8; 20];
+	address.copy_from_slice(signer);
+	Some((Address::from(address), call))
+}
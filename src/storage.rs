@@ -1,12 +1,15 @@
 //! Storage extensions for pwasm-ethereum.
 //! Storage api is a key-value storage where both key and value are 32 bytes in len
 
-use pwasm_std::types::H256;
+use pwasm_std::{self, types::H256};
 
-extern "C" {
-	fn storage_read(key: *const u8, dst: *mut u8);
-	fn storage_write(key: *const u8, src: *const u8);
-}
+use ext;
+
+use backend::external::{storage_read, storage_write};
+#[cfg(feature = "storage-batch")]
+use backend::external::{storage_read_multi, storage_write_multi};
+#[cfg(feature = "eip1153")]
+use backend::external::{transient_storage_read, transient_storage_write};
 
 /// Performs read from the storage.
 pub fn read(key: &H256) -> [u8; 32] {
@@ -23,3 +26,199 @@ pub fn write(key: &H256, val: &[u8; 32]) {
 		storage_write(key.as_ptr(), val.as_ptr());
 	}
 }
+
+/// Performs read from the transient storage (EIP-1153).
+///
+/// Transient storage is cleared at the end of the transaction; keys never written in the
+/// transaction read as zero.
+#[cfg(feature = "eip1153")]
+pub fn tread(key: &H256) -> [u8; 32] {
+	let mut dst = [0u8; 32];
+	unsafe {
+		transient_storage_read(key.as_ptr(), dst.as_mut_ptr());
+	}
+	dst
+}
+
+/// Performs write to the transient storage (EIP-1153)
+#[cfg(feature = "eip1153")]
+pub fn twrite(key: &H256, val: &[u8; 32]) {
+	unsafe {
+		transient_storage_write(key.as_ptr(), val.as_ptr());
+	}
+}
+
+/// Reads the values of all `keys` into `dst`, which must have the same length.
+///
+/// Uses a single host call with the `storage-batch` feature, one per key otherwise.
+///
+/// # Panics
+///
+/// Traps if `keys` and `dst` differ in length.
+pub fn read_many(keys: &[H256], dst: &mut [[u8; 32]]) {
+	if keys.len() != dst.len() {
+		ext::trap();
+	}
+	#[cfg(feature = "storage-batch")]
+	{
+		if !keys.is_empty() {
+			let mut buf = pwasm_std::Vec::with_capacity(32 * keys.len());
+			for key in keys {
+				buf.extend_from_slice(&<[u8; 32]>::from(*key));
+			}
+			unsafe {
+				storage_read_multi(buf.as_ptr(), keys.len() as u32, dst.as_mut_ptr() as *mut u8);
+			}
+		}
+	}
+	#[cfg(not(feature = "storage-batch"))]
+	{
+		for (key, value) in keys.iter().zip(dst.iter_mut()) {
+			*value = read(key);
+		}
+	}
+}
+
+/// Writes all `(key, value)` entries, in order.
+///
+/// Uses a single host call with the `storage-batch` feature, one per entry otherwise.
+pub fn write_many(entries: &[(H256, [u8; 32])]) {
+	#[cfg(feature = "storage-batch")]
+	{
+		if !entries.is_empty() {
+			let mut buf = pwasm_std::Vec::with_capacity(64 * entries.len());
+			for &(ref key, ref value) in entries {
+				buf.extend_from_slice(&<[u8; 32]>::from(*key));
+				buf.extend_from_slice(value);
+			}
+			unsafe {
+				storage_write_multi(buf.as_ptr(), entries.len() as u32);
+			}
+		}
+	}
+	#[cfg(not(feature = "storage-batch"))]
+	{
+		for &(ref key, ref value) in entries {
+			write(key, value);
+		}
+	}
+}
+
+struct Entry {
+	key: H256,
+	value: [u8; 32],
+	/// Value in storage, if known.
+	stored: Option<[u8; 32]>,
+}
+
+/// Storage session caching reads and buffering writes in wasm memory.
+///
+/// Each key is read from the storage at most once, and only the last value written to a key is
+/// written back, unless it is already stored. Writes are flushed by [`flush`] or when the
+/// session is dropped; a trapping execution discards them anyway. Lookups are linear in the
+/// number of keys touched, which suits sessions of up to a few dozen keys.
+///
+/// [`ret`] and [`suicide`] halt without dropping the session, losing the writes not flushed
+/// yet: end the session with [`commit`] before halting, or halt with [`CachedStorage::ret`].
+///
+/// The session does not see writes made outside of it, including by calls back into the
+/// contract, so flush it before making calls.
+///
+/// [`flush`]: #method.flush
+/// [`commit`]: #method.commit
+/// [`CachedStorage::ret`]: #method.ret
+/// [`ret`]: fn.ret.html
+/// [`suicide`]: fn.suicide.html
+#[derive(Default)]
+#[must_use = "writes are buffered until the session is committed or dropped"]
+pub struct CachedStorage {
+	entries: pwasm_std::Vec<Entry>,
+}
+
+impl CachedStorage {
+	/// Empty session.
+	pub fn new() -> Self {
+		CachedStorage { entries: pwasm_std::Vec::new() }
+	}
+
+	fn position(&self, key: &H256) -> Option<usize> {
+		self.entries.iter().position(|entry| entry.key == *key)
+	}
+
+	/// Reads the given keys into the cache, in a single host call with the `storage-batch`
+	/// feature.
+	pub fn prefetch(&mut self, keys: &[H256]) {
+		let mut missing: pwasm_std::Vec<H256> = pwasm_std::Vec::new();
+		for key in keys {
+			if self.position(key).is_none() && !missing.contains(key) {
+				missing.push(*key);
+			}
+		}
+		let mut values = pwasm_std::Vec::new();
+		values.resize(missing.len(), [0u8; 32]);
+		read_many(&missing, &mut values);
+		for (key, value) in missing.into_iter().zip(values.into_iter()) {
+			self.entries.push(Entry { key: key, value: value, stored: Some(value) });
+		}
+	}
+
+	/// Value of `key`, as last written in the session or read from the storage.
+	pub fn read(&mut self, key: &H256) -> [u8; 32] {
+		match self.position(key) {
+			Some(index) => self.entries[index].value,
+			None => {
+				let value = read(key);
+				self.entries.push(Entry { key: *key, value: value, stored: Some(value) });
+				value
+			},
+		}
+	}
+
+	/// Sets the value of `key`, written to the storage on flush.
+	pub fn write(&mut self, key: &H256, value: &[u8; 32]) {
+		match self.position(key) {
+			Some(index) => self.entries[index].value = *value,
+			None => self.entries.push(Entry { key: *key, value: *value, stored: None }),
+		}
+	}
+
+	/// Whether some writes are not flushed yet.
+	pub fn is_dirty(&self) -> bool {
+		self.entries.iter().any(|entry| entry.stored != Some(entry.value))
+	}
+
+	/// Writes back the values that changed, in the order their keys were first touched.
+	pub fn flush(&mut self) {
+		let dirty: pwasm_std::Vec<(H256, [u8; 32])> = self.entries.iter()
+			.filter(|entry| entry.stored != Some(entry.value))
+			.map(|entry| (entry.key, entry.value))
+			.collect();
+		write_many(&dirty);
+		for entry in self.entries.iter_mut() {
+			entry.stored = Some(entry.value);
+		}
+	}
+
+	/// Flushes the session and ends it.
+	pub fn commit(mut self) {
+		self.flush();
+	}
+
+	/// Commits the session, then halts like [`ret`](fn.ret.html).
+	pub fn ret(self, data: &[u8]) -> ! {
+		self.commit();
+		ext::ret(data)
+	}
+}
+
+impl Drop for CachedStorage {
+	fn drop(&mut self) {
+		#[cfg(feature = "std")]
+		{
+			if ::std::thread::panicking() {
+				return;
+			}
+		}
+		self.flush();
+	}
+}
@@ -0,0 +1,365 @@
+//! Builder-style setup of the native externalities for contract unit tests.
+//!
+//! [`ExternalsBuilder`] describes the environment a contract entry point runs in: the call
+//! context, storage, balances, block and the calls the contract is expected to make. Building
+//! it installs that state in the native backend, after which every function of this crate is
+//! served by it.
+//!
+//! The module requires the `native` feature rather than only `std`, as the externalities it
+//! configures are those of the native backend; `native` enables `std` itself.
+//!
+//! ```ignore
+//! let externals = ExternalsBuilder::new()
+//! 	.sender(owner)
+//! 	.storage(key, value)
+//! 	.expect_call(ExpectedCall::to(token).returning(vec![1]))
+//! 	.build();
+//! native::execute(|| contract::call());
+//! externals.verify();
+//! ```
+//!
+//! Negative paths are checked in one line with [`expect_revert`] and its variants:
+//!
+//! ```ignore
+//! expect_revert_reason(native::transact(token, U256::zero(), input), "insufficient balance");
+//! ```
+//!
+//! [`ExternalsBuilder`]: struct.ExternalsBuilder.html
+//! [`expect_revert`]: fn.expect_revert.html
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use pwasm_std::types::{H256, U256, Address};
+
+use ext::CallError;
+use native::{self, Externalities, Outcome};
+
+/// How a mocked call ends.
+#[derive(Clone, Debug)]
+enum Response {
+	Return(Vec<u8>),
+	Revert(Vec<u8>),
+	Fail,
+}
+
+/// Call a contract is expected to make, and its mocked result.
+#[derive(Clone, Debug)]
+pub struct ExpectedCall {
+	address: Address,
+	input: Option<Vec<u8>>,
+	value: Option<U256>,
+	response: Response,
+	times: usize,
+}
+
+impl ExpectedCall {
+	/// Expects a single call to `address` with any input, returning no data.
+	pub fn to(address: Address) -> Self {
+		ExpectedCall { address: address, input: None, value: None, response: Response::Return(Vec::new()), times: 1 }
+	}
+
+	/// Only matches calls with `input`.
+	pub fn with_input(mut self, input: Vec<u8>) -> Self {
+		self.input = Some(input);
+		self
+	}
+
+	/// Only matches calls carrying `value`.
+	pub fn with_value(mut self, value: U256) -> Self {
+		self.value = Some(value);
+		self
+	}
+
+	/// Makes the call return `result`.
+	pub fn returning(mut self, result: Vec<u8>) -> Self {
+		self.response = Response::Return(result);
+		self
+	}
+
+	/// Makes the call revert with `data`, which the caller gets as [`CallError::Reverted`].
+	///
+	/// Without the `eip140` and `eip211` features the call traps instead, like a
+	/// [`failing`](#method.failing) one.
+	///
+	/// [`CallError::Reverted`]: ../enum.CallError.html#variant.Reverted
+	pub fn reverting(mut self, data: Vec<u8>) -> Self {
+		self.response = Response::Revert(data);
+		self
+	}
+
+	/// Makes the call fail.
+	pub fn failing(mut self) -> Self {
+		self.response = Response::Fail;
+		self
+	}
+
+	/// Expects the call to be made `times` times.
+	pub fn times(mut self, times: usize) -> Self {
+		self.times = times;
+		self
+	}
+
+	fn matches(&self, input: &[u8], value: U256) -> bool {
+		self.input.as_ref().map_or(true, |expected| expected[..] == *input)
+			&& self.value.map_or(true, |expected| expected == value)
+	}
+}
+
+/// Expected call with the number of times it was made.
+struct Expectation {
+	call: ExpectedCall,
+	count: Cell<usize>,
+}
+
+/// Builder of the native externalities.
+pub struct ExternalsBuilder {
+	ext: Externalities,
+	storage: Vec<(H256, [u8; 32])>,
+	expectations: Vec<Rc<Expectation>>,
+}
+
+impl Default for ExternalsBuilder {
+	fn default() -> Self {
+		ExternalsBuilder::new()
+	}
+}
+
+impl ExternalsBuilder {
+	/// Starts from empty externalities.
+	pub fn new() -> Self {
+		ExternalsBuilder { ext: Externalities::default(), storage: Vec::new(), expectations: Vec::new() }
+	}
+
+	/// Sets the address of the contract under test.
+	pub fn address(mut self, address: Address) -> Self {
+		self.ext.frame_mut().address = address;
+		self
+	}
+
+	/// Sets the direct caller.
+	pub fn sender(mut self, sender: Address) -> Self {
+		self.ext.frame_mut().sender = sender;
+		self
+	}
+
+	/// Sets the transaction origin.
+	pub fn origin(mut self, origin: Address) -> Self {
+		self.ext.origin = origin;
+		self
+	}
+
+	/// Sets the gas price of the transaction.
+	pub fn gas_price(mut self, gas_price: U256) -> Self {
+		self.ext.gas_price = gas_price;
+		self
+	}
+
+	/// Sets the value deposited with the call.
+	pub fn value(mut self, value: U256) -> Self {
+		self.ext.frame_mut().value = value;
+		self
+	}
+
+	/// Sets the call input.
+	pub fn input(mut self, input: Vec<u8>) -> Self {
+		self.ext.frame_mut().input = input;
+		self
+	}
+
+	/// Sets the gas available to the call.
+	pub fn gas(mut self, gas: u64) -> Self {
+		self.ext.frame_mut().gas = gas;
+		self
+	}
+
+	/// Sets a storage value of the contract under test, whatever the order of the call to
+	/// [`address`](#method.address).
+	pub fn storage(mut self, key: H256, value: [u8; 32]) -> Self {
+		self.storage.push((key, value));
+		self
+	}
+
+	/// Sets a storage value of any account.
+	pub fn storage_of(mut self, address: Address, key: H256, value: [u8; 32]) -> Self {
+		self.ext.set_storage(address, key, value);
+		self
+	}
+
+	/// Sets the balance of an account.
+	pub fn balance(mut self, address: Address, balance: U256) -> Self {
+		self.ext.set_balance(address, balance);
+		self
+	}
+
+	/// Sets the block number.
+	pub fn block_number(mut self, number: u64) -> Self {
+		self.ext.block.number = number;
+		self
+	}
+
+	/// Sets the block timestamp.
+	pub fn timestamp(mut self, timestamp: u64) -> Self {
+		self.ext.block.timestamp = timestamp;
+		self
+	}
+
+	/// Sets the block beneficiary.
+	pub fn coinbase(mut self, coinbase: Address) -> Self {
+		self.ext.block.coinbase = coinbase;
+		self
+	}
+
+	/// Sets the block difficulty.
+	pub fn difficulty(mut self, difficulty: U256) -> Self {
+		self.ext.block.difficulty = difficulty;
+		self
+	}
+
+	/// Sets the block gas limit.
+	pub fn gas_limit(mut self, gas_limit: U256) -> Self {
+		self.ext.block.gas_limit = gas_limit;
+		self
+	}
+
+	/// Sets the base fee per gas of the block.
+	pub fn base_fee(mut self, base_fee: U256) -> Self {
+		self.ext.block.base_fee = base_fee;
+		self
+	}
+
+	/// Sets the chain ID.
+	pub fn chain_id(mut self, chain_id: u64) -> Self {
+		self.ext.chain_id = chain_id;
+		self
+	}
+
+	/// Sets the hash of a previous block.
+	pub fn block_hash(mut self, number: u64, hash: H256) -> Self {
+		self.ext.block.hashes.insert(number, hash);
+		self
+	}
+
+	/// Installs the software precompiles, requires the `mock-precompiles` feature.
+	#[cfg(feature = "mock-precompiles")]
+	pub fn precompiles(mut self) -> Self {
+		self.ext.register_precompiles();
+		self
+	}
+
+	/// Mocks a call the contract is expected to make.
+	///
+	/// Calls to an address with expectations trap unless they match one of them, tried in the
+	/// order they were added. Expectations already made [`times`] times are skipped while
+	/// another one matches, so the same call can be mocked with successive results; extra calls
+	/// count towards the first matching expectation and fail [`verify`].
+	///
+	/// [`times`]: struct.ExpectedCall.html#method.times
+	/// [`verify`]: struct.Externals.html#method.verify
+	pub fn expect_call(mut self, call: ExpectedCall) -> Self {
+		self.expectations.push(Rc::new(Expectation { call: call, count: Cell::new(0) }));
+		self
+	}
+
+	/// Installs the externalities in the native backend, replacing its state.
+	pub fn build(self) -> Externals {
+		let ExternalsBuilder { mut ext, storage, expectations } = self;
+		let contract = ext.frame().address;
+		for (key, value) in storage {
+			ext.set_storage(contract, key, value);
+		}
+		let mut addresses: Vec<Address> = expectations.iter().map(|e| e.call.address).collect();
+		addresses.sort();
+		addresses.dedup();
+		for address in addresses {
+			let mocks: Vec<_> = expectations.iter().filter(|e| e.call.address == address).cloned().collect();
+			ext.register(address, move || {
+				let (input, value) = (::input(), ::value());
+				let expectation = mocks.iter()
+					.find(|e| e.count.get() < e.call.times && e.call.matches(&input, value))
+					.or_else(|| mocks.iter().find(|e| e.call.matches(&input, value)))
+					.unwrap_or_else(|| panic!("unexpected call to {:?}", address));
+				expectation.count.set(expectation.count.get() + 1);
+				match expectation.call.response {
+					Response::Return(ref result) => ::ret(result),
+					Response::Revert(ref data) => ::revert(data),
+					Response::Fail => panic!("mocked call to {:?} fails", address),
+				}
+			});
+		}
+		native::with_externalities(|current| *current = ext);
+		Externals { expectations: expectations }
+	}
+}
+
+/// Handle to built externalities, checking the expected calls.
+pub struct Externals {
+	expectations: Vec<Rc<Expectation>>,
+}
+
+impl Externals {
+	/// Number of calls made to `address` that matched an expectation.
+	pub fn calls_to(&self, address: &Address) -> usize {
+		self.expectations.iter().filter(|e| e.call.address == *address).map(|e| e.count.get()).sum()
+	}
+
+	/// Panics unless every expected call was made the expected number of times.
+	pub fn verify(&self) {
+		for expectation in &self.expectations {
+			let call = &expectation.call;
+			if expectation.count.get() != call.times {
+				panic!(
+					"expected {} call(s) to {:?}{}, got {}",
+					call.times,
+					call.address,
+					match (call.input.is_some(), call.value.is_some()) {
+						(true, true) => " with the given input and value",
+						(true, false) => " with the given input",
+						(false, true) => " with the given value",
+						(false, false) => "",
+					},
+					expectation.count.get(),
+				);
+			}
+		}
+	}
+}
+
+/// Payload of a reverted execution, as returned by [`native::execute`] or [`native::transact`].
+///
+/// # Panics
+///
+/// Unless `outcome` is a revert.
+///
+/// [`native::execute`]: ../native/fn.execute.html
+/// [`native::transact`]: ../native/fn.transact.html
+pub fn expect_revert(outcome: Outcome) -> Vec<u8> {
+	match outcome {
+		Outcome::Revert(data) => data,
+		outcome => panic!("expected a revert, got {:?}", outcome),
+	}
+}
+
+/// Checks that an execution reverted with the Solidity `Error(string)` `reason`.
+///
+/// # Panics
+///
+/// Unless `outcome` is a revert with that reason.
+pub fn expect_revert_reason(outcome: Outcome, reason: &str) {
+	let error = CallError::Reverted(expect_revert(outcome));
+	assert_eq!(error.reason(), Some(reason), "unexpected revert reason");
+}
+
+/// Checks that an execution reverted with the custom error `selector`, returning the encoded
+/// error arguments that follow it.
+///
+/// # Panics
+///
+/// Unless `outcome` is a revert with a payload starting with `selector`.
+pub fn expect_revert_error(outcome: Outcome, selector: [u8; 4]) -> Vec<u8> {
+	let data = expect_revert(outcome);
+	if data.get(..4) != Some(&selector[..]) {
+		panic!("expected a revert with selector {:?}, got {:?}", selector, data);
+	}
+	data[4..].to_vec()
+}
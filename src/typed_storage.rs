@@ -0,0 +1,309 @@
+//! Typed storage on top of raw 32-byte slots, with the Solidity layout.
+//!
+//! Slots are numbered by `U256`. A [`StorageValue`] lives at its slot, the entry of key `k` of
+//! a [`StorageMap`] at slot `p` lives at `keccak256(k || p)`, and a [`StorageVec`] at slot `p`
+//! keeps its length at `p` and its elements from `keccak256(p)` on. Words are big-endian and
+//! right-aligned; values taking several words, like tuples, use consecutive slots, without the
+//! packing Solidity applies to types narrower than a word.
+//!
+//! [`StorageValue`]: struct.StorageValue.html
+//! [`StorageMap`]: struct.StorageMap.html
+//! [`StorageVec`]: struct.StorageVec.html
+
+#[cfg(not(feature = "std"))]
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::marker::PhantomData;
+
+use pwasm_std::types::{H256, U256, Address};
+
+use ext;
+use hash::keccak256;
+
+fn slot_key(slot: U256) -> H256 {
+	let mut key = [0u8; 32];
+	slot.to_big_endian(&mut key);
+	H256::from(key)
+}
+
+/// Type stored in a single word.
+pub trait Word: Sized {
+	/// Encodes the value as a word.
+	fn to_word(&self) -> [u8; 32];
+	/// Decodes the value from a word.
+	fn from_word(word: &[u8; 32]) -> Self;
+}
+
+impl Word for [u8; 32] {
+	fn to_word(&self) -> [u8; 32] {
+		*self
+	}
+
+	fn from_word(word: &[u8; 32]) -> Self {
+		*word
+	}
+}
+
+impl Word for H256 {
+	fn to_word(&self) -> [u8; 32] {
+		(*self).into()
+	}
+
+	fn from_word(word: &[u8; 32]) -> Self {
+		H256::from(*word)
+	}
+}
+
+impl Word for U256 {
+	fn to_word(&self) -> [u8; 32] {
+		let mut word = [0u8; 32];
+		self.to_big_endian(&mut word);
+		word
+	}
+
+	fn from_word(word: &[u8; 32]) -> Self {
+		U256::from_big_endian(word)
+	}
+}
+
+impl Word for Address {
+	fn to_word(&self) -> [u8; 32] {
+		let mut word = [0u8; 32];
+		word[12..].copy_from_slice(&<[u8; 20]>::from(*self));
+		word
+	}
+
+	fn from_word(word: &[u8; 32]) -> Self {
+		let mut address = [0u8; 20];
+		address.copy_from_slice(&word[12..]);
+		Address::from(address)
+	}
+}
+
+impl Word for bool {
+	fn to_word(&self) -> [u8; 32] {
+		let mut word = [0u8; 32];
+		word[31] = *self as u8;
+		word
+	}
+
+	fn from_word(word: &[u8; 32]) -> Self {
+		word[31] != 0
+	}
+}
+
+macro_rules! impl_word_for_uint {
+	($($ty:ty),*) => {
+		$(
+			impl Word for $ty {
+				fn to_word(&self) -> [u8; 32] {
+					U256::from(*self as u64).to_word()
+				}
+
+				fn from_word(word: &[u8; 32]) -> Self {
+					U256::from_big_endian(word).low_u64() as $ty
+				}
+			}
+		)*
+	};
+}
+
+impl_word_for_uint!(u8, u16, u32, u64);
+
+/// Type stored in one or more consecutive slots.
+pub trait Storable: Sized {
+	/// Number of slots taken.
+	fn slots() -> u64;
+	/// Reads the value starting at `slot`.
+	fn read_from(slot: U256) -> Self;
+	/// Writes the value starting at `slot`.
+	fn write_to(&self, slot: U256);
+	/// Zeroes the slots of a value starting at `slot`.
+	fn clear(slot: U256) {
+		for i in 0..Self::slots() {
+			::write(&slot_key(slot + U256::from(i)), &[0u8; 32]);
+		}
+	}
+}
+
+impl<T: Word> Storable for T {
+	fn slots() -> u64 {
+		1
+	}
+
+	fn read_from(slot: U256) -> Self {
+		T::from_word(&::read(&slot_key(slot)))
+	}
+
+	fn write_to(&self, slot: U256) {
+		::write(&slot_key(slot), &self.to_word());
+	}
+}
+
+impl<A: Storable, B: Storable> Storable for (A, B) {
+	fn slots() -> u64 {
+		A::slots() + B::slots()
+	}
+
+	fn read_from(slot: U256) -> Self {
+		(A::read_from(slot), B::read_from(slot + U256::from(A::slots())))
+	}
+
+	fn write_to(&self, slot: U256) {
+		self.0.write_to(slot);
+		self.1.write_to(slot + U256::from(A::slots()));
+	}
+}
+
+impl<A: Storable, B: Storable, C: Storable> Storable for (A, B, C) {
+	fn slots() -> u64 {
+		A::slots() + B::slots() + C::slots()
+	}
+
+	fn read_from(slot: U256) -> Self {
+		let b = slot + U256::from(A::slots());
+		let c = b + U256::from(B::slots());
+		(A::read_from(slot), B::read_from(b), C::read_from(c))
+	}
+
+	fn write_to(&self, slot: U256) {
+		let b = slot + U256::from(A::slots());
+		let c = b + U256::from(B::slots());
+		self.0.write_to(slot);
+		self.1.write_to(b);
+		self.2.write_to(c);
+	}
+}
+
+/// Value at a fixed slot.
+#[derive(Debug)]
+pub struct StorageValue<T> {
+	slot: U256,
+	_marker: PhantomData<T>,
+}
+
+impl<T: Storable> StorageValue<T> {
+	/// Value at `slot`.
+	pub fn new(slot: U256) -> Self {
+		StorageValue { slot: slot, _marker: PhantomData }
+	}
+
+	/// Reads the value; unset slots read as zero.
+	pub fn get(&self) -> T {
+		T::read_from(self.slot)
+	}
+
+	/// Writes the value.
+	pub fn set(&self, value: &T) {
+		value.write_to(self.slot)
+	}
+
+	/// Zeroes the value.
+	pub fn clear(&self) {
+		T::clear(self.slot)
+	}
+}
+
+/// Mapping from keys to values, rooted at a slot.
+#[derive(Debug)]
+pub struct StorageMap<K, V> {
+	slot: U256,
+	_marker: PhantomData<(K, V)>,
+}
+
+impl<K: Word, V: Storable> StorageMap<K, V> {
+	/// Mapping rooted at `slot`.
+	pub fn new(slot: U256) -> Self {
+		StorageMap { slot: slot, _marker: PhantomData }
+	}
+
+	/// First slot of the value of `key`, `keccak256(key || slot)`.
+	pub fn slot_of(&self, key: &K) -> U256 {
+		let mut preimage = [0u8; 64];
+		preimage[..32].copy_from_slice(&key.to_word());
+		self.slot.to_big_endian(&mut preimage[32..]);
+		U256::from_big_endian(&<[u8; 32]>::from(keccak256(&preimage)))
+	}
+
+	/// Reads the value of `key`; missing keys read as zero.
+	pub fn get(&self, key: &K) -> V {
+		V::read_from(self.slot_of(key))
+	}
+
+	/// Writes the value of `key`.
+	pub fn set(&self, key: &K, value: &V) {
+		value.write_to(self.slot_of(key))
+	}
+
+	/// Zeroes the value of `key`.
+	pub fn remove(&self, key: &K) {
+		V::clear(self.slot_of(key))
+	}
+}
+
+/// Dynamic array, rooted at a slot.
+#[derive(Debug)]
+pub struct StorageVec<T> {
+	slot: U256,
+	_marker: PhantomData<T>,
+}
+
+impl<T: Storable> StorageVec<T> {
+	/// Array rooted at `slot`.
+	pub fn new(slot: U256) -> Self {
+		StorageVec { slot: slot, _marker: PhantomData }
+	}
+
+	/// Number of elements.
+	pub fn len(&self) -> u64 {
+		U256::read_from(self.slot).low_u64()
+	}
+
+	/// Whether the array is empty.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	fn element_slot(&self, index: u64) -> U256 {
+		let mut root = [0u8; 32];
+		self.slot.to_big_endian(&mut root);
+		U256::from_big_endian(&<[u8; 32]>::from(keccak256(&root))) + U256::from(index) * U256::from(T::slots())
+	}
+
+	/// Reads element `index`, if it exists.
+	pub fn get(&self, index: u64) -> Option<T> {
+		if index < self.len() { Some(T::read_from(self.element_slot(index))) } else { None }
+	}
+
+	/// Writes element `index`.
+	///
+	/// # Panics
+	///
+	/// Traps if `index` is out of bounds.
+	pub fn set(&self, index: u64, value: &T) {
+		if index >= self.len() {
+			ext::trap();
+		}
+		value.write_to(self.element_slot(index))
+	}
+
+	/// Appends an element.
+	pub fn push(&self, value: &T) {
+		let len = self.len();
+		value.write_to(self.element_slot(len));
+		U256::from(len + 1).write_to(self.slot);
+	}
+
+	/// Removes the last element, zeroing its slots.
+	pub fn pop(&self) -> Option<T> {
+		let len = self.len();
+		if len == 0 {
+			return None;
+		}
+		let slot = self.element_slot(len - 1);
+		let value = T::read_from(slot);
+		T::clear(slot);
+		U256::from(len - 1).write_to(self.slot);
+		Some(value)
+	}
+}
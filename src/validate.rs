@@ -0,0 +1,408 @@
+//! Validation of compiled contracts against the externs provided by the runtime.
+//!
+//! Mistyped or feature-gated imports are only reported by the runtime when the contract is
+//! deployed. [`validate_imports`] catches them locally by checking the import section of the
+//! compiled module against the set of externs this crate targets with the enabled backend
+//! and features.
+//!
+//! [`validate_imports`]: fn.validate_imports.html
+
+use std::fmt;
+
+#[cfg(not(feature = "backend-ewasm"))]
+/// Module name all runtime imports are expected to come from.
+pub const ENV_MODULE: &str = "env";
+
+#[cfg(feature = "backend-ewasm")]
+/// Module name all runtime imports are expected to come from.
+pub const ENV_MODULE: &str = "ethereum";
+
+/// Module name of the imports of pwasm-std.
+pub const STD_MODULE: &str = "env";
+
+/// Function imports of pwasm-std.
+pub const STD_FUNCTIONS: &[&str] = &["panic", "debug"];
+
+#[cfg(not(feature = "backend-ewasm"))]
+/// Function imports provided by the runtime regardless of enabled features.
+pub const FUNCTIONS: &[&str] = &[
+	"ccall",
+	"dcall",
+	"scall",
+	"blockhash",
+	"balance",
+	"coinbase",
+	"timestamp",
+	"blocknumber",
+	"difficulty",
+	"gaslimit",
+	"sender",
+	"address",
+	"value",
+	"origin",
+	"elog",
+	"create",
+	"suicide",
+	"ret",
+	"input_length",
+	"fetch_input",
+	"storage_read",
+	"storage_write",
+	// injected by gas metering
+	"gas",
+];
+
+#[cfg(feature = "backend-ewasm")]
+/// Function imports provided by the runtime regardless of enabled features.
+pub const FUNCTIONS: &[&str] = &[
+	"call",
+	"callDelegate",
+	"callStatic",
+	"create",
+	"getBlockHash",
+	"getExternalBalance",
+	"getExternalCodeSize",
+	"externalCodeCopy",
+	"getBlockCoinbase",
+	"getBlockTimestamp",
+	"getBlockNumber",
+	"getBlockDifficulty",
+	"getBlockGasLimit",
+	"getCaller",
+	"getAddress",
+	"getCallValue",
+	"getTxOrigin",
+	"log",
+	"selfDestruct",
+	"finish",
+	"revert",
+	"getCallDataSize",
+	"callDataCopy",
+	"getReturnDataSize",
+	"returnDataCopy",
+	"storageLoad",
+	"storageStore",
+	// injected by gas metering
+	"useGas",
+];
+
+/// Kind of an imported entity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportKind {
+	/// Function.
+	Function,
+	/// Table.
+	Table,
+	/// Linear memory.
+	Memory,
+	/// Global variable.
+	Global,
+}
+
+/// Entry of the import section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Import {
+	/// Module name.
+	pub module: String,
+	/// Field name.
+	pub field: String,
+	/// Kind of the imported entity.
+	pub kind: ImportKind,
+}
+
+impl fmt::Display for Import {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}.{} ({:?})", self.module, self.field, self.kind)
+	}
+}
+
+/// Import not provided by the runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownImport {
+	/// The offending import.
+	pub import: Import,
+	/// Closest provided function name, if the import looks like a typo of one.
+	pub suggestion: Option<&'static str>,
+}
+
+impl fmt::Display for UnknownImport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "unknown import {}", self.import)?;
+		if let Some(suggestion) = self.suggestion {
+			write!(f, ", did you mean `{}`?", suggestion)?;
+		}
+		Ok(())
+	}
+}
+
+/// Validation failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+	/// Module could not be parsed.
+	Malformed(&'static str),
+	/// Module imports entities the runtime does not provide.
+	UnknownImports(Vec<UnknownImport>),
+}
+
+impl fmt::Display for ValidationError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ValidationError::Malformed(reason) => write!(f, "malformed wasm module: {}", reason),
+			ValidationError::UnknownImports(ref imports) => {
+				for (i, import) in imports.iter().enumerate() {
+					if i > 0 {
+						f.write_str("\n")?;
+					}
+					write!(f, "{}", import)?;
+				}
+				Ok(())
+			},
+		}
+	}
+}
+
+impl ::std::error::Error for ValidationError {
+	fn description(&self) -> &str {
+		"wasm module validation failed"
+	}
+}
+
+#[cfg(not(feature = "backend-ewasm"))]
+/// Function imports provided by the runtime with the enabled features.
+pub fn provided_functions() -> Vec<&'static str> {
+	let mut functions = FUNCTIONS.to_vec();
+	if cfg!(feature = "kip4") {
+		functions.push("create2");
+	}
+	if cfg!(feature = "create-gas") {
+		functions.push("gcreate");
+		if cfg!(feature = "kip4") {
+			functions.push("gcreate2");
+		}
+	}
+	if cfg!(feature = "kip6") {
+		functions.push("gasleft");
+	}
+	if cfg!(feature = "eip1344") {
+		functions.push("chainid");
+	}
+	if cfg!(feature = "eip3198") {
+		functions.push("basefee");
+	}
+	if cfg!(feature = "gasprice") {
+		functions.push("gasprice");
+	}
+	if cfg!(feature = "eip1884") {
+		functions.push("selfbalance");
+	}
+	if cfg!(feature = "storage-batch") {
+		functions.push("storage_read_multi");
+		functions.push("storage_write_multi");
+	}
+	if cfg!(feature = "eip1153") {
+		functions.push("transient_storage_read");
+		functions.push("transient_storage_write");
+	}
+	if cfg!(feature = "eip140") {
+		functions.push("revert");
+	}
+	if cfg!(feature = "eip211") {
+		functions.push("return_data_length");
+		functions.push("fetch_return_data");
+	}
+	if cfg!(feature = "extcode") {
+		functions.push("extcodesize");
+		functions.push("extcodecopy");
+		functions.push("extcodehash");
+	}
+	if cfg!(feature = "input-range") {
+		functions.push("fetch_input_range");
+	}
+	if cfg!(all(feature = "keccak", not(feature = "tiny-keccak"))) {
+		functions.push("keccak");
+	}
+	functions
+}
+
+#[cfg(feature = "backend-ewasm")]
+/// Function imports provided by the runtime with the enabled features.
+pub fn provided_functions() -> Vec<&'static str> {
+	let mut functions = FUNCTIONS.to_vec();
+	if cfg!(feature = "kip6") {
+		functions.push("getGasLeft");
+	}
+	if cfg!(feature = "gasprice") {
+		functions.push("getTxGasPrice");
+	}
+	functions
+}
+
+/// Checks that every import of the compiled `wasm` module is provided by the runtime.
+///
+/// Functions must be one of [`provided_functions`] from the [`ENV_MODULE`] or one of
+/// [`STD_FUNCTIONS`] from the [`STD_MODULE`], and the only other allowed import is the
+/// linear memory from the [`ENV_MODULE`].
+///
+/// [`provided_functions`]: fn.provided_functions.html
+/// [`ENV_MODULE`]: constant.ENV_MODULE.html
+/// [`STD_FUNCTIONS`]: constant.STD_FUNCTIONS.html
+/// [`STD_MODULE`]: constant.STD_MODULE.html
+pub fn validate_imports(wasm: &[u8]) -> Result<(), ValidationError> {
+	let provided = provided_functions();
+	let unknown: Vec<UnknownImport> = imports(wasm)?.into_iter()
+		.filter(|import| match import.kind {
+			ImportKind::Function => {
+				let field = import.field.as_str();
+				!((import.module == ENV_MODULE && provided.contains(&field))
+					|| (import.module == STD_MODULE && STD_FUNCTIONS.contains(&field)))
+			},
+			ImportKind::Memory => import.module != ENV_MODULE,
+			ImportKind::Table | ImportKind::Global => true,
+		})
+		.map(|import| UnknownImport {
+			suggestion: match import.kind {
+				ImportKind::Function => suggest(&import.field, &provided),
+				_ => None,
+			},
+			import: import,
+		})
+		.collect();
+
+	if unknown.is_empty() {
+		Ok(())
+	} else {
+		Err(ValidationError::UnknownImports(unknown))
+	}
+}
+
+/// Parses the import section of the compiled `wasm` module.
+pub fn imports(wasm: &[u8]) -> Result<Vec<Import>, ValidationError> {
+	let mut reader = Reader { data: wasm, pos: 0 };
+	if reader.bytes(4)? != b"\0asm" {
+		return Err(ValidationError::Malformed("invalid magic"));
+	}
+	if reader.bytes(4)? != [1, 0, 0, 0] {
+		return Err(ValidationError::Malformed("unsupported version"));
+	}
+
+	while !reader.is_empty() {
+		let id = reader.byte()?;
+		let size = reader.varuint32()? as usize;
+		let payload = reader.bytes(size)?;
+		if id == 2 {
+			return parse_imports(Reader { data: payload, pos: 0 });
+		}
+	}
+	Ok(Vec::new())
+}
+
+fn parse_imports(mut reader: Reader) -> Result<Vec<Import>, ValidationError> {
+	let count = reader.varuint32()?;
+	let mut imports = Vec::new();
+	for _ in 0..count {
+		let module = reader.name()?;
+		let field = reader.name()?;
+		let kind = match reader.byte()? {
+			0 => {
+				reader.varuint32()?;
+				ImportKind::Function
+			},
+			1 => {
+				reader.byte()?;
+				reader.limits()?;
+				ImportKind::Table
+			},
+			2 => {
+				reader.limits()?;
+				ImportKind::Memory
+			},
+			3 => {
+				reader.bytes(2)?;
+				ImportKind::Global
+			},
+			_ => return Err(ValidationError::Malformed("invalid import kind")),
+		};
+		imports.push(Import { module: module, field: field, kind: kind });
+	}
+	Ok(imports)
+}
+
+struct Reader<'a> {
+	data: &'a [u8],
+	pos: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn is_empty(&self) -> bool {
+		self.pos >= self.data.len()
+	}
+
+	fn byte(&mut self) -> Result<u8, ValidationError> {
+		Ok(self.bytes(1)?[0])
+	}
+
+	fn bytes(&mut self, len: usize) -> Result<&'a [u8], ValidationError> {
+		if self.data.len() - self.pos < len {
+			return Err(ValidationError::Malformed("unexpected end of module"));
+		}
+		let bytes = &self.data[self.pos..self.pos + len];
+		self.pos += len;
+		Ok(bytes)
+	}
+
+	fn varuint32(&mut self) -> Result<u32, ValidationError> {
+		let mut result = 0u32;
+		for i in 0..5 {
+			let byte = self.byte()?;
+			result |= ((byte & 0x7f) as u32) << (7 * i);
+			if byte & 0x80 == 0 {
+				return Ok(result);
+			}
+		}
+		Err(ValidationError::Malformed("invalid varuint32"))
+	}
+
+	fn name(&mut self) -> Result<String, ValidationError> {
+		let len = self.varuint32()? as usize;
+		String::from_utf8(self.bytes(len)?.to_vec()).map_err(|_| ValidationError::Malformed("invalid utf-8 name"))
+	}
+
+	fn limits(&mut self) -> Result<(), ValidationError> {
+		let flags = self.byte()?;
+		self.varuint32()?;
+		if flags & 1 != 0 {
+			self.varuint32()?;
+		}
+		Ok(())
+	}
+}
+
+/// Closest name within a small edit distance, case-insensitively.
+fn suggest(name: &str, provided: &[&'static str]) -> Option<&'static str> {
+	let name = name.to_lowercase();
+	provided.iter()
+		.map(|candidate| (distance(&name, &candidate.to_lowercase()), *candidate))
+		.filter(|&(distance, _)| distance <= 2)
+		.min_by_key(|&(distance, _)| distance)
+		.map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance.
+fn distance(a: &str, b: &str) -> usize {
+	let b: Vec<char> = b.chars().collect();
+	let mut row: Vec<usize> = (0..b.len() + 1).collect();
+	for (i, ca) in a.chars().enumerate() {
+		let mut previous = row[0];
+		row[0] = i + 1;
+		for j in 0..b.len() {
+			let current = row[j + 1];
+			row[j + 1] = if ca == b[j] {
+				previous
+			} else {
+				1 + ::std::cmp::min(previous, ::std::cmp::min(row[j], current))
+			};
+			previous = current;
+		}
+	}
+	row[b.len()]
+}
@@ -0,0 +1,147 @@
+//! Deterministic test accounts able to sign arbitrary hashes.
+//!
+//! Keys are derived from a phrase and an account index, so tests get the same addresses and
+//! signatures on every run. The derivation is `keccak256(phrase || index)`, rehashed until it
+//! is a valid secp256k1 secret; it is meant for tests only and is not BIP-39/BIP-32 compatible.
+//!
+//! EIP-712 typed data is signed with [`TestAccount::sign_typed`] over a [`Domain`] separator
+//! and the struct hash computed by the test.
+//!
+//! [`TestAccount::sign_typed`]: struct.TestAccount.html#method.sign_typed
+//! [`Domain`]: struct.Domain.html
+
+use pwasm_std::types::{H256, Address};
+use secp256k1::{self, Message, PublicKey, RecoveryId, SecretKey};
+use tiny_keccak::keccak256;
+
+use eip712;
+
+pub use eip712::{type_hash, typed_data_hash, DOMAIN_TYPE};
+pub use signed::Signature;
+
+/// Phrase used by [`TestAccount::from_index`](struct.TestAccount.html#method.from_index).
+pub const DEFAULT_PHRASE: &str = "pwasm-ethereum test accounts";
+
+/// Key pair of a test account.
+#[derive(Clone)]
+pub struct TestAccount {
+	secret: SecretKey,
+	public: PublicKey,
+	address: Address,
+}
+
+impl TestAccount {
+	/// Account number `index` derived from [`DEFAULT_PHRASE`](constant.DEFAULT_PHRASE.html).
+	pub fn from_index(index: u32) -> Self {
+		TestAccount::from_phrase(DEFAULT_PHRASE, index)
+	}
+
+	/// Account number `index` derived from `phrase`.
+	pub fn from_phrase(phrase: &str, index: u32) -> Self {
+		let mut seed = phrase.as_bytes().to_vec();
+		seed.extend_from_slice(&[(index >> 24) as u8, (index >> 16) as u8, (index >> 8) as u8, index as u8]);
+		let mut secret = keccak256(&seed);
+		loop {
+			if let Some(account) = TestAccount::from_secret(secret) {
+				return account;
+			}
+			secret = keccak256(&secret);
+		}
+	}
+
+	/// Account with the given secret key, if it is valid.
+	pub fn from_secret(secret: [u8; 32]) -> Option<Self> {
+		let secret = SecretKey::parse(&secret).ok()?;
+		let public = PublicKey::from_secret_key(&secret);
+		Some(TestAccount {
+			address: public_to_address(&public),
+			secret: secret,
+			public: public,
+		})
+	}
+
+	/// Address of the account.
+	pub fn address(&self) -> Address {
+		self.address
+	}
+
+	/// Secret key of the account.
+	pub fn secret(&self) -> [u8; 32] {
+		self.secret.serialize()
+	}
+
+	/// Uncompressed public key of the account, without the `0x04` prefix.
+	pub fn public(&self) -> [u8; 64] {
+		let serialized = self.public.serialize();
+		let mut public = [0u8; 64];
+		public.copy_from_slice(&serialized[1..]);
+		public
+	}
+
+	/// Signs `hash` with the account key.
+	pub fn sign(&self, hash: &H256) -> Signature {
+		let message = Message::parse(&(*hash).into());
+		let (signature, recovery_id) = secp256k1::sign(&message, &self.secret)
+			.expect("signing with a valid key never fails; qed");
+		let serialized = signature.serialize();
+		let mut r = [0u8; 32];
+		let mut s = [0u8; 32];
+		r.copy_from_slice(&serialized[..32]);
+		s.copy_from_slice(&serialized[32..]);
+		Signature { v: 27 + recovery_id.serialize(), r: r.into(), s: s.into() }
+	}
+
+	/// Signs EIP-712 typed data given its domain separator and struct hash.
+	pub fn sign_typed(&self, domain_separator: &H256, struct_hash: &H256) -> Signature {
+		self.sign(&typed_data_hash(domain_separator, struct_hash))
+	}
+
+	/// Sets the balance of the account in the native externalities.
+	#[cfg(feature = "native")]
+	pub fn fund(&self, balance: ::pwasm_std::types::U256) {
+		::native::with_externalities(|ext| ext.set_balance(self.address, balance));
+	}
+}
+
+/// EIP-712 domain with all of the standard fields present.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Domain {
+	/// Name of the signing domain.
+	pub name: String,
+	/// Version of the signing domain.
+	pub version: String,
+	/// Chain id (EIP-155).
+	pub chain_id: u64,
+	/// Address of the verifying contract.
+	pub verifying_contract: Address,
+}
+
+impl Domain {
+	/// Domain separator, `hashStruct(domain)`.
+	pub fn separator(&self) -> H256 {
+		eip712::domain_separator(&self.name, &self.version, self.chain_id, &self.verifying_contract)
+	}
+}
+
+/// First `count` accounts derived from [`DEFAULT_PHRASE`](constant.DEFAULT_PHRASE.html).
+pub fn accounts(count: u32) -> Vec<TestAccount> {
+	(0..count).map(TestAccount::from_index).collect()
+}
+
+/// Recovers the address that signed `hash`, like the `ecrecover` precompile.
+pub fn recover(hash: &H256, signature: &Signature) -> Option<Address> {
+	let message = Message::parse(&(*hash).into());
+	let mut serialized = [0u8; 64];
+	serialized[..32].copy_from_slice(&<[u8; 32]>::from(signature.r));
+	serialized[32..].copy_from_slice(&<[u8; 32]>::from(signature.s));
+	let recovery_id = RecoveryId::parse(signature.v.wrapping_sub(27)).ok()?;
+	let public = secp256k1::recover(&message, &secp256k1::Signature::parse(&serialized), &recovery_id).ok()?;
+	Some(public_to_address(&public))
+}
+
+fn public_to_address(public: &PublicKey) -> Address {
+	let hash = keccak256(&public.serialize()[1..]);
+	let mut address = [0u8; 20];
+	address.copy_from_slice(&hash[12..]);
+	Address::from(address)
+}
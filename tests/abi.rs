@@ -0,0 +1,135 @@
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::abi::{self, ParamType, Token};
+use pwasm_ethereum::U256;
+
+fn hex(words: &[&str]) -> Vec<u8> {
+	let hex: String = words.concat();
+	(0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}
+
+fn uint(value: u64) -> Token {
+	Token::Uint(U256::from(value))
+}
+
+fn uint_word(value: u64) -> String {
+	format!("{:064x}", value)
+}
+
+fn string_word(value: &str) -> String {
+	let mut word: String = value.bytes().map(|b| format!("{:02x}", b)).collect();
+	while word.len() < 64 {
+		word.push('0');
+	}
+	word
+}
+
+fn round_trip(params: &[ParamType], tokens: &[Token], expected: &[u8]) {
+	let encoded = abi::encode(tokens);
+	assert_eq!(encoded, expected);
+	assert_eq!(abi::decode(params, &encoded).unwrap(), tokens);
+}
+
+#[test]
+fn static_and_dynamic_arguments() {
+	// f(uint,uint32[],bytes10,bytes) from the Solidity ABI specification
+	let params = [
+		ParamType::Uint,
+		ParamType::Array(Box::new(ParamType::Uint)),
+		ParamType::FixedBytes(10),
+		ParamType::Bytes,
+	];
+	let tokens = [
+		uint(0x123),
+		Token::Array(vec![uint(0x456), uint(0x789)]),
+		Token::FixedBytes(b"1234567890".to_vec()),
+		Token::Bytes(b"Hello, world!".to_vec()),
+	];
+	let expected = hex(&[
+		&uint_word(0x123), &uint_word(0x80), &string_word("1234567890"), &uint_word(0xe0),
+		&uint_word(2), &uint_word(0x456), &uint_word(0x789),
+		&uint_word(13), &string_word("Hello, world!"),
+	]);
+	round_trip(&params, &tokens, &expected);
+}
+
+#[test]
+fn nested_dynamic_arrays() {
+	// g(uint[][],string[]) from the Solidity ABI specification
+	let params = [
+		ParamType::Array(Box::new(ParamType::Array(Box::new(ParamType::Uint)))),
+		ParamType::Array(Box::new(ParamType::String)),
+	];
+	let tokens = [
+		Token::Array(vec![Token::Array(vec![uint(1), uint(2)]), Token::Array(vec![uint(3)])]),
+		Token::Array(vec![Token::String("one".into()), Token::String("two".into()), Token::String("three".into())]),
+	];
+	let expected = hex(&[
+		&uint_word(0x40), &uint_word(0x140),
+		&uint_word(2), &uint_word(0x40), &uint_word(0xa0),
+		&uint_word(2), &uint_word(1), &uint_word(2),
+		&uint_word(1), &uint_word(3),
+		&uint_word(3), &uint_word(0x60), &uint_word(0xa0), &uint_word(0xe0),
+		&uint_word(3), &string_word("one"),
+		&uint_word(3), &string_word("two"),
+		&uint_word(5), &string_word("three"),
+	]);
+	round_trip(&params, &tokens, &expected);
+}
+
+#[test]
+fn fixed_arrays() {
+	// h(uint[2],string[2]): static elements are in place, dynamic ones encoded as a tuple
+	let params = [
+		ParamType::FixedArray(Box::new(ParamType::Uint), 2),
+		ParamType::FixedArray(Box::new(ParamType::String), 2),
+	];
+	let tokens = [
+		Token::FixedArray(vec![uint(1), uint(2)]),
+		Token::FixedArray(vec![Token::String("a".into()), Token::String("b".into())]),
+	];
+	let expected = hex(&[
+		&uint_word(1), &uint_word(2), &uint_word(0x60),
+		&uint_word(0x40), &uint_word(0x80),
+		&uint_word(1), &string_word("a"),
+		&uint_word(1), &string_word("b"),
+	]);
+	round_trip(&params, &tokens, &expected);
+}
+
+#[test]
+fn long_fixed_bytes_are_truncated() {
+	let encoded = abi::encode(&[Token::FixedBytes(vec![1u8; 40])]);
+	assert_eq!(encoded, vec![1u8; 32]);
+}
+
+#[test]
+fn out_of_bounds_offsets_are_rejected() {
+	let bytes = [ParamType::Bytes];
+	// offset past the end of the data
+	assert!(abi::decode(&bytes, &hex(&[&uint_word(0x40)])).is_err());
+	// offset not fitting in a usize
+	assert!(abi::decode(&bytes, &hex(&[&format!("{:064x}", u128::max_value())])).is_err());
+	// length past the end of the data
+	assert!(abi::decode(&bytes, &hex(&[&uint_word(0x20), &uint_word(0x40), &uint_word(0)])).is_err());
+	// truncated head
+	assert!(abi::decode(&[ParamType::Uint, ParamType::Uint], &hex(&[&uint_word(1)])).is_err());
+}
+
+#[test]
+fn oversized_array_lengths_are_rejected() {
+	let array = [ParamType::Array(Box::new(ParamType::Uint))];
+	assert!(abi::decode(&array, &hex(&[&uint_word(0x20), &uint_word(1 << 40)])).is_err());
+	assert!(abi::decode(&array, &hex(&[&uint_word(0x20), &uint_word(2), &uint_word(1)])).is_err());
+	let fixed = [ParamType::FixedArray(Box::new(ParamType::Uint), 1 << 40)];
+	assert!(abi::decode(&fixed, &hex(&[&uint_word(1)])).is_err());
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+	assert!(abi::decode(&[ParamType::Bool], &hex(&[&uint_word(2)])).is_err());
+	assert!(abi::decode(&[ParamType::Address], &hex(&[&format!("01{}", "0".repeat(62))])).is_err());
+	assert!(abi::decode(&[ParamType::FixedBytes(33)], &hex(&[&uint_word(0)])).is_err());
+	let invalid_utf8 = hex(&[&uint_word(0x20), &uint_word(1), &format!("ff{}", "0".repeat(62))]);
+	assert!(abi::decode(&[ParamType::String], &invalid_utf8).is_err());
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "alloc-stats")]
+
+extern crate pwasm_ethereum;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+
+use pwasm_ethereum::alloc_stats::{self, Counting, Stats};
+
+// the wrapper is not installed as the global allocator, so only the allocations made through
+// it below are counted; the counters are process-wide, hence a single test
+#[test]
+fn counts_allocations_and_the_peak() {
+	let alloc = Counting(System);
+	let (small, large) = (Layout::from_size_align(64, 8).unwrap(), Layout::from_size_align(32, 8).unwrap());
+	alloc_stats::reset();
+	unsafe {
+		let a = alloc.alloc(small);
+		let b = alloc.alloc_zeroed(large);
+		assert_eq!(alloc_stats::stats(), Stats { allocations: 2, deallocations: 0, current: 96, peak: 96 });
+
+		alloc.dealloc(a, small);
+		assert_eq!(alloc_stats::stats(), Stats { allocations: 2, deallocations: 1, current: 32, peak: 96 });
+
+		let b = alloc.realloc(b, large, 128);
+		assert_eq!(alloc_stats::stats(), Stats { allocations: 3, deallocations: 1, current: 128, peak: 128 });
+
+		alloc.dealloc(b, Layout::from_size_align(128, 8).unwrap());
+	}
+	assert_eq!(alloc_stats::stats(), Stats { allocations: 3, deallocations: 2, current: 0, peak: 128 });
+
+	alloc_stats::reset();
+	assert_eq!(alloc_stats::stats(), Stats::default());
+
+	#[cfg(feature = "native")]
+	{
+		use pwasm_ethereum::{hash, native, H256, U256};
+
+		assert_eq!(H256::from(alloc_stats::HEAP_STATS_TOPIC), hash::keccak256(b"HeapStats(uint256,uint256,uint256)"));
+
+		let ptr = unsafe { alloc.alloc(small) };
+		native::reset();
+		let outcome = native::execute(alloc_stats::log_stats);
+		assert!(outcome.is_success(), "{:?}", outcome);
+		native::with_externalities(|ext| {
+			let log = &ext.logs[0];
+			assert_eq!(log.topics, vec![H256::from(alloc_stats::HEAP_STATS_TOPIC)]);
+			let words: Vec<U256> = log.data.chunks(32).map(U256::from_big_endian).collect();
+			assert_eq!(words, vec![U256::from(1), U256::from(64), U256::from(64)]);
+		});
+		unsafe { alloc.dealloc(ptr, small) };
+	}
+}
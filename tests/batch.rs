@@ -0,0 +1,105 @@
+#![cfg(all(feature = "native", feature = "kip6"))]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::batch;
+use pwasm_ethereum::native::{self, Outcome};
+use pwasm_ethereum::{hash, Address, H256, U256};
+
+const CONTRACT: [u8; 20] = [1u8; 20];
+
+fn slot(index: u8) -> H256 {
+	let mut key = [0u8; 32];
+	key[31] = index;
+	H256::from(key)
+}
+
+/// Contract answering batches; any other input `[n]` stores `n` at slot `n` and returns it,
+/// except `0` which traps.
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| ext.register(Address::from(CONTRACT), || {
+		let input = pwasm_ethereum::input();
+		batch::handle(&input, 1);
+		if input[0] == 0 {
+			panic!("operation failed");
+		}
+		let mut value = [0u8; 32];
+		value[31] = input[0];
+		pwasm_ethereum::write(&slot(input[0]), &value);
+		pwasm_ethereum::ret(&input[..1])
+	}));
+}
+
+fn batch_call(selector: [u8; 4], ops: &[&[u8]]) -> Vec<u8> {
+	let mut input = selector.to_vec();
+	input.extend_from_slice(&batch::encode(ops));
+	input
+}
+
+fn stored(index: u8) -> u8 {
+	native::with_externalities(|ext| ext.storage_at(&Address::from(CONTRACT), &slot(index))[31])
+}
+
+#[test]
+fn selectors_are_not_abi_signatures() {
+	let selector = |id: &str| {
+		let hash = <[u8; 32]>::from(hash::keccak256(id.as_bytes()));
+		[hash[0], hash[1], hash[2], hash[3]]
+	};
+	assert_eq!(batch::EXECUTE_BATCH, selector("pwasm.batch.execute"));
+	assert_eq!(batch::TRY_EXECUTE_BATCH, selector("pwasm.batch.tryExecute"));
+}
+
+#[test]
+fn frames_round_trip() {
+	let ops: &[&[u8]] = &[b"a", b"", b"bcd"];
+	let encoded = batch::encode(ops);
+	assert_eq!(encoded, vec![0, 0, 0, 1, b'a', 0, 0, 0, 0, 0, 0, 0, 3, b'b', b'c', b'd']);
+	assert_eq!(batch::decode(&encoded).unwrap(), ops.to_vec());
+	assert!(batch::decode(&encoded[..encoded.len() - 1]).is_err());
+	assert!(batch::decode(&[0, 0, 1]).is_err());
+}
+
+#[test]
+fn best_effort_keeps_going_after_a_failure() {
+	setup();
+	let input = batch_call(batch::TRY_EXECUTE_BATCH, &[&[1], &[0], &[3]]);
+	let outcome = native::transact(Address::from(CONTRACT), U256::zero(), input);
+	let output = match outcome {
+		Outcome::Return(output) => output,
+		outcome => panic!("unexpected {:?}", outcome),
+	};
+	assert_eq!(batch::decode(&output).unwrap(), vec![&[1u8, 1][..], &[0, 0][..], &[1, 3][..]]);
+	assert_eq!((stored(1), stored(3)), (1, 3));
+}
+
+#[test]
+fn all_or_nothing_reverts_every_operation() {
+	setup();
+	let input = batch_call(batch::EXECUTE_BATCH, &[&[1], &[0], &[3]]);
+	let outcome = native::transact(Address::from(CONTRACT), U256::zero(), input);
+	if cfg!(feature = "eip140") {
+		pwasm_ethereum::test::expect_revert_reason(outcome, "batch operation 1 failed");
+	} else {
+		assert!(!outcome.is_success());
+	}
+	assert_eq!((stored(1), stored(3)), (0, 0));
+
+	let input = batch_call(batch::EXECUTE_BATCH, &[&[1], &[3]]);
+	assert!(native::transact(Address::from(CONTRACT), U256::zero(), input).is_success());
+	assert_eq!((stored(1), stored(3)), (1, 3));
+}
+
+#[test]
+fn malformed_batches_and_value_are_rejected() {
+	setup();
+	let mut input = batch_call(batch::EXECUTE_BATCH, &[&[1]]);
+	input.pop();
+	assert!(!native::transact(Address::from(CONTRACT), U256::zero(), input).is_success());
+
+	native::with_externalities(|ext| ext.set_balance(Address::zero(), U256::from(10)));
+	let input = batch_call(batch::TRY_EXECUTE_BATCH, &[&[1]]);
+	assert!(!native::transact(Address::from(CONTRACT), U256::from(1), input).is_success());
+	assert_eq!(stored(1), 0);
+}
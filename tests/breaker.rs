@@ -0,0 +1,147 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::breaker::{self, CircuitBreaker};
+use pwasm_ethereum::native::{self, Outcome};
+use pwasm_ethereum::{Address, H256, U256};
+
+const BASE: [u8; 31] = [0xbb; 31];
+const ADMIN: [u8; 20] = [2u8; 20];
+const OTHER: [u8; 20] = [3u8; 20];
+const START: u64 = 1_000;
+const PERIOD: u64 = 3_600;
+
+fn breaker() -> CircuitBreaker {
+	CircuitBreaker::new(BASE)
+}
+
+fn setup(threshold: U256) {
+	native::reset();
+	native::with_externalities(|ext| ext.block.timestamp = START);
+	let outcome = native::execute(move || breaker().configure(&Address::from(ADMIN), threshold, PERIOD));
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+fn set_sender(sender: [u8; 20]) {
+	native::with_externalities(|ext| ext.frame_mut().sender = Address::from(sender));
+}
+
+fn record(amount: U256) -> bool {
+	let outcome = native::execute(move || {
+		let accepted = breaker().record_outflow(amount).is_ok();
+		pwasm_ethereum::ret(&[accepted as u8])
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+	outcome.data() == &[1u8][..]
+}
+
+fn check<F: FnOnce(&CircuitBreaker)>(f: F) {
+	let outcome = native::execute(move || f(&breaker()));
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+fn words(first: U256, second: U256) -> Vec<u8> {
+	let mut data = vec![0u8; 64];
+	first.to_big_endian(&mut data[..32]);
+	second.to_big_endian(&mut data[32..]);
+	data
+}
+
+#[test]
+fn trips_only_above_the_threshold() {
+	setup(U256::from(100));
+	assert!(record(U256::from(60)));
+	assert!(record(U256::from(40)));
+	check(|breaker| {
+		assert_eq!(breaker.outflow(), U256::from(100));
+		assert!(!breaker.is_tripped());
+		breaker.require_not_tripped();
+	});
+
+	assert!(!record(U256::from(1)));
+	check(|breaker| assert!(breaker.is_tripped()));
+	native::with_externalities(|ext| {
+		let log = ext.logs.last().unwrap();
+		assert_eq!(log.topics, vec![H256::from(breaker::TRIPPED_TOPIC)]);
+		assert_eq!(log.data, words(U256::from(101), U256::from(100)));
+	});
+
+	// once tripped, even empty outflows are refused
+	assert!(!record(U256::zero()));
+	assert_eq!(native::execute(|| breaker().require_not_tripped()), Outcome::Trap("trap".into()));
+}
+
+#[test]
+fn outflow_is_counted_per_period() {
+	setup(U256::from(100));
+	assert!(record(U256::from(100)));
+
+	native::with_externalities(|ext| ext.block.timestamp = START + PERIOD - 1);
+	check(|breaker| assert_eq!(breaker.outflow(), U256::from(100)));
+
+	native::with_externalities(|ext| ext.block.timestamp = START + PERIOD);
+	check(|breaker| assert_eq!(breaker.outflow(), U256::zero()));
+	assert!(record(U256::from(100)));
+	check(|breaker| {
+		assert_eq!(breaker.outflow(), U256::from(100));
+		assert!(!breaker.is_tripped());
+	});
+}
+
+#[test]
+fn only_the_admin_resets() {
+	setup(U256::from(100));
+	assert!(!record(U256::from(101)));
+
+	set_sender(OTHER);
+	check(|breaker| assert!(breaker.reset().is_err()));
+	check(|breaker| assert!(breaker.is_tripped()));
+
+	set_sender(ADMIN);
+	native::with_externalities(|ext| ext.block.timestamp = START + 10);
+	check(|breaker| assert!(breaker.reset().is_ok()));
+	check(|breaker| {
+		assert!(!breaker.is_tripped());
+		assert_eq!(breaker.outflow(), U256::zero());
+	});
+	native::with_externalities(|ext| {
+		let log = ext.logs.last().unwrap();
+		assert_eq!(log.topics, vec![H256::from(breaker::RESET_TOPIC)]);
+		let mut admin = vec![0u8; 32];
+		admin[12..].copy_from_slice(&ADMIN);
+		assert_eq!(log.data, admin);
+	});
+
+	// the reset starts a new period, which would otherwise have ended at START + PERIOD
+	assert!(record(U256::from(50)));
+	native::with_externalities(|ext| ext.block.timestamp = START + PERIOD + 9);
+	check(|breaker| assert_eq!(breaker.outflow(), U256::from(50)));
+	assert!(!record(U256::from(51)));
+}
+
+#[test]
+fn only_the_admin_sets_the_threshold() {
+	setup(U256::from(100));
+
+	set_sender(OTHER);
+	check(|breaker| assert!(breaker.set_threshold(U256::from(1_000)).is_err()));
+	check(|breaker| assert_eq!(breaker.threshold(), U256::from(100)));
+
+	set_sender(ADMIN);
+	check(|breaker| assert!(breaker.set_threshold(U256::from(1_000)).is_ok()));
+	check(|breaker| assert_eq!(breaker.threshold(), U256::from(1_000)));
+	assert!(record(U256::from(1_000)));
+}
+
+#[test]
+fn overflowing_outflow_trips_with_the_maximum() {
+	setup(U256::max_value());
+	assert!(record(U256::from(1)));
+	assert!(!record(U256::max_value()));
+	check(|breaker| assert!(breaker.is_tripped()));
+	native::with_externalities(|ext| {
+		let log = ext.logs.last().unwrap();
+		assert_eq!(log.data, words(U256::max_value(), U256::max_value()));
+	});
+}
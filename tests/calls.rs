@@ -0,0 +1,123 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, GasSchedule};
+use pwasm_ethereum::{Address, CallError, H256, U256};
+
+const CALLER: [u8; 20] = [1u8; 20];
+const ECHO: [u8; 20] = [2u8; 20];
+const FAILING: [u8; 20] = [3u8; 20];
+
+/// `ECHO` returns the address it runs as followed by its input, repeated 50 times on `[0xff]`.
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.frame_mut().address = Address::from(CALLER);
+		ext.register(Address::from(ECHO), || {
+			let input = pwasm_ethereum::input();
+			let mut output = <[u8; 20]>::from(pwasm_ethereum::address()).to_vec();
+			output.extend_from_slice(&input);
+			if input == [0xff] {
+				output = repeat(&output, 50);
+			}
+			pwasm_ethereum::ret(&output)
+		});
+	});
+}
+
+fn execute<F: FnOnce()>(f: F) {
+	let outcome = native::execute(f);
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+fn repeat(data: &[u8], times: usize) -> Vec<u8> {
+	(0..times).flat_map(|_| data.iter().cloned()).collect()
+}
+
+#[cfg(feature = "eip211")]
+fn echoed(address: [u8; 20], input: &[u8]) -> Vec<u8> {
+	[&address[..], input].concat()
+}
+
+#[cfg(feature = "eip211")]
+#[test]
+fn calls_return_the_whole_output() {
+	setup();
+	execute(|| {
+		let echo = Address::from(ECHO);
+		let long = repeat(&echoed(ECHO, &[0xff]), 50);
+		assert_eq!(pwasm_ethereum::call_with_output(100_000, &echo, U256::zero(), &[0xff]).unwrap(), long);
+		assert_eq!(pwasm_ethereum::static_call_with_output(100_000, &echo, b"static").unwrap(), echoed(ECHO, b"static"));
+		// delegate calls run as the caller
+		assert_eq!(pwasm_ethereum::call_code_with_output(100_000, &echo, b"code").unwrap(), echoed(CALLER, b"code"));
+	});
+}
+
+#[cfg(feature = "eip211")]
+#[test]
+fn return_data_outlives_short_result_buffers() {
+	setup();
+	execute(|| {
+		assert_eq!(pwasm_ethereum::return_data_size(), 0);
+		assert!(pwasm_ethereum::return_data().is_empty());
+
+		let mut first = [0u8; 1];
+		pwasm_ethereum::call(100_000, &Address::from(ECHO), U256::zero(), &[0xff], &mut first).unwrap();
+		assert_eq!(first, [ECHO[0]]);
+		assert_eq!(pwasm_ethereum::return_data_size(), 21 * 50);
+		assert_eq!(pwasm_ethereum::return_data(), repeat(&echoed(ECHO, &[0xff]), 50));
+
+		pwasm_ethereum::call(100_000, &Address::from(ECHO), U256::zero(), &[], &mut []).unwrap();
+		assert_eq!(pwasm_ethereum::return_data(), echoed(ECHO, &[]));
+	});
+}
+
+/// `FAILING` reverts with `nope` on `[1]`, writes storage on `[2]` and traps otherwise.
+fn setup_failing() {
+	setup();
+	native::with_externalities(|ext| {
+		ext.gas_schedule = Some(GasSchedule::frontier());
+		ext.register(Address::from(FAILING), || match pwasm_ethereum::input()[0] {
+			1 => pwasm_ethereum::revert(b"nope"),
+			2 => pwasm_ethereum::write(&H256::zero(), &[1u8; 32]),
+			_ => panic!("failing callee"),
+		});
+	});
+}
+
+fn call_failing(input: u8) -> Result<(), CallError> {
+	pwasm_ethereum::call(1_000, &Address::from(FAILING), U256::zero(), &[input], &mut [])
+}
+
+#[test]
+fn call_errors_tell_why_the_callee_failed() {
+	setup_failing();
+	execute(|| {
+		let (reverted, out_of_gas) = if cfg!(feature = "eip211") {
+			let reverted = if cfg!(feature = "eip140") { CallError::Reverted(b"nope".to_vec()) } else { CallError::Trapped };
+			(reverted, CallError::OutOfGas)
+		} else {
+			// without EIP-211 the runtime only tells that the call failed
+			(CallError::Trapped, CallError::Trapped)
+		};
+		assert_eq!(call_failing(1), Err(reverted));
+		assert_eq!(call_failing(2), Err(out_of_gas));
+		assert_eq!(call_failing(0), Err(CallError::Trapped));
+	});
+}
+
+#[cfg(feature = "eip211")]
+#[test]
+fn reverted_output_is_returned_as_the_error() {
+	setup_failing();
+	execute(|| {
+		let result = pwasm_ethereum::call_with_output(1_000, &Address::from(FAILING), U256::zero(), &[1]);
+		if cfg!(feature = "eip140") {
+			assert_eq!(result, Err(CallError::Reverted(b"nope".to_vec())));
+			assert_eq!(pwasm_ethereum::return_data(), b"nope".to_vec());
+		} else {
+			assert_eq!(result, Err(CallError::Trapped));
+		}
+	});
+}
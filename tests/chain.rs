@@ -0,0 +1,45 @@
+#![cfg(all(feature = "native", any(feature = "eip1344", feature = "eip3198")))]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native;
+use pwasm_ethereum::test::ExternalsBuilder;
+#[cfg(feature = "eip3198")]
+use pwasm_ethereum::U256;
+
+fn execute<F: FnOnce()>(f: F) {
+	let outcome = native::execute(f);
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[cfg(feature = "eip1344")]
+#[test]
+fn chain_id_defaults_to_mainnet() {
+	native::reset();
+	execute(|| assert_eq!(pwasm_ethereum::chain_id(), native::DEFAULT_CHAIN_ID));
+	assert_eq!(native::DEFAULT_CHAIN_ID, 1);
+}
+
+#[cfg(feature = "eip1344")]
+#[test]
+fn chain_id_of_the_externalities() {
+	native::reset();
+	native::with_externalities(|ext| ext.chain_id = 42);
+	execute(|| assert_eq!(pwasm_ethereum::chain_id(), 42));
+
+	let _externals = ExternalsBuilder::new().chain_id(5).build();
+	execute(|| assert_eq!(pwasm_ethereum::chain_id(), 5));
+}
+
+#[cfg(feature = "eip3198")]
+#[test]
+fn base_fee_of_the_current_block() {
+	native::reset();
+	execute(|| assert_eq!(pwasm_ethereum::base_fee(), U256::zero()));
+
+	native::with_externalities(|ext| ext.block.base_fee = U256::from(7_000_000_000u64));
+	execute(|| assert_eq!(pwasm_ethereum::base_fee(), U256::from(7_000_000_000u64)));
+
+	let _externals = ExternalsBuilder::new().base_fee(U256::from(100)).build();
+	execute(|| assert_eq!(pwasm_ethereum::base_fee(), U256::from(100)));
+}
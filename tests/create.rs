@@ -0,0 +1,105 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native;
+use pwasm_ethereum::{Address, U256};
+#[cfg(feature = "kip4")]
+use pwasm_ethereum::{hash, H256};
+
+const CREATOR: [u8; 20] = [1u8; 20];
+const CODE: &[u8] = b"\0asm contract code";
+
+/// Runs `f` as the code of `CREATOR`, which holds 100 Wei.
+fn as_creator<F: FnOnce()>(f: F) {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.set_balance(Address::from(CREATOR), U256::from(100));
+		ext.frame_mut().address = Address::from(CREATOR);
+	});
+	let outcome = native::execute(f);
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+fn check_deployed(address: Address, balance: u64) {
+	native::with_externalities(|ext| {
+		assert_eq!(ext.code(&address), CODE);
+		assert_eq!(ext.balance(&address), U256::from(balance));
+		assert_eq!(ext.balance(&Address::from(CREATOR)), U256::from(100 - balance));
+	});
+}
+
+#[test]
+fn create_deploys_code_with_the_endowment() {
+	as_creator(|| {
+		let address = pwasm_ethereum::create(U256::from(10), CODE).unwrap();
+		check_deployed(address, 10);
+		let other = pwasm_ethereum::create(U256::zero(), CODE).unwrap();
+		assert!(other != address);
+	});
+}
+
+#[test]
+fn create_fails_without_the_endowment() {
+	as_creator(|| assert!(pwasm_ethereum::create(U256::from(101), CODE).is_err()));
+}
+
+#[cfg(feature = "kip4")]
+#[test]
+fn create2_deploys_at_the_eip1014_address() {
+	as_creator(|| {
+		let salt = H256::from([7u8; 32]);
+		let address = pwasm_ethereum::create2(U256::from(10), salt, CODE).unwrap();
+		assert_eq!(address, hash::create2_address(&Address::from(CREATOR), &salt, &hash::keccak256(CODE)));
+		check_deployed(address, 10);
+
+		let other = pwasm_ethereum::create2(U256::zero(), H256::from([8u8; 32]), CODE).unwrap();
+		assert!(other != address);
+	});
+}
+
+#[cfg(feature = "kip4")]
+#[test]
+fn create2_fails_on_an_existing_contract() {
+	as_creator(|| {
+		let salt = H256::from([7u8; 32]);
+		pwasm_ethereum::create2(U256::zero(), salt, CODE).unwrap();
+		assert!(pwasm_ethereum::create2(U256::zero(), salt, CODE).is_err());
+	});
+}
+
+#[cfg(feature = "create-gas")]
+#[test]
+fn create_with_gas_reports_the_new_contract() {
+	as_creator(|| {
+		let result = pwasm_ethereum::create_with_gas(100_000, U256::from(10), CODE);
+		// constructors do not run natively, so they use no gas and cannot revert
+		assert_eq!(result.gas_used, 0);
+		assert!(result.revert_data.is_empty());
+		check_deployed(result.address.unwrap(), 10);
+	});
+}
+
+#[cfg(feature = "create-gas")]
+#[test]
+fn create_with_gas_reports_failures() {
+	as_creator(|| {
+		let result = pwasm_ethereum::create_with_gas(100_000, U256::from(101), CODE);
+		assert_eq!(result, pwasm_ethereum::CreateResult { address: None, gas_used: 0, revert_data: Vec::new() });
+	});
+}
+
+#[cfg(all(feature = "kip4", feature = "create-gas"))]
+#[test]
+fn create2_with_gas_deploys_at_the_eip1014_address() {
+	as_creator(|| {
+		let salt = H256::from([7u8; 32]);
+		let result = pwasm_ethereum::create2_with_gas(100_000, U256::from(10), salt, CODE);
+		let expected = hash::create2_address(&Address::from(CREATOR), &salt, &hash::keccak256(CODE));
+		assert_eq!(result.address, Some(expected));
+		check_deployed(expected, 10);
+
+		let again = pwasm_ethereum::create2_with_gas(100_000, U256::zero(), salt, CODE);
+		assert_eq!(again.address, None);
+	});
+}
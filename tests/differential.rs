@@ -0,0 +1,69 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use std::collections::BTreeMap;
+
+use pwasm_ethereum::native::{self, Log};
+use pwasm_ethereum::native::differential::{self, Observation, Reference, Step};
+use pwasm_ethereum::{Address, H256, U256};
+
+const COUNTER: [u8; 20] = [1u8; 20];
+
+/// Counter adding `input[0]` to slot zero and logging the new value.
+fn counter() {
+	let mut word = pwasm_ethereum::read(&H256::zero());
+	word[31] += pwasm_ethereum::input()[0];
+	pwasm_ethereum::write(&H256::zero(), &word);
+	pwasm_ethereum::log(&[H256::from([7u8; 32])], &word);
+	pwasm_ethereum::ret(&word);
+}
+
+/// Model of the same counter standing in for an EVM, which may be off by one.
+struct Model {
+	value: u8,
+	off_by_one: bool,
+}
+
+impl Reference for Model {
+	fn run(&mut self, step: &Step) -> Observation {
+		self.value += step.input[0];
+		if self.off_by_one && self.value > 3 {
+			self.value += 1;
+		}
+		let mut word = [0u8; 32];
+		word[31] = self.value;
+		let mut storage = BTreeMap::new();
+		storage.insert(H256::zero(), word);
+		Observation {
+			success: true,
+			data: word.to_vec(),
+			logs: vec![Log { address: step.address, topics: vec![H256::from([7u8; 32])], data: word.to_vec() }],
+			storage: storage,
+		}
+	}
+}
+
+fn steps() -> Vec<Step> {
+	[1u8, 2, 3].iter()
+		.map(|&amount| Step { address: Address::from(COUNTER), value: U256::zero(), input: vec![amount] })
+		.collect()
+}
+
+#[test]
+fn matching_backends() {
+	native::reset();
+	native::with_externalities(|ext| ext.register(Address::from(COUNTER), counter));
+	assert_eq!(differential::compare(&steps(), &mut Model { value: 0, off_by_one: false }), Ok(()));
+}
+
+#[test]
+fn first_mismatch_is_reported() {
+	native::reset();
+	native::with_externalities(|ext| ext.register(Address::from(COUNTER), counter));
+	let mismatch = differential::compare(&steps(), &mut Model { value: 0, off_by_one: true }).unwrap_err();
+	assert_eq!(mismatch.step, 2);
+	assert_eq!(mismatch.field, "data");
+	assert_eq!(mismatch.native.data[31], 6);
+	assert_eq!(mismatch.reference.data[31], 7);
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "tiny-keccak")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::{eip712, hash, Address, H256};
+
+fn bytes(hex: &str) -> Vec<u8> {
+	(0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}
+
+fn address(hex: &str) -> Address {
+	let mut address = [0u8; 20];
+	address.copy_from_slice(&bytes(hex));
+	Address::from(address)
+}
+
+fn word(hex: &str) -> H256 {
+	let mut word = [0u8; 32];
+	word.copy_from_slice(&bytes(hex));
+	H256::from(word)
+}
+
+fn hash_words(words: &[H256]) -> H256 {
+	let encoded: Vec<u8> = words.iter().flat_map(|word| <[u8; 32]>::from(*word).to_vec()).collect();
+	hash::keccak256(&encoded)
+}
+
+fn address_word(address: &Address) -> H256 {
+	let mut word = [0u8; 32];
+	word[12..].copy_from_slice(&<[u8; 20]>::from(*address));
+	H256::from(word)
+}
+
+fn person(name: &str, wallet: &str) -> H256 {
+	let type_hash = eip712::type_hash("Person(string name,address wallet)");
+	hash_words(&[type_hash, hash::keccak256(name.as_bytes()), address_word(&address(wallet))])
+}
+
+// The `Mail` example of EIP-712.
+
+const MAIL_TYPE: &str = "Mail(Person from,Person to,string contents)Person(string name,address wallet)";
+
+fn mail_domain_separator() -> H256 {
+	eip712::domain_separator("Ether Mail", "1", 1, &address("cccccccccccccccccccccccccccccccccccccccc"))
+}
+
+fn mail_struct_hash() -> H256 {
+	hash_words(&[
+		eip712::type_hash(MAIL_TYPE),
+		person("Cow", "cd2a3d9f938e13cd947ec05abc7fe734df8dd826"),
+		person("Bob", "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+		hash::keccak256(b"Hello, Bob!"),
+	])
+}
+
+#[test]
+fn mail_type_hash() {
+	assert_eq!(
+		eip712::type_hash(MAIL_TYPE),
+		word("a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2")
+	);
+}
+
+#[test]
+fn mail_domain_separator_matches() {
+	assert_eq!(
+		mail_domain_separator(),
+		word("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+	);
+}
+
+#[test]
+fn mail_struct_hash_matches() {
+	assert_eq!(mail_struct_hash(), word("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"));
+}
+
+#[test]
+fn mail_typed_data_hash() {
+	assert_eq!(
+		eip712::typed_data_hash(&mail_domain_separator(), &mail_struct_hash()),
+		word("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+	);
+}
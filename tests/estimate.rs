@@ -0,0 +1,48 @@
+#![cfg(feature = "std")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::estimate::{self, DeployEstimate, DeploySchedule};
+use pwasm_ethereum::validate::ValidationError;
+
+/// Module with an empty function type and a single `env.ret` import: 27 bytes, 8 of them zero.
+fn module() -> Vec<u8> {
+	let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+	wasm.extend_from_slice(&[1, 4, 1, 0x60, 0, 0]);
+	wasm.extend_from_slice(&[2, 11, 1, 3, b'e', b'n', b'v', 3, b'r', b'e', b't', 0, 0]);
+	wasm
+}
+
+#[test]
+fn estimates_the_deployment_of_a_module() {
+	let wasm = module();
+	let estimate = estimate::estimate(&wasm, &DeploySchedule::default()).unwrap();
+	assert_eq!(estimate, DeployEstimate {
+		size: 27,
+		code_size: 27,
+		imports: 1,
+		intrinsic_gas: 21000 + 32000 + 8 * 4 + 19 * 68,
+		deposit_gas: 27 * 200,
+		exceeds_code_size: false,
+	});
+	assert_eq!(estimate.gas(), 54324 + 5400);
+}
+
+#[test]
+fn deposited_code_is_checked_against_the_schedule() {
+	let wasm = module();
+	let schedule = DeploySchedule { max_code_size: 100, ..DeploySchedule::default() };
+	let estimate = estimate::estimate_with_code(&wasm, 100, &schedule).unwrap();
+	assert_eq!((estimate.size, estimate.code_size), (27, 100));
+	assert_eq!(estimate.deposit_gas, 100 * 200);
+	assert!(!estimate.exceeds_code_size);
+	assert!(estimate::estimate_with_code(&wasm, 101, &schedule).unwrap().exceeds_code_size);
+}
+
+#[test]
+fn malformed_modules_are_rejected() {
+	assert_eq!(
+		estimate::estimate(b"\0wasm", &DeploySchedule::default()),
+		Err(ValidationError::Malformed("invalid magic")),
+	);
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::events::{self, DecodeEvent, Event, FromTopic, LogBuilder, LogReader, Topic};
+use pwasm_ethereum::{native, Address, H256, U256};
+
+#[derive(Debug, PartialEq)]
+struct Transfer {
+	from: Address,
+	to: Address,
+	value: U256,
+}
+
+impl Event for Transfer {
+	fn topic0() -> H256 {
+		events::signature_topic("Transfer(address,address,uint256)")
+	}
+
+	fn write(&self, log: LogBuilder) -> LogBuilder {
+		log.topic(&self.from).topic(&self.to).word(&self.value)
+	}
+}
+
+impl DecodeEvent for Transfer {
+	fn read(log: &mut LogReader) -> Option<Self> {
+		Some(Transfer { from: log.topic()?, to: log.topic()?, value: log.word()? })
+	}
+}
+
+fn transfer(value: u64) -> Transfer {
+	Transfer { from: Address::from([1u8; 20]), to: Address::from([2u8; 20]), value: U256::from(value) }
+}
+
+#[test]
+fn decoded_logs_of_one_event() {
+	native::reset();
+	let outcome = native::execute(|| {
+		transfer(5).emit();
+		events::log1(events::signature_topic("Approval(address,address,uint256)"), &[]);
+		transfer(7).emit();
+	});
+	assert!(outcome.is_success());
+	native::with_externalities(|ext| {
+		assert_eq!(ext.logs.len(), 3);
+		assert_eq!(ext.decoded_logs::<Transfer>(), vec![transfer(5), transfer(7)]);
+	});
+}
+
+#[test]
+fn decode_rejects_other_events_and_leftovers() {
+	let from = Address::from([1u8; 20]).to_topic();
+	let to = Address::from([2u8; 20]).to_topic();
+	let value = [0u8; 32];
+	assert!(Transfer::decode(&[Transfer::topic0(), from, to], &value).is_some());
+	assert!(Transfer::decode(&[H256::zero(), from, to], &value).is_none());
+	assert!(Transfer::decode(&[Transfer::topic0(), from], &value).is_none());
+	assert!(Transfer::decode(&[Transfer::topic0(), from, to], &[0u8; 64]).is_none());
+}
+
+#[test]
+fn from_topic_rejects_dirty_words() {
+	let mut word = [0u8; 32];
+	word[0] = 1;
+	assert_eq!(Address::from_topic(&H256::from(word)), None);
+	assert_eq!(u64::from_topic(&H256::from(word)), None);
+	assert_eq!(bool::from_topic(&H256::from(word)), None);
+	word[0] = 0;
+	word[31] = 2;
+	assert_eq!(bool::from_topic(&H256::from(word)), None);
+	assert_eq!(u64::from_topic(&H256::from(word)), Some(2));
+}
+
+#[test]
+fn topic_encoding() {
+	let mut address = [0u8; 32];
+	address[12..].copy_from_slice(&[1u8; 20]);
+	assert_eq!(Address::from([1u8; 20]).to_topic(), H256::from(address));
+	let mut value = [0u8; 32];
+	value[30] = 1;
+	value[31] = 2;
+	assert_eq!(U256::from(0x102).to_topic(), H256::from(value));
+	assert_eq!(0x102u64.to_topic(), H256::from(value));
+	let mut flag = [0u8; 32];
+	flag[31] = 1;
+	assert_eq!(true.to_topic(), H256::from(flag));
+	assert_eq!(false.to_topic(), H256::zero());
+	assert_eq!(
+		<[u8; 32]>::from(Transfer::topic0())[..4],
+		[0xdd, 0xf2, 0x52, 0xad]
+	);
+}
+
+#[test]
+fn logged_topics_and_data() {
+	native::reset();
+	let topic0 = H256::from([7u8; 32]);
+	let outcome = native::execute(|| {
+		events::log1(topic0, b"one");
+		events::log2(topic0, &1u64, b"two");
+		events::log3(topic0, &1u64, &true, b"");
+		events::log4(topic0, &1u64, &true, &Address::from([1u8; 20]), b"");
+		LogBuilder::anonymous().topic(&2u64).word(&3u64).data(b"raw").emit();
+		transfer(5).emit();
+	});
+	assert!(outcome.is_success());
+	native::with_externalities(|ext| {
+		let topics: Vec<usize> = ext.logs.iter().map(|log| log.topics.len()).collect();
+		assert_eq!(topics, vec![1, 2, 3, 4, 1, 3]);
+		assert_eq!(ext.logs[1].topics, vec![topic0, 1u64.to_topic()]);
+		assert_eq!(ext.logs[1].data, b"two".to_vec());
+		assert_eq!(ext.logs[4].topics, vec![2u64.to_topic()]);
+		let mut data = <[u8; 32]>::from(3u64.to_topic()).to_vec();
+		data.extend_from_slice(b"raw");
+		assert_eq!(ext.logs[4].data, data);
+		assert_eq!(ext.logs[5].topics, vec![
+			Transfer::topic0(),
+			Address::from([1u8; 20]).to_topic(),
+			Address::from([2u8; 20]).to_topic(),
+		]);
+		assert_eq!(ext.logs[5].data, <[u8; 32]>::from(U256::from(5).to_topic()).to_vec());
+	});
+}
+
+#[test]
+fn fifth_topic_traps() {
+	native::reset();
+	let outcome = native::execute(|| {
+		LogBuilder::new(H256::zero()).topic(&1u64).topic(&2u64).topic(&3u64).topic(&4u64).emit();
+	});
+	assert!(!outcome.is_success());
+	native::with_externalities(|ext| assert!(ext.logs.is_empty()));
+}
@@ -0,0 +1,182 @@
+//! Checks the eWASM backend against a mock of the Ethereum Environment Interface.
+//!
+//! The EEI imports are resolved at link time by the functions below, so the backend runs on
+//! the host: `cargo test --features backend-ewasm,std --test ewasm`. EEI `log` is left to the
+//! linker, which resolves it to the math library, so logs are not covered here.
+
+#![cfg(all(feature = "backend-ewasm", feature = "std", not(feature = "native")))]
+#![allow(non_snake_case)]
+
+extern crate pwasm_ethereum;
+
+use std::cell::RefCell;
+use std::{process, ptr, slice};
+
+use pwasm_ethereum::{Address, CallError, U256};
+
+#[derive(Default)]
+struct Eei {
+	/// Status returned by calls and creations.
+	status: i32,
+	/// Data returned by the last call.
+	return_data: Vec<u8>,
+	/// Little-endian value given to the last call or creation.
+	value: [u8; 16],
+	/// Little-endian balance of every account.
+	balance: [u8; 16],
+}
+
+thread_local!(static EEI: RefCell<Eei> = RefCell::new(Eei::default()));
+
+fn with_eei<R, F: FnOnce(&mut Eei) -> R>(f: F) -> R {
+	EEI.with(|eei| f(&mut eei.borrow_mut()))
+}
+
+unsafe fn write(dest: *mut u8, data: &[u8]) {
+	ptr::copy_nonoverlapping(data.as_ptr(), dest, data.len());
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn call(_gas: i64, _address: *const u8, value: *const u8, _data: *const u8, _len: u32) -> i32 {
+	with_eei(|eei| {
+		eei.value.copy_from_slice(slice::from_raw_parts(value, 16));
+		eei.status
+	})
+}
+#[no_mangle]
+pub unsafe extern "C" fn callDelegate(_gas: i64, _address: *const u8, _data: *const u8, _len: u32) -> i32 {
+	with_eei(|eei| eei.status)
+}
+#[no_mangle]
+pub unsafe extern "C" fn callStatic(_gas: i64, _address: *const u8, _data: *const u8, _len: u32) -> i32 {
+	with_eei(|eei| eei.status)
+}
+#[no_mangle]
+pub unsafe extern "C" fn create(value: *const u8, _data: *const u8, _len: u32, result: *mut u8) -> i32 {
+	with_eei(|eei| {
+		eei.value.copy_from_slice(slice::from_raw_parts(value, 16));
+		write(result, &[0xcc; 20]);
+		eei.status
+	})
+}
+#[no_mangle]
+pub unsafe extern "C" fn getReturnDataSize() -> u32 {
+	with_eei(|eei| eei.return_data.len() as u32)
+}
+#[no_mangle]
+pub unsafe extern "C" fn returnDataCopy(result: *mut u8, offset: u32, len: u32) {
+	with_eei(|eei| write(result, &eei.return_data[offset as usize..(offset + len) as usize]))
+}
+#[no_mangle]
+pub unsafe extern "C" fn getExternalBalance(_address: *const u8, result: *mut u8) {
+	with_eei(|eei| write(result, &eei.balance))
+}
+#[no_mangle]
+pub unsafe extern "C" fn getBlockGasLimit() -> i64 {
+	0x0102_0304_0506_0708
+}
+
+// imports the tests do not use
+#[no_mangle] pub extern "C" fn getBlockHash(_number: i64, _result: *mut u8) -> i32 { 1 }
+#[no_mangle] pub extern "C" fn getExternalCodeSize(_address: *const u8) -> u32 { 0 }
+#[no_mangle] pub extern "C" fn externalCodeCopy(_address: *const u8, _result: *mut u8, _offset: u32, _len: u32) {}
+#[no_mangle] pub extern "C" fn getBlockCoinbase(_result: *mut u8) {}
+#[no_mangle] pub extern "C" fn getBlockTimestamp() -> i64 { 0 }
+#[no_mangle] pub extern "C" fn getBlockNumber() -> i64 { 0 }
+#[no_mangle] pub extern "C" fn getBlockDifficulty(_result: *mut u8) {}
+#[no_mangle] pub extern "C" fn getGasLeft() -> i64 { 0 }
+#[no_mangle] pub extern "C" fn getTxGasPrice(_result: *mut u8) {}
+#[no_mangle] pub extern "C" fn getCaller(_result: *mut u8) {}
+#[no_mangle] pub extern "C" fn getAddress(_result: *mut u8) {}
+#[no_mangle] pub extern "C" fn getCallValue(_result: *mut u8) {}
+#[no_mangle] pub extern "C" fn getTxOrigin(_result: *mut u8) {}
+#[no_mangle] pub extern "C" fn selfDestruct(_beneficiary: *const u8) -> ! { process::abort() }
+#[no_mangle] pub extern "C" fn finish(_data: *const u8, _len: u32) -> ! { process::abort() }
+#[no_mangle] pub extern "C" fn revert(_data: *const u8, _len: u32) -> ! { process::abort() }
+#[no_mangle] pub extern "C" fn getCallDataSize() -> u32 { 0 }
+#[no_mangle] pub extern "C" fn callDataCopy(_result: *mut u8, _offset: u32, _len: u32) {}
+#[no_mangle] pub extern "C" fn storageLoad(_key: *const u8, _result: *mut u8) {}
+#[no_mangle] pub extern "C" fn storageStore(_key: *const u8, _value: *const u8) {}
+
+/// Makes the next calls end with the EEI `status`, returning `data`.
+fn returning(status: i32, data: &[u8]) {
+	with_eei(|eei| {
+		eei.status = status;
+		eei.return_data = data.to_vec();
+	});
+}
+
+fn callee() -> Address {
+	Address::from([1u8; 20])
+}
+
+#[test]
+fn successful_calls_return_their_data() {
+	returning(0, b"output");
+	let mut result = [0u8; 3];
+	assert_eq!(pwasm_ethereum::call(1000, &callee(), U256::zero(), &[], &mut result), Ok(()));
+	assert_eq!(&result, b"out");
+	assert_eq!(pwasm_ethereum::return_data(), b"output".to_vec());
+	assert_eq!(pwasm_ethereum::call_code(1000, &callee(), &[], &mut result), Ok(()));
+	assert_eq!(pwasm_ethereum::static_call(1000, &callee(), &[], &mut result), Ok(()));
+}
+
+#[test]
+fn eei_revert_status_is_a_revert() {
+	returning(2, b"nope");
+	let mut result = [0u8; 4];
+	let reverted = Err(CallError::Reverted(b"nope".to_vec()));
+	assert_eq!(pwasm_ethereum::call(1000, &callee(), U256::zero(), &[], &mut result), reverted);
+	assert_eq!(&result, b"nope");
+	assert_eq!(pwasm_ethereum::call_code(1000, &callee(), &[], &mut []), reverted);
+	assert_eq!(pwasm_ethereum::static_call(1000, &callee(), &[], &mut []), reverted);
+}
+
+#[test]
+fn eei_failure_status_is_a_trap() {
+	// EEI does not tell running out of gas apart from other failures
+	returning(1, b"stale");
+	let mut result = [0u8; 5];
+	assert_eq!(pwasm_ethereum::call(1000, &callee(), U256::zero(), &[], &mut result), Err(CallError::Trapped));
+	assert_eq!(result, [0u8; 5]);
+	assert_eq!(pwasm_ethereum::call_code(1000, &callee(), &[], &mut result), Err(CallError::Trapped));
+	assert_eq!(pwasm_ethereum::static_call(1000, &callee(), &[], &mut result), Err(CallError::Trapped));
+}
+
+#[test]
+fn create_status() {
+	returning(0, &[]);
+	assert_eq!(pwasm_ethereum::create(U256::from(5), b"code"), Ok(Address::from([0xcc; 20])));
+	assert_eq!(with_eei(|eei| eei.value[0]), 5);
+	returning(2, &[]);
+	assert!(pwasm_ethereum::create(U256::zero(), b"code").is_err());
+	returning(1, &[]);
+	assert!(pwasm_ethereum::create(U256::zero(), b"code").is_err());
+}
+
+#[test]
+fn values_are_passed_as_128_bit_little_endian() {
+	returning(0, &[]);
+	let value = U256::from_big_endian(&(1..17).collect::<Vec<u8>>());
+	pwasm_ethereum::call(1000, &callee(), value, &[], &mut []).unwrap();
+	assert_eq!(with_eei(|eei| eei.value), [16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1]);
+
+	let max = (U256::one() << 128) - U256::one();
+	pwasm_ethereum::call(1000, &callee(), max, &[], &mut []).unwrap();
+	assert_eq!(with_eei(|eei| eei.value), [0xff; 16]);
+}
+
+#[test]
+#[should_panic(expected = "trap")]
+fn values_above_128_bits_trap() {
+	returning(0, &[]);
+	let _ = pwasm_ethereum::call(1000, &callee(), U256::one() << 128, &[], &mut []);
+}
+
+#[test]
+fn little_endian_results_are_converted() {
+	with_eei(|eei| eei.balance = [1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x80]);
+	let expected = (U256::from(0x80) << 120) + U256::from(0x0201);
+	assert_eq!(pwasm_ethereum::balance(&callee()), expected);
+	assert_eq!(pwasm_ethereum::gas_limit(), U256::from(0x0102_0304_0506_0708u64));
+}
@@ -0,0 +1,137 @@
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::CallError;
+#[cfg(feature = "native")]
+use pwasm_ethereum::{native, H256};
+
+fn error_payload(offset: u8, len: u8, message: &[u8]) -> Vec<u8> {
+	let mut payload = CallError::ERROR_SELECTOR.to_vec();
+	let mut word = [0u8; 32];
+	word[31] = offset;
+	payload.extend_from_slice(&word);
+	word[31] = len;
+	payload.extend_from_slice(&word);
+	payload.extend_from_slice(message);
+	payload.resize(68 + (message.len() + 31) / 32 * 32, 0);
+	payload
+}
+
+#[test]
+fn reason_of_error_string() {
+	let error = CallError::Reverted(error_payload(32, 5, b"hello"));
+	assert_eq!(error.reason(), Some("hello"));
+	assert_eq!(CallError::Reverted(error_payload(32, 0, b"")).reason(), Some(""));
+}
+
+#[test]
+fn reason_of_short_payload() {
+	let payload = error_payload(32, 5, b"hello");
+	assert_eq!(CallError::Reverted(payload[..4].to_vec()).reason(), None);
+	assert_eq!(CallError::Reverted(payload[..40].to_vec()).reason(), None);
+	assert_eq!(CallError::Reverted(payload[..70].to_vec()).reason(), None);
+	assert_eq!(CallError::Reverted(Vec::new()).reason(), None);
+}
+
+#[test]
+fn reason_with_bad_offset() {
+	assert_eq!(CallError::Reverted(error_payload(64, 5, b"hello")).reason(), None);
+	assert_eq!(CallError::Reverted(error_payload(0, 5, b"hello")).reason(), None);
+}
+
+#[test]
+fn reason_of_non_utf8_message() {
+	assert_eq!(CallError::Reverted(error_payload(32, 2, &[0xc3, 0x28])).reason(), None);
+}
+
+#[test]
+fn reason_of_other_errors() {
+	let mut payload = error_payload(32, 5, b"hello");
+	payload[0] = 0;
+	assert_eq!(CallError::Reverted(payload).reason(), None);
+	assert_eq!(CallError::OutOfGas.reason(), None);
+	assert_eq!(CallError::Trapped.reason(), None);
+}
+
+#[cfg(feature = "native")]
+#[test]
+fn log_sends_up_to_four_topics() {
+	native::reset();
+	let topics: Vec<H256> = (1..5u8).map(|i| H256::from([i; 32])).collect();
+	let sent = topics.clone();
+	let outcome = native::execute(move || {
+		for count in 0..5 {
+			pwasm_ethereum::log(&sent[..count], &[count as u8]);
+		}
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+	native::with_externalities(|ext| {
+		assert_eq!(ext.logs.len(), 5);
+		for (count, log) in ext.logs.iter().enumerate() {
+			assert_eq!(&log.topics[..], &topics[..count]);
+			assert_eq!(log.data, vec![count as u8]);
+		}
+	});
+}
+
+#[cfg(feature = "native")]
+#[test]
+fn log_traps_on_more_than_four_topics() {
+	native::reset();
+	let outcome = native::execute(|| {
+		let topics = [H256::from([1u8; 32]); 5];
+		pwasm_ethereum::log(&topics, b"data");
+	});
+	match outcome {
+		native::Outcome::Trap(_) => {},
+		outcome => panic!("expected a trap, got {:?}", outcome),
+	}
+	native::with_externalities(|ext| assert!(ext.logs.is_empty()));
+}
+
+#[cfg(feature = "native")]
+#[test]
+fn try_log_rejects_more_than_four_topics() {
+	native::reset();
+	let outcome = native::execute(|| {
+		let topics = [H256::from([1u8; 32]); 5];
+		assert!(pwasm_ethereum::try_log(&topics, b"data").is_err());
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+	native::with_externalities(|ext| assert!(ext.logs.is_empty()));
+}
+
+#[cfg(feature = "native")]
+#[test]
+fn try_block_hash_of_available_blocks_only() {
+	native::reset();
+	let hash = H256::from([7u8; 32]);
+	native::with_externalities(|ext| {
+		ext.block.number = 1000;
+		ext.block.hashes.insert(999, hash);
+		ext.block.hashes.insert(744, hash);
+		ext.block.hashes.insert(743, hash);
+	});
+	let outcome = native::execute(move || {
+		assert_eq!(pwasm_ethereum::try_block_hash(999), Some(hash));
+		assert_eq!(pwasm_ethereum::try_block_hash(744), Some(hash));
+		assert_eq!(pwasm_ethereum::try_block_hash(743), None);
+		assert_eq!(pwasm_ethereum::try_block_hash(998), None);
+		assert_eq!(pwasm_ethereum::try_block_hash(1000), None);
+		assert_eq!(pwasm_ethereum::try_block_hash(1001), None);
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[cfg(all(feature = "native", feature = "eip211"))]
+#[test]
+fn call_builder_rejects_value_without_calling() {
+	native::reset();
+	let outcome = native::execute(|| {
+		let builder = pwasm_ethereum::CallBuilder::new(&pwasm_ethereum::Address::from([1u8; 20]))
+			.gas(50_000)
+			.value(1u64.into());
+		assert_eq!(builder.static_call(), Err(CallError::Invalid));
+		assert_eq!(builder.delegate_call(), Err(CallError::Invalid));
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
@@ -0,0 +1,59 @@
+#![cfg(all(feature = "native", feature = "extcode"))]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native;
+use pwasm_ethereum::{hash, Address, H256, U256};
+
+const CONTRACT: [u8; 20] = [1u8; 20];
+const ACCOUNT: [u8; 20] = [2u8; 20];
+const MISSING: [u8; 20] = [3u8; 20];
+const CODE: &[u8] = b"\0asm contract code longer than a single word";
+
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.register(Address::from(CONTRACT), || {});
+		ext.accounts.get_mut(&Address::from(CONTRACT)).unwrap().code = CODE.to_vec();
+		ext.set_balance(Address::from(ACCOUNT), U256::from(1));
+	});
+}
+
+fn execute<F: FnOnce()>(f: F) {
+	let outcome = native::execute(f);
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn code_of_contracts() {
+	setup();
+	execute(|| {
+		let contract = Address::from(CONTRACT);
+		assert_eq!(pwasm_ethereum::code_size(&contract), CODE.len() as u64);
+		assert_eq!(pwasm_ethereum::code_at(&contract), CODE.to_vec());
+		assert_eq!(pwasm_ethereum::code_hash(&contract), hash::keccak256(CODE));
+	});
+}
+
+#[test]
+fn code_of_external_accounts() {
+	setup();
+	execute(|| {
+		let account = Address::from(ACCOUNT);
+		assert_eq!(pwasm_ethereum::code_size(&account), 0);
+		assert!(pwasm_ethereum::code_at(&account).is_empty());
+		// existing accounts without code hash to the hash of empty code
+		assert_eq!(pwasm_ethereum::code_hash(&account), hash::keccak256(&[]));
+	});
+}
+
+#[test]
+fn code_of_missing_accounts() {
+	setup();
+	execute(|| {
+		let missing = Address::from(MISSING);
+		assert_eq!(pwasm_ethereum::code_size(&missing), 0);
+		assert!(pwasm_ethereum::code_at(&missing).is_empty());
+		assert_eq!(pwasm_ethereum::code_hash(&missing), H256::zero());
+	});
+}
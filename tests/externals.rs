@@ -0,0 +1,118 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::test::{ExpectedCall, ExternalsBuilder};
+use pwasm_ethereum::{native, test, Address, CallError, U256};
+
+const TOKEN: [u8; 20] = [7u8; 20];
+
+fn call_token() -> Result<u8, CallError> {
+	let mut result = [0u8; 1];
+	pwasm_ethereum::call(100_000, &Address::from(TOKEN), U256::zero(), &[], &mut result)?;
+	Ok(result[0])
+}
+
+#[cfg(feature = "eip140")]
+#[test]
+fn expect_revert_returns_the_payload() {
+	native::reset();
+	assert_eq!(test::expect_revert(native::execute(|| pwasm_ethereum::revert(b"nope"))), b"nope".to_vec());
+}
+
+#[cfg(feature = "eip140")]
+#[test]
+fn expect_revert_reason_decodes_error_strings() {
+	native::reset();
+	test::expect_revert_reason(native::execute(|| pwasm_ethereum::revert_with_reason("not the owner")), "not the owner");
+}
+
+#[cfg(feature = "eip140")]
+#[test]
+#[should_panic(expected = "unexpected revert reason")]
+fn expect_revert_reason_rejects_other_reasons() {
+	native::reset();
+	test::expect_revert_reason(native::execute(|| pwasm_ethereum::revert_with_reason("paused")), "not the owner");
+}
+
+#[cfg(feature = "eip140")]
+#[test]
+fn expect_revert_error_strips_the_selector() {
+	native::reset();
+	let args = test::expect_revert_error(native::execute(|| pwasm_ethereum::revert(&[1, 2, 3, 4, 5])), [1, 2, 3, 4]);
+	assert_eq!(args, vec![5]);
+}
+
+#[test]
+#[should_panic(expected = "expected a revert")]
+fn expect_revert_rejects_success() {
+	native::reset();
+	test::expect_revert(native::execute(|| {}));
+}
+
+#[test]
+#[should_panic(expected = "expected a revert")]
+fn expect_revert_rejects_traps() {
+	native::reset();
+	test::expect_revert(native::execute(|| panic!("trap")));
+}
+
+#[test]
+fn exhausted_expectations_are_skipped() {
+	let externals = ExternalsBuilder::new()
+		.expect_call(ExpectedCall::to(Address::from(TOKEN)).returning(vec![1]))
+		.expect_call(ExpectedCall::to(Address::from(TOKEN)).returning(vec![2]).times(2))
+		.build();
+	let outcome = native::execute(|| {
+		assert_eq!(call_token(), Ok(1));
+		assert_eq!(call_token(), Ok(2));
+		assert_eq!(call_token(), Ok(2));
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+	assert_eq!(externals.calls_to(&Address::from(TOKEN)), 3);
+	externals.verify();
+}
+
+#[test]
+#[should_panic(expected = "expected 1 call(s)")]
+fn extra_calls_fail_verification() {
+	let externals = ExternalsBuilder::new()
+		.expect_call(ExpectedCall::to(Address::from(TOKEN)).returning(vec![1]))
+		.build();
+	let outcome = native::execute(|| {
+		assert_eq!(call_token(), Ok(1));
+		assert_eq!(call_token(), Ok(1));
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+	externals.verify();
+}
+
+#[test]
+fn reverting_expectation() {
+	let externals = ExternalsBuilder::new()
+		.expect_call(ExpectedCall::to(Address::from(TOKEN)).reverting(b"nope".to_vec()))
+		.build();
+	let outcome = native::execute(|| {
+		let expected = if cfg!(all(feature = "eip140", feature = "eip211")) {
+			CallError::Reverted(b"nope".to_vec())
+		} else {
+			CallError::Trapped
+		};
+		assert_eq!(call_token(), Err(expected));
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+	externals.verify();
+}
+
+#[test]
+fn reverting_expectation_traps_without_eip140() {
+	let _externals = ExternalsBuilder::new()
+		.expect_call(ExpectedCall::to(Address::from(TOKEN)).reverting(b"nope".to_vec()))
+		.build();
+	let outcome = native::transact(Address::from(TOKEN), U256::zero(), Vec::new());
+	if cfg!(feature = "eip140") {
+		assert_eq!(outcome, native::Outcome::Revert(b"nope".to_vec()));
+	} else {
+		assert_eq!(outcome, native::Outcome::Trap("trap".into()));
+	}
+}
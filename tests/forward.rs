@@ -0,0 +1,70 @@
+#![cfg(all(feature = "native", feature = "eip211", feature = "kip6"))]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::forward::forward;
+use pwasm_ethereum::native::{self, Outcome};
+use pwasm_ethereum::{Address, U256};
+
+const ROUTER: [u8; 20] = [1u8; 20];
+const TARGET: [u8; 20] = [2u8; 20];
+const USER: [u8; 20] = [3u8; 20];
+
+fn setup<F: Fn() + 'static>(target: F) {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.origin = Address::from(USER);
+		ext.set_balance(Address::from(USER), U256::from(1_000));
+		ext.register(Address::from(ROUTER), || forward(&Address::from(TARGET)));
+		ext.register(Address::from(TARGET), target);
+	});
+}
+
+fn balance(address: [u8; 20]) -> U256 {
+	native::with_externalities(|ext| ext.balance(&Address::from(address)))
+}
+
+#[test]
+fn forwards_input_value_and_return_data() {
+	setup(|| {
+		let mut output = <[u8; 20]>::from(pwasm_ethereum::sender()).to_vec();
+		output.push(pwasm_ethereum::value().low_u64() as u8);
+		output.extend_from_slice(&pwasm_ethereum::input());
+		pwasm_ethereum::ret(&output)
+	});
+
+	let outcome = native::transact(Address::from(ROUTER), U256::from(7), b"ping".to_vec());
+	let expected = [&ROUTER[..], &[7u8][..], &b"ping"[..]].concat();
+	assert_eq!(outcome, Outcome::Return(expected));
+	assert_eq!(balance(USER), U256::from(993));
+	assert_eq!(balance(ROUTER), U256::zero());
+	assert_eq!(balance(TARGET), U256::from(7));
+}
+
+#[test]
+fn forwards_long_return_data() {
+	setup(|| pwasm_ethereum::ret(&[0xab; 1000]));
+
+	let outcome = native::transact(Address::from(ROUTER), U256::zero(), Vec::new());
+	assert_eq!(outcome, Outcome::Return(vec![0xab; 1000]));
+}
+
+#[cfg(feature = "eip140")]
+#[test]
+fn forwards_revert_data() {
+	setup(|| pwasm_ethereum::revert(b"denied"));
+
+	let outcome = native::transact(Address::from(ROUTER), U256::from(7), b"ping".to_vec());
+	assert_eq!(outcome, Outcome::Revert(b"denied".to_vec()));
+	assert_eq!(balance(USER), U256::from(1_000));
+	assert_eq!(balance(TARGET), U256::zero());
+}
+
+#[test]
+fn traps_when_the_target_fails() {
+	setup(|| panic!("broken target"));
+
+	let outcome = native::transact(Address::from(ROUTER), U256::from(7), Vec::new());
+	assert_eq!(outcome, Outcome::Trap("trap".into()));
+	assert_eq!(balance(USER), U256::from(1_000));
+}
@@ -0,0 +1,215 @@
+#![cfg(all(feature = "native", feature = "kip6"))]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::gas::{self, GasMeter, Meter};
+use pwasm_ethereum::native::{self, GasSchedule, Outcome};
+use pwasm_ethereum::{hash, test, Address, H256, U256};
+
+const CONTRACT: [u8; 20] = [1u8; 20];
+const CALLEE: [u8; 20] = [2u8; 20];
+const LABEL: [u8; 32] = [0x1a; 32];
+/// Gas of a `gasleft`, under all schedules.
+const GASLEFT: u64 = 2;
+/// Gas of a write into an empty slot under the frontier schedule.
+const SSTORE_SET: u64 = 20000;
+/// Gas of a call under the byzantium schedule.
+const CALL: u64 = 700;
+
+/// Registers `entry` at `CONTRACT` and meters gas with `schedule`.
+fn setup<F: Fn() + 'static>(schedule: Option<GasSchedule>, entry: F) {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.gas_schedule = schedule;
+		ext.register(Address::from(CONTRACT), entry);
+	});
+}
+
+fn call() -> Outcome {
+	native::transact(Address::from(CONTRACT), U256::zero(), Vec::new())
+}
+
+fn store() {
+	pwasm_ethereum::write(&H256::zero(), &[1u8; 32]);
+}
+
+fn expect_budget_exceeded(outcome: Outcome) {
+	if cfg!(feature = "eip140") {
+		test::expect_revert_reason(outcome, "gas budget exceeded");
+	} else {
+		assert!(!outcome.is_success(), "{:?}", outcome);
+	}
+	native::with_externalities(|ext| {
+		assert_eq!(ext.storage_at(&Address::from(CONTRACT), &H256::zero()), [0u8; 32]);
+		assert!(ext.logs.is_empty());
+	});
+}
+
+#[test]
+fn gas_used_topic() {
+	assert_eq!(H256::from(gas::GAS_USED_TOPIC), hash::keccak256(b"GasUsed(bytes32,uint256)"));
+}
+
+#[test]
+fn labelled_meter_logs_the_gas_used() {
+	setup(Some(GasSchedule::frontier()), || {
+		let _meter = GasMeter::start().with_label(H256::from(LABEL));
+		store();
+	});
+	let outcome = call();
+	assert!(outcome.is_success(), "{:?}", outcome);
+
+	native::with_externalities(|ext| {
+		assert_eq!(ext.logs.len(), 1);
+		let log = &ext.logs[0];
+		assert_eq!(log.topics, vec![H256::from(gas::GAS_USED_TOPIC), H256::from(LABEL)]);
+		assert_eq!(U256::from_big_endian(&log.data), U256::from(SSTORE_SET + GASLEFT));
+	});
+}
+
+#[test]
+fn unlabelled_meter_logs_nothing() {
+	setup(Some(GasSchedule::frontier()), || {
+		let meter = GasMeter::start();
+		store();
+		assert_eq!(meter.used(), SSTORE_SET + GASLEFT);
+	});
+	let outcome = call();
+	assert!(outcome.is_success(), "{:?}", outcome);
+	native::with_externalities(|ext| assert!(ext.logs.is_empty()));
+}
+
+#[test]
+fn budget_is_enforced_when_dropped() {
+	setup(Some(GasSchedule::frontier()), || {
+		let _meter = GasMeter::start().with_budget(SSTORE_SET + GASLEFT).with_label(H256::from(LABEL));
+		store();
+	});
+	let outcome = call();
+	assert!(outcome.is_success(), "{:?}", outcome);
+
+	setup(Some(GasSchedule::frontier()), || {
+		let _meter = GasMeter::start().with_budget(SSTORE_SET + GASLEFT - 1).with_label(H256::from(LABEL));
+		store();
+	});
+	expect_budget_exceeded(call());
+}
+
+#[test]
+fn check_stops_once_the_budget_is_exceeded() {
+	setup(Some(GasSchedule::frontier()), || {
+		let meter = GasMeter::start().with_budget(100).with_label(H256::from(LABEL));
+		meter.check();
+		store();
+		meter.check();
+		panic!("unreachable after an exceeded budget");
+	});
+	expect_budget_exceeded(call());
+}
+
+#[test]
+fn unmetered_execution_uses_no_gas() {
+	setup(None, || {
+		let meter = GasMeter::start().with_budget(0);
+		store();
+		assert_eq!(meter.used(), 0);
+	});
+	let outcome = call();
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+/// Registers `callee` at `CALLEE` and gives `gas` to the next top-level call.
+fn with_callee<F: Fn() + 'static>(gas: u64, callee: F) {
+	native::with_externalities(|ext| {
+		ext.register(Address::from(CALLEE), callee);
+		ext.frame_mut().gas = gas;
+	});
+}
+
+/// Returns the gas left to the callee, as a 32-byte word.
+fn report_gas() {
+	let mut word = [0u8; 32];
+	U256::from(pwasm_ethereum::gas_left()).to_big_endian(&mut word);
+	pwasm_ethereum::ret(&word)
+}
+
+#[test]
+fn all_but_one_64th() {
+	assert_eq!(gas::all_but_one_64th(0), 0);
+	assert_eq!(gas::all_but_one_64th(63), 63);
+	assert_eq!(gas::all_but_one_64th(64), 63);
+	assert_eq!(gas::all_but_one_64th(6400), 6300);
+	assert_eq!(gas::all_but_one_64th(u64::max_value()), u64::max_value() - u64::max_value() / 64);
+}
+
+#[test]
+fn meter_counts_consumed_and_remaining_gas() {
+	setup(Some(GasSchedule::frontier()), || {
+		let meter = Meter::new();
+		store();
+		assert_eq!(meter.consumed(), SSTORE_SET + GASLEFT);
+
+		// every check reads the remaining gas, which costs a `gasleft` itself
+		let remaining = meter.remaining();
+		assert!(meter.require(remaining - GASLEFT).is_ok());
+		assert!(meter.require(remaining - GASLEFT).is_err());
+	});
+	let outcome = call();
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn forwardable_gas_follows_the_63_64_rule() {
+	const GAS: u64 = 100_000;
+	const RESERVE: u64 = 10_000;
+	setup(Some(GasSchedule::byzantium()), || {
+		let meter = Meter::new();
+		let forwarded = meter.forwardable(RESERVE);
+		assert_eq!(forwarded, gas::all_but_one_64th(GAS - 2 * GASLEFT - RESERVE));
+
+		let mut result = [0u8; 32];
+		assert!(pwasm_ethereum::call(forwarded, &Address::from(CALLEE), U256::zero(), &[], &mut result).is_ok());
+		assert_eq!(U256::from_big_endian(&result), U256::from(forwarded - GASLEFT));
+	});
+	with_callee(GAS, report_gas);
+	let outcome = call();
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn forwardable_gas_keeps_the_reserve_when_the_callee_fails() {
+	const GAS: u64 = 20_000;
+	const RESERVE: u64 = 10_000;
+	setup(Some(GasSchedule::byzantium()), || {
+		let meter = Meter::new();
+		let forwarded = meter.forwardable(RESERVE);
+		assert!(pwasm_ethereum::call(forwarded, &Address::from(CALLEE), U256::zero(), &[], &mut []).is_err());
+		// the failed callee consumed all it got: the reserve is left, less the price of the call
+		assert!(meter.require(RESERVE - CALL).is_ok());
+	});
+	with_callee(GAS, || panic!("consumes all its gas"));
+	let outcome = call();
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn forwardable_gas_fits_uncapped_schedules() {
+	const GAS: u64 = 100_000;
+	setup(GasSchedule::named("kip"), || {
+		let forwarded = Meter::new().forwardable(0);
+		let mut result = [0u8; 32];
+		assert!(pwasm_ethereum::call(forwarded, &Address::from(CALLEE), U256::zero(), &[], &mut result).is_ok());
+		assert_eq!(U256::from_big_endian(&result), U256::from(forwarded - GASLEFT));
+	});
+	with_callee(GAS, report_gas);
+	let outcome = call();
+	assert!(outcome.is_success(), "{:?}", outcome);
+
+	// without the cap, asking for all the remaining gas runs out of it
+	setup(GasSchedule::named("kip"), || {
+		let all = Meter::new().remaining();
+		let _ = pwasm_ethereum::call(all, &Address::from(CALLEE), U256::zero(), &[], &mut []);
+	});
+	with_callee(GAS, report_gas);
+	assert_eq!(call(), Outcome::Trap(native::OUT_OF_GAS.into()));
+}
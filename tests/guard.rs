@@ -0,0 +1,39 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::{guard, native, Address, U256};
+
+#[test]
+fn entered_ret_releases_the_guard() {
+	native::reset();
+	let contract = Address::from([1u8; 20]);
+	native::with_externalities(|ext| ext.register(contract, || {
+		let entered = guard::ReentrancyGuard::new().enter().expect("guard is not entered");
+		entered.ret(b"done")
+	}));
+
+	// a guard left entered would make the second call trap
+	for _ in 0..2 {
+		let outcome = native::transact(contract, U256::zero(), Vec::new());
+		assert!(outcome.is_success(), "{:?}", outcome);
+		assert_eq!(outcome.data(), b"done");
+	}
+}
+
+#[test]
+fn reentrant_call_is_rejected() {
+	native::reset();
+	let contract = Address::from([2u8; 20]);
+	native::with_externalities(|ext| ext.register(contract, move || {
+		guard::non_reentrant(|| {
+			if pwasm_ethereum::input().is_empty() {
+				let reentered = pwasm_ethereum::call(100_000, &contract, U256::zero(), &[1], &mut []);
+				assert!(reentered.is_err());
+			}
+		})
+	}));
+
+	let outcome = native::transact(contract, U256::zero(), Vec::new());
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
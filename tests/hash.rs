@@ -0,0 +1,69 @@
+#![cfg(feature = "tiny-keccak")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::hash;
+use pwasm_ethereum::{Address, H256};
+
+fn bytes(hex: &str) -> Vec<u8> {
+	(0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}
+
+fn address(hex: &str) -> Address {
+	let mut address = [0u8; 20];
+	address.copy_from_slice(&bytes(&hex.to_lowercase()));
+	Address::from(address)
+}
+
+fn word(hex: &str) -> H256 {
+	let mut word = [0u8; 32];
+	word.copy_from_slice(&bytes(hex));
+	H256::from(word)
+}
+
+#[test]
+fn create_addresses() {
+	let sender = address("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0");
+	assert_eq!(hash::create_address(&sender, 0), address("cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d"));
+	assert_eq!(hash::create_address(&sender, 1), address("343c43a37d37dff08ae8c4a11544c718abb4fcf8"));
+	assert_eq!(hash::create_address(&sender, 2), address("f778b86fa74e846c4f0a1fbd1335fe81c00a0c91"));
+	assert_eq!(hash::create_address(&sender, 3), address("fffd933a0bc612844eaf0c6fe3e5b8e9b6c1d19c"));
+}
+
+#[test]
+fn create2_addresses() {
+	// examples of EIP-1014
+	let zero = "0000000000000000000000000000000000000000000000000000000000000000";
+	let cases: &[(&str, &str, &str, &str)] = &[
+		("0000000000000000000000000000000000000000", zero, "00", "4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"),
+		("deadbeef00000000000000000000000000000000", zero, "00", "B928f69Bb1D91Cd65274e3c79d8986362984fDA3"),
+		(
+			"deadbeef00000000000000000000000000000000",
+			"000000000000000000000000feed000000000000000000000000000000000000",
+			"00",
+			"D04116cDd17beBE565EB2422F2497E06cC1C9833",
+		),
+		("0000000000000000000000000000000000000000", zero, "deadbeef", "70f2b2914A2a4b783FaEFb75f459A580616Fcb5e"),
+		(
+			"00000000000000000000000000000000deadbeef",
+			"00000000000000000000000000000000000000000000000000000000cafebabe",
+			"deadbeef",
+			"60f3f640a8508fC6a86d45DF051962668E1e8AC7",
+		),
+		(
+			"00000000000000000000000000000000deadbeef",
+			"00000000000000000000000000000000000000000000000000000000cafebabe",
+			"deadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef",
+			"1d8bfDC5D46DC4f61D6b6115972536eBE6A8854C",
+		),
+		("0000000000000000000000000000000000000000", zero, "", "E33C0C7F7df4809055C3ebA6c09CFe4BaF1BD9e0"),
+	];
+	for &(sender, salt, init_code, expected) in cases {
+		let code_hash = hash::keccak256(&bytes(init_code));
+		assert_eq!(
+			hash::create2_address(&address(sender), &word(salt), &code_hash),
+			address(expected),
+			"init code {}", init_code
+		);
+	}
+}
@@ -0,0 +1,116 @@
+#![cfg(all(feature = "native", feature = "input-range"))]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native;
+use pwasm_ethereum::InputReader;
+
+/// Runs `f` with `input` as the input of the current call.
+fn with_input<F: FnOnce()>(input: Vec<u8>, f: F) {
+	native::reset();
+	native::with_externalities(|ext| ext.frame_mut().input = input);
+	let outcome = native::execute(f);
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+fn bytes(len: u8) -> Vec<u8> {
+	(0..len).collect()
+}
+
+#[test]
+fn reads_selector_and_words() {
+	with_input(bytes(100), || {
+		let mut reader = InputReader::new();
+		assert_eq!(reader.len(), 100);
+		assert_eq!(reader.selector(), Some([0, 1, 2, 3]));
+		assert_eq!(reader.position(), 0);
+
+		reader.seek(4).unwrap();
+		for start in [4u8, 36, 68].iter() {
+			let word = reader.read_word().unwrap();
+			assert_eq!(&word[..], &bytes(*start + 32)[*start as usize..]);
+		}
+		assert_eq!(reader.position(), 100);
+		assert_eq!(reader.remaining(), 0);
+	});
+}
+
+#[test]
+fn reads_past_the_end_fail_without_moving() {
+	with_input(bytes(100), || {
+		let mut reader = InputReader::new();
+		reader.seek(90).unwrap();
+		assert!(reader.read_word().is_err());
+		assert_eq!(reader.position(), 90);
+
+		let mut rest = [0u8; 11];
+		assert!(reader.read(&mut rest).is_err());
+		assert_eq!(reader.position(), 90);
+		reader.read(&mut rest[..10]).unwrap();
+		assert_eq!(&rest[..10], &bytes(100)[90..]);
+		assert_eq!(reader.remaining(), 0);
+
+		assert!(reader.read(&mut [0u8; 1]).is_err());
+		assert!(reader.read(&mut []).is_ok());
+	});
+}
+
+#[test]
+fn read_at_checks_the_whole_range() {
+	with_input(bytes(100), || {
+		let reader = InputReader::new();
+		let mut byte = [0u8; 1];
+		reader.read_at(99, &mut byte).unwrap();
+		assert_eq!(byte, [99]);
+		assert!(reader.read_at(100, &mut byte).is_err());
+		assert!(reader.read_at(100, &mut []).is_ok());
+		assert!(reader.read_at(101, &mut []).is_err());
+		assert!(reader.read_at(usize::max_value(), &mut byte).is_err());
+		assert!(reader.read_at(1, &mut [0u8; 100]).is_err());
+	});
+}
+
+#[test]
+fn seek_stops_at_the_end() {
+	with_input(bytes(10), || {
+		let mut reader = InputReader::new();
+		reader.seek(10).unwrap();
+		assert_eq!(reader.remaining(), 0);
+		assert!(reader.seek(11).is_err());
+		assert_eq!(reader.position(), 10);
+		reader.seek(0).unwrap();
+		assert_eq!(reader.remaining(), 10);
+	});
+}
+
+#[test]
+fn chunks_reassemble_the_input() {
+	for &chunk in [1usize, 7, 31, 32, 33, 100].iter() {
+		with_input(bytes(100), move || {
+			let mut reader = InputReader::new();
+			let mut read = Vec::new();
+			let mut buf = vec![0u8; chunk];
+			while reader.remaining() > 0 {
+				let len = chunk.min(reader.remaining());
+				reader.read(&mut buf[..len]).unwrap();
+				read.extend_from_slice(&buf[..len]);
+			}
+			assert_eq!(read, pwasm_ethereum::input());
+		});
+	}
+}
+
+#[test]
+fn short_inputs_have_no_selector() {
+	with_input(Vec::new(), || {
+		let reader = InputReader::new();
+		assert!(reader.is_empty());
+		assert_eq!(reader.selector(), None);
+	});
+	with_input(vec![1, 2, 3], || {
+		let mut reader = InputReader::default();
+		assert_eq!(reader.selector(), None);
+		assert!(reader.read_word().is_err());
+		assert_eq!(reader.position(), 0);
+	});
+}
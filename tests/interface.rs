@@ -0,0 +1,114 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::interface::{self, Version};
+use pwasm_ethereum::native::{self, Outcome};
+use pwasm_ethereum::{hash, test, Address, U256};
+
+const CONTRACT: [u8; 20] = [1u8; 20];
+const CALLER: [u8; 20] = [2u8; 20];
+const ACCOUNT: [u8; 20] = [3u8; 20];
+const TOKEN: [u8; 4] = [0x36, 0x37, 0x2b, 0x07];
+
+fn selector(signature: &str) -> [u8; 4] {
+	let hash = <[u8; 32]>::from(hash::keccak256(signature.as_bytes()));
+	[hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Deploys `CONTRACT` at version 1.2.0, implementing `TOKEN`, and `CALLER` running `f`.
+fn setup<F: Fn() + 'static>(f: F) {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.register(Address::from(CONTRACT), || interface::handle(&pwasm_ethereum::input()));
+		// the code is only looked at without EIP-211, to tell contracts from accounts
+		ext.accounts.get_mut(&Address::from(CONTRACT)).unwrap().code = vec![0];
+		ext.register(Address::from(CALLER), f);
+		ext.frame_mut().address = Address::from(CONTRACT);
+	});
+	let outcome = native::execute(|| {
+		interface::set_version(&Version::new(1, 2, 0));
+		interface::register_interface(TOKEN);
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+fn call() -> Outcome {
+	native::transact(Address::from(CALLER), U256::zero(), Vec::new())
+}
+
+fn expect_rejected(outcome: Outcome, reason: &str) {
+	if cfg!(feature = "eip140") {
+		test::expect_revert_reason(outcome, reason);
+	} else {
+		assert!(!outcome.is_success(), "{:?}", outcome);
+	}
+}
+
+#[test]
+fn selectors() {
+	assert_eq!(interface::SUPPORTS_INTERFACE, selector("supportsInterface(bytes4)"));
+	assert_eq!(interface::INTERFACE_VERSION, selector("interfaceVersion()"));
+}
+
+#[test]
+fn versions() {
+	let version = Version::new(1, 2, 3);
+	assert_eq!(Version::from_word(&version.to_word()), version);
+	assert_eq!(&version.to_word()[20..], &[0u8, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3][..]);
+
+	assert!(version.is_compatible(&Version::new(1, 0, 0)));
+	assert!(version.is_compatible(&Version::new(1, 2, 3)));
+	assert!(!version.is_compatible(&Version::new(1, 2, 4)));
+	assert!(!version.is_compatible(&Version::new(1, 3, 0)));
+	assert!(!version.is_compatible(&Version::new(0, 9, 0)));
+	assert!(!version.is_compatible(&Version::new(2, 0, 0)));
+}
+
+#[test]
+fn implements_queries_erc165() {
+	setup(|| {
+		assert!(interface::implements(&Address::from(CONTRACT), TOKEN));
+		assert!(interface::implements(&Address::from(CONTRACT), interface::SUPPORTS_INTERFACE));
+		assert!(!interface::implements(&Address::from(CONTRACT), [1, 2, 3, 4]));
+		assert!(!interface::implements(&Address::from(CONTRACT), interface::INVALID_INTERFACE));
+		assert!(!interface::implements(&Address::from(ACCOUNT), TOKEN));
+	});
+	let outcome = call();
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn version_of_contracts_only() {
+	setup(|| {
+		assert_eq!(interface::version_of(&Address::from(CONTRACT)).unwrap(), Version::new(1, 2, 0));
+		assert!(interface::version_of(&Address::from(ACCOUNT)).is_err());
+	});
+	let outcome = call();
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn require_compatible_accepts_compatible_versions() {
+	setup(|| {
+		let version = interface::require_compatible(&Address::from(CONTRACT), &Version::new(1, 1, 5));
+		assert_eq!(version, Version::new(1, 2, 0));
+	});
+	let outcome = call();
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn require_compatible_rejects_incompatible_versions() {
+	setup(|| { interface::require_compatible(&Address::from(CONTRACT), &Version::new(1, 3, 0)); });
+	expect_rejected(call(), "interface: incompatible version");
+
+	setup(|| { interface::require_compatible(&Address::from(CONTRACT), &Version::new(2, 0, 0)); });
+	expect_rejected(call(), "interface: incompatible version");
+}
+
+#[test]
+fn require_compatible_rejects_accounts_without_code() {
+	setup(|| { interface::require_compatible(&Address::from(ACCOUNT), &Version::new(0, 0, 0)); });
+	expect_rejected(call(), "interface: no version");
+}
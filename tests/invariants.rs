@@ -0,0 +1,90 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use std::panic::{self, AssertUnwindSafe};
+
+use pwasm_ethereum::native::{self, Externalities};
+use pwasm_ethereum::{Address, H256, U256};
+
+const TOKEN: [u8; 20] = [1u8; 20];
+const FAILING: [u8; 20] = [2u8; 20];
+
+fn slot(index: u8) -> H256 {
+	let mut key = [0u8; 32];
+	key[31] = index;
+	H256::from(key)
+}
+
+fn value(amount: u8) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	word[31] = amount;
+	word
+}
+
+fn balances_sum_to_supply(ext: &Externalities) -> bool {
+	let token = Address::from(TOKEN);
+	let a = ext.storage_at(&token, &slot(1))[31] as u32;
+	let b = ext.storage_at(&token, &slot(2))[31] as u32;
+	a + b == ext.storage_at(&token, &slot(0))[31] as u32
+}
+
+/// Token moving `input[0]` units from the first to the second balance; with `input[1]` set it
+/// debits first, calls a failing contract, then credits.
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| {
+		let token = Address::from(TOKEN);
+		ext.set_storage(token, slot(0), value(10));
+		ext.set_storage(token, slot(1), value(10));
+		ext.register(Address::from(FAILING), || panic!("failing"));
+		ext.register(token, || {
+			let input = pwasm_ethereum::input();
+			let amount = input[0];
+			let a = pwasm_ethereum::read(&slot(1))[31];
+			let b = pwasm_ethereum::read(&slot(2))[31];
+			pwasm_ethereum::write(&slot(1), &value(a - amount));
+			if input.get(1) == Some(&1) {
+				let _ = pwasm_ethereum::call(100_000, &Address::from(FAILING), U256::zero(), &[], &mut []);
+			}
+			if input.get(1) != Some(&2) {
+				pwasm_ethereum::write(&slot(2), &value(b + amount));
+			}
+		});
+		ext.add_invariant("balances sum to supply", balances_sum_to_supply);
+	});
+}
+
+fn transact(input: Vec<u8>) -> Result<native::Outcome, String> {
+	panic::catch_unwind(AssertUnwindSafe(|| native::transact(Address::from(TOKEN), U256::zero(), input)))
+		.map_err(|payload| payload.downcast::<String>().map(|message| *message).unwrap_or_default())
+}
+
+#[test]
+fn holding_invariant_passes() {
+	setup();
+	assert!(transact(vec![3]).unwrap().is_success());
+}
+
+#[test]
+fn violation_after_top_level_call_panics_with_trace() {
+	setup();
+	let message = transact(vec![3, 2]).unwrap_err();
+	assert!(message.starts_with("invariant `balances sum to supply` violated after:\n"), "{}", message);
+	assert!(message.contains("TRANSACT"), "{}", message);
+}
+
+#[test]
+fn violation_at_revert_boundary_panics() {
+	setup();
+	let message = transact(vec![3, 1]).unwrap_err();
+	assert!(message.starts_with("invariant `balances sum to supply` violated after:\n"), "{}", message);
+	assert!(message.contains("failing"), "{}", message);
+}
+
+#[test]
+fn cleared_invariants_are_not_checked() {
+	setup();
+	native::with_externalities(|ext| ext.clear_invariants());
+	assert!(transact(vec![3, 2]).unwrap().is_success());
+}
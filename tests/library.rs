@@ -0,0 +1,96 @@
+#![cfg(feature = "native")]
+
+#[macro_use]
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::library::Library;
+use pwasm_ethereum::{native, Address, H256, U256};
+
+const CONTRACT: [u8; 20] = [1u8; 20];
+const LIB: [u8; 20] = [2u8; 20];
+const ADD: [u8; 4] = [0x77, 0x16, 0x02, 0xf7];
+const LINK_SLOT: [u8; 32] = [0x11; 32];
+
+library! {
+	/// Arithmetic library.
+	pub struct SafeMath {
+		/// Sum of `a` and `b`, failing on overflow.
+		fn add(a, b) = ADD;
+	}
+}
+
+fn word(value: u64) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	U256::from(value).to_big_endian(&mut word);
+	word
+}
+
+/// `LIB` adds two words, storing the sum in slot 0 of the calling contract and failing on
+/// overflow or any other selector.
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.frame_mut().address = Address::from(CONTRACT);
+		ext.register(Address::from(LIB), || {
+			let input = pwasm_ethereum::input();
+			assert_eq!(&input[..4], &ADD[..]);
+			let (a, b) = (U256::from_big_endian(&input[4..36]), U256::from_big_endian(&input[36..68]));
+			let (sum, overflow) = a.overflowing_add(b);
+			assert!(!overflow, "overflow");
+			let mut result = [0u8; 32];
+			sum.to_big_endian(&mut result);
+			pwasm_ethereum::write(&H256::zero(), &result);
+			pwasm_ethereum::ret(&result)
+		});
+	});
+}
+
+fn execute<F: FnOnce()>(f: F) {
+	let outcome = native::execute(f);
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn stubs_delegate_to_the_library() {
+	setup();
+	execute(|| {
+		let math = SafeMath(Library::at(Address::from(LIB)).with_gas(100_000));
+		assert_eq!(math.add(word(2), word(3)), Ok(word(5)));
+		assert!(math.add([0xff; 32], word(1)).is_err());
+	});
+	native::with_externalities(|ext| {
+		// the library runs in the storage of the caller
+		assert_eq!(ext.storage_at(&Address::from(CONTRACT), &H256::zero()), word(5));
+		assert_eq!(ext.storage_at(&Address::from(LIB), &H256::zero()), [0u8; 32]);
+	});
+}
+
+#[test]
+fn libraries_are_linked_through_storage() {
+	setup();
+	execute(|| {
+		Library::at(Address::from(LIB)).link(&H256::from(LINK_SLOT));
+		let math = SafeMath::from_storage(&H256::from(LINK_SLOT));
+		assert_eq!(math.0.address(), Address::from(LIB));
+		assert_eq!(math, SafeMath::at(Address::from(LIB)));
+	});
+	native::with_externalities(|ext| {
+		let stored = ext.storage_at(&Address::from(CONTRACT), &H256::from(LINK_SLOT));
+		assert_eq!(&stored[12..], &LIB[..]);
+		assert_eq!(&stored[..12], &[0u8; 12][..]);
+	});
+}
+
+#[test]
+fn raw_calls_fill_the_result() {
+	setup();
+	execute(|| {
+		let mut input = ADD.to_vec();
+		input.extend_from_slice(&word(7));
+		input.extend_from_slice(&word(8));
+		let mut result = [0u8; 32];
+		Library::at(Address::from(LIB)).with_gas(100_000).call(&input, &mut result).unwrap();
+		assert_eq!(result, word(15));
+		assert_eq!(Library::at(Address::from(LIB)).with_gas(100_000).invoke(ADD, &[word(1), word(1)]), Ok(word(2)));
+	});
+}
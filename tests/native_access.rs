@@ -0,0 +1,82 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, GasSchedule};
+use pwasm_ethereum::{Address, H256, U256};
+
+const CONTRACT: [u8; 20] = [1u8; 20];
+const OTHER: [u8; 20] = [2u8; 20];
+const THIRD: [u8; 20] = [3u8; 20];
+
+/// Registers `entry` at `CONTRACT` and meters gas with the Berlin schedule.
+fn setup<F: Fn() + 'static>(entry: F) {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.gas_schedule = Some(GasSchedule::berlin());
+		ext.register(Address::from(CONTRACT), entry);
+		ext.register(Address::from(OTHER), || {
+			pwasm_ethereum::balance(&Address::from(THIRD));
+			panic!("callee fails");
+		});
+	});
+}
+
+/// Gas used by a top-level call to `CONTRACT`.
+fn gas_used() -> u64 {
+	let outcome = native::transact(Address::from(CONTRACT), U256::zero(), Vec::new());
+	assert!(outcome.is_success(), "{:?}", outcome);
+	native::with_externalities(|ext| ext.calls.last().unwrap().gas_used)
+}
+
+#[test]
+fn storage_slots_warm_up() {
+	setup(|| {
+		pwasm_ethereum::write(&H256::zero(), &[1u8; 32]);
+		pwasm_ethereum::read(&H256::zero());
+		pwasm_ethereum::write(&H256::zero(), &[2u8; 32]);
+		pwasm_ethereum::read(&H256::from([1u8; 32]));
+	});
+	assert_eq!(gas_used(), (20000 + 2100) + 100 + 2900 + 2100);
+	// slots are cold again in a new transaction
+	assert_eq!(gas_used(), (2900 + 2100) + 100 + 2900 + 2100);
+}
+
+#[test]
+fn accounts_warm_up() {
+	setup(|| {
+		pwasm_ethereum::balance(&Address::from(THIRD));
+		pwasm_ethereum::balance(&Address::from(THIRD));
+	});
+	assert_eq!(gas_used(), 2600 + 100);
+}
+
+#[test]
+fn sender_and_callee_start_warm() {
+	setup(|| {
+		pwasm_ethereum::balance(&Address::from(CONTRACT));
+		pwasm_ethereum::balance(&Address::zero());
+	});
+	assert_eq!(gas_used(), 100 + 100);
+}
+
+#[test]
+fn accesses_of_failed_calls_are_forgotten() {
+	setup(|| {
+		let other = Address::from(OTHER);
+		assert!(pwasm_ethereum::call(10_000, &other, U256::zero(), &[], &mut []).is_err());
+		pwasm_ethereum::balance(&Address::from(THIRD));
+	});
+	// the callee used all of its gas; `THIRD` is cold again once its call failed
+	assert_eq!(gas_used(), 2600 + 10_000 + 2600);
+}
+
+#[test]
+fn cold_and_warm_prices() {
+	let berlin = GasSchedule::berlin();
+	assert_eq!((berlin.sload_cost(true), berlin.sload_cost(false)), (2100, 100));
+	assert_eq!((berlin.sstore_cost(true, true), berlin.sstore_cost(true, false)), (22100, 20000));
+	assert_eq!((berlin.sstore_cost(false, true), berlin.sstore_cost(false, false)), (5000, 2900));
+	assert_eq!(berlin.account_access_cost(berlin.balance, true), 2600);
+	assert_eq!(berlin.account_access_cost(berlin.extcode, false), 100);
+}
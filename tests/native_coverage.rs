@@ -0,0 +1,56 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, coverage};
+use pwasm_ethereum::{Address, H256, U256};
+
+const CONTRACT: [u8; 20] = [1u8; 20];
+const TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+const APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+
+fn call(input: &[u8]) {
+	let outcome = native::transact(Address::from(CONTRACT), U256::zero(), input.to_vec());
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+// coverage is process-wide, so everything is checked from a single test
+#[test]
+fn records_externs_and_selectors() {
+	native::reset();
+	native::with_externalities(|ext| ext.register(Address::from(CONTRACT), || {
+		let input = pwasm_ethereum::input();
+		if input.len() >= 4 {
+			pwasm_ethereum::write(&H256::zero(), &[1u8; 32]);
+		}
+	}));
+	coverage::reset();
+
+	call(&TRANSFER);
+	call(&[TRANSFER[0], TRANSFER[1], TRANSFER[2], TRANSFER[3], 0xff]);
+	call(&[1, 2]);
+
+	let report = coverage::collect();
+	let contract = Address::from(CONTRACT);
+	assert_eq!(report.selectors[&contract][&TRANSFER], 2);
+	assert_eq!(report.selectors[&contract].len(), 1, "inputs shorter than a selector are not recorded");
+	assert!(report.is_selector_covered(&contract, TRANSFER));
+	assert_eq!(report.uncovered_selectors(&contract, &[TRANSFER, APPROVE]), vec![APPROVE]);
+	assert!(report.uncovered_selectors(&Address::zero(), &[TRANSFER]) == vec![TRANSFER]);
+
+	assert_eq!(report.externs["storage_write"], 2);
+	assert!(report.externs["input_length"] >= 3);
+	let unused = report.unused_externs();
+	assert!(unused.contains(&"create2"));
+	assert!(!unused.contains(&"storage_write"));
+	assert_eq!(unused.len() + report.externs.len(), coverage::EXTERNS.len());
+	assert!(report.to_string().contains("0xa9059cbb 2"));
+
+	coverage::set_enabled(false);
+	call(&APPROVE);
+	coverage::set_enabled(true);
+	assert_eq!(coverage::collect(), report);
+
+	coverage::reset();
+	assert_eq!(coverage::collect(), coverage::Coverage::default());
+}
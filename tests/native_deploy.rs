@@ -0,0 +1,108 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, deploy_contract, Code, Contract, Outcome};
+use pwasm_ethereum::{Address, H256, U256};
+
+const ORIGIN: [u8; 20] = [9u8; 20];
+const RUNTIME: &[u8] = b"counter runtime";
+
+/// Counter starting at its constructor argument, which fails on `0`, and incremented by every
+/// call.
+fn counter() -> Code {
+	Code::new(
+		|| {
+			let start = pwasm_ethereum::input();
+			if start == [0] {
+				panic!("zero start");
+			}
+			let mut value = [0u8; 32];
+			value[31] = start[0];
+			pwasm_ethereum::write(&H256::zero(), &value);
+			pwasm_ethereum::ret(RUNTIME)
+		},
+		|| {
+			let mut value = pwasm_ethereum::read(&H256::zero());
+			value[31] += 1;
+			pwasm_ethereum::write(&H256::zero(), &value);
+			pwasm_ethereum::ret(&value[31..])
+		},
+	)
+}
+
+/// Contract calling the counter whose address it is deployed with.
+fn caller() -> Code {
+	Code::new(
+		|| {
+			let mut slot = [0u8; 32];
+			slot[12..].copy_from_slice(&pwasm_ethereum::input());
+			pwasm_ethereum::write(&H256::zero(), &slot);
+		},
+		|| {
+			let target = Address::from(&pwasm_ethereum::read(&H256::zero())[12..]);
+			let mut result = [0u8; 1];
+			pwasm_ethereum::call(100_000, &target, U256::zero(), &[], &mut result).unwrap();
+			pwasm_ethereum::ret(&result)
+		},
+	)
+}
+
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.origin = Address::from(ORIGIN);
+		ext.set_balance(Address::from(ORIGIN), U256::from(100));
+	});
+}
+
+fn counter_value(contract: &Contract) -> u8 {
+	contract.storage_at(&H256::zero())[31]
+}
+
+#[test]
+fn deploys_runs_the_constructor_and_registers_the_runtime() {
+	setup();
+	let contract = deploy_contract(counter(), vec![5], U256::from(10)).unwrap();
+	assert_eq!(counter_value(&contract), 5);
+	assert_eq!(contract.balance(), U256::from(10));
+	native::with_externalities(|ext| {
+		assert_eq!(ext.code(&contract.address()), RUNTIME);
+		assert_eq!(ext.balance(&Address::from(ORIGIN)), U256::from(90));
+	});
+
+	assert_eq!(contract.call(Vec::new()), Outcome::Return(vec![6]));
+	assert_eq!(counter_value(&contract), 6);
+	assert_eq!(Contract::at(contract.address()), contract);
+}
+
+#[test]
+fn addresses_are_deterministic_and_distinct() {
+	setup();
+	let first = deploy_contract(counter(), vec![1], U256::zero()).unwrap();
+	let second = deploy_contract(counter(), vec![1], U256::zero()).unwrap();
+	assert!(first.address() != second.address());
+
+	setup();
+	assert_eq!(deploy_contract(counter(), vec![1], U256::zero()).unwrap(), first);
+}
+
+#[test]
+fn failed_constructors_return_their_outcome() {
+	setup();
+	match deploy_contract(counter(), vec![0], U256::from(10)) {
+		Err(Outcome::Trap(message)) => assert_eq!(message, "zero start"),
+		result => panic!("unexpected {:?}", result.map(|c| c.address())),
+	}
+	native::with_externalities(|ext| assert_eq!(ext.balance(&Address::from(ORIGIN)), U256::from(100)));
+}
+
+#[test]
+fn deployed_contracts_call_each_other() {
+	setup();
+	let counter = deploy_contract(counter(), vec![1], U256::zero()).unwrap();
+	let caller = deploy_contract(caller(), <[u8; 20]>::from(counter.address()).to_vec(), U256::zero()).unwrap();
+	assert_eq!(caller.call(Vec::new()), Outcome::Return(vec![2]));
+	assert_eq!(caller.call(Vec::new()), Outcome::Return(vec![3]));
+	assert_eq!(counter_value(&counter), 3);
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, GasSchedule, Outcome, OUT_OF_GAS};
+use pwasm_ethereum::{Address, H256, U256};
+
+const CONTRACT: [u8; 20] = [1u8; 20];
+const OTHER: [u8; 20] = [2u8; 20];
+
+/// Registers `entry` at `CONTRACT` and meters gas with `schedule`.
+fn setup<F: Fn() + 'static>(schedule: GasSchedule, entry: F) {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.gas_schedule = Some(schedule);
+		ext.register(Address::from(CONTRACT), entry);
+		ext.register(Address::from(OTHER), || {});
+	});
+}
+
+/// Gas used by a top-level call to `CONTRACT`.
+fn gas_used() -> u64 {
+	let outcome = native::transact(Address::from(CONTRACT), U256::zero(), Vec::new());
+	assert!(outcome.is_success(), "{:?}", outcome);
+	native::with_externalities(|ext| ext.calls.last().unwrap().gas_used)
+}
+
+fn store_and_load() {
+	pwasm_ethereum::write(&H256::zero(), &[1u8; 32]);
+	pwasm_ethereum::read(&H256::zero());
+	pwasm_ethereum::write(&H256::zero(), &[2u8; 32]);
+}
+
+#[test]
+fn storage_without_access_lists() {
+	setup(GasSchedule::frontier(), store_and_load);
+	assert_eq!(gas_used(), 20000 + 50 + 5000);
+	// the slot is no longer empty
+	assert_eq!(gas_used(), 5000 + 50 + 5000);
+}
+
+#[test]
+fn calls() {
+	setup(GasSchedule::byzantium(), || {
+		pwasm_ethereum::call(10_000, &Address::from(OTHER), U256::zero(), &[], &mut []).unwrap();
+	});
+	assert_eq!(gas_used(), 700);
+}
+
+#[test]
+fn logs() {
+	setup(GasSchedule::frontier(), || pwasm_ethereum::log(&[H256::zero(), H256::zero()], &[0u8; 10]));
+	assert_eq!(gas_used(), 375 + 2 * 375 + 10 * 8);
+}
+
+#[test]
+fn running_out_of_gas_traps_and_reverts() {
+	setup(GasSchedule::frontier(), store_and_load);
+	native::with_externalities(|ext| ext.frame_mut().gas = 20049);
+	let outcome = native::transact(Address::from(CONTRACT), U256::zero(), Vec::new());
+	assert_eq!(outcome, Outcome::Trap(OUT_OF_GAS.into()));
+	native::with_externalities(|ext| {
+		assert_eq!(ext.storage_at(&Address::from(CONTRACT), &H256::zero()), [0u8; 32]);
+		assert_eq!(ext.calls.last().unwrap().gas_used, 20049);
+	});
+}
+
+#[test]
+fn unmetered_without_schedule() {
+	native::reset();
+	native::with_externalities(|ext| ext.register(Address::from(CONTRACT), store_and_load));
+	assert_eq!(gas_used(), 0);
+}
+
+#[test]
+fn named_schedules() {
+	for name in &["frontier", "byzantium", "kip", "istanbul", "berlin"] {
+		assert_eq!(GasSchedule::named(name).unwrap().name, *name);
+	}
+	assert_eq!(GasSchedule::named("london"), None);
+	assert_eq!(GasSchedule::named("istanbul").unwrap().sload, 800);
+}
+
+#[test]
+fn forwarded_gas() {
+	let byzantium = GasSchedule::byzantium();
+	assert_eq!(byzantium.forwarded_gas(1000, 640), Some(630));
+	assert_eq!(byzantium.forwarded_gas(100, 640), Some(100));
+	let kip = GasSchedule::named("kip").unwrap();
+	assert_eq!(kip.forwarded_gas(1000, 640), None);
+	assert_eq!(kip.forwarded_gas(640, 640), Some(640));
+}
+
+#[test]
+fn flat_access_costs() {
+	let istanbul = GasSchedule::istanbul();
+	assert_eq!(istanbul.sload_cost(true), 800);
+	assert_eq!(istanbul.account_access_cost(istanbul.balance, true), 700);
+}
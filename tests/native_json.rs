@@ -0,0 +1,66 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, CallKind, CallTrace, Log, Outcome, StorageOp};
+use pwasm_ethereum::{Address, H256, U256};
+
+fn trace(outcome: Outcome) -> CallTrace {
+	CallTrace {
+		kind: CallKind::Call,
+		caller: Address::from([1u8; 20]),
+		target: Address::from([2u8; 20]),
+		value: U256::from(1000),
+		gas: 21000,
+		gas_used: 500,
+		input: vec![0xab],
+		outcome: outcome,
+		storage: Vec::new(),
+		logs: Vec::new(),
+		subcalls: Vec::new(),
+	}
+}
+
+fn hex(byte: &str, count: usize) -> String {
+	format!("\"0x{}\"", byte.repeat(count))
+}
+
+#[test]
+fn call_fields_in_stable_order() {
+	let mut call = trace(Outcome::Return(vec![1, 2]));
+	call.storage.push(StorageOp::Read { key: H256::zero(), value: [0u8; 32] });
+	call.logs.push(Log { address: Address::from([2u8; 20]), topics: vec![H256::from([3u8; 32])], data: vec![4] });
+	let expected = format!(
+		"{{\"kind\":\"CALL\",\"caller\":{},\"target\":{},\"value\":\"1000\",\"gas\":21000,\"gas_used\":500,\
+		 \"input\":\"0xab\",\"outcome\":{{\"type\":\"return\",\"data\":\"0x0102\"}},\
+		 \"storage\":[{{\"op\":\"read\",\"key\":{},\"value\":{}}}],\
+		 \"logs\":[{{\"address\":{},\"topics\":[{}],\"data\":\"0x04\"}}],\"subcalls\":[]}}",
+		hex("01", 20), hex("02", 20), hex("00", 32), hex("00", 32), hex("02", 20), hex("03", 32),
+	);
+	assert_eq!(call.to_json(), expected);
+}
+
+#[test]
+fn outcomes() {
+	let json = trace(Outcome::Revert(vec![9])).to_json();
+	assert!(json.contains("\"outcome\":{\"type\":\"revert\",\"data\":\"0x09\"}"), "{}", json);
+	let json = trace(Outcome::Suicide(Address::from([5u8; 20]))).to_json();
+	assert!(json.contains(&format!("\"outcome\":{{\"type\":\"suicide\",\"refund\":{}}}", hex("05", 20))), "{}", json);
+	let json = trace(Outcome::Trap("say \"no\"\n\u{1}".into())).to_json();
+	assert!(json.contains("\"outcome\":{\"type\":\"trap\",\"message\":\"say \\\"no\\\"\\n\\u0001\"}"), "{}", json);
+}
+
+#[test]
+fn recorded_calls_as_array() {
+	native::reset();
+	assert_eq!(native::to_json(&[]), "[]");
+	let target = Address::from([2u8; 20]);
+	native::with_externalities(|ext| ext.register(target, || pwasm_ethereum::write(&H256::zero(), &[1u8; 32])));
+	native::transact(target, U256::zero(), Vec::new());
+	native::transact(target, U256::zero(), Vec::new());
+	let json = native::with_externalities(|ext| native::to_json(&ext.calls));
+	assert!(json.starts_with("[{\"kind\":\"TRANSACT\""), "{}", json);
+	assert_eq!(json.matches("\"kind\":\"TRANSACT\"").count(), 2);
+	assert_eq!(json.matches("{\"op\":\"write\"").count(), 2);
+	assert!(json.ends_with("\"subcalls\":[]}]"), "{}", json);
+}
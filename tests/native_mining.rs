@@ -0,0 +1,87 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, AutoMine, Block, Outcome, BLOCKHASH_WINDOW};
+use pwasm_ethereum::{Address, H256, U256};
+
+const CONTRACT: [u8; 20] = [1u8; 20];
+
+/// Synthetic hash of mined block `number`.
+fn mined_hash(number: u64) -> H256 {
+	let mut hash = [0u8; 32];
+	hash[0] = 0xbb;
+	hash[24..].copy_from_slice(&[0, 0, 0, 0, 0, 0, (number >> 8) as u8, number as u8]);
+	H256::from(hash)
+}
+
+#[test]
+fn advance_keeps_a_256_block_window() {
+	let mut block = Block::default();
+	block.advance(300, 15);
+	assert_eq!((block.number, block.timestamp), (300, 4500));
+	assert_eq!(block.hashes.len() as u64, BLOCKHASH_WINDOW);
+	assert_eq!(block.hash(299), mined_hash(299));
+	assert_eq!(block.hash(300 - BLOCKHASH_WINDOW), mined_hash(300 - BLOCKHASH_WINDOW));
+	assert_eq!(block.hash(299 - BLOCKHASH_WINDOW), H256::zero());
+	assert_eq!(block.hash(300), H256::zero());
+}
+
+#[test]
+fn known_hashes_outside_the_window_are_hidden() {
+	let mut block = Block::default();
+	block.number = 1000;
+	block.hashes.insert(999, H256::from([1u8; 32]));
+	block.hashes.insert(100, H256::from([2u8; 32]));
+	assert_eq!(block.hash(999), H256::from([1u8; 32]));
+	assert_eq!(block.hash(100), H256::zero());
+}
+
+/// Returns the block number, the timestamp and the block number seen by a nested call to
+/// itself, as single bytes; the nested call (input `[1]`) returns its block number only.
+fn block_reporter() {
+	let number = pwasm_ethereum::block_number() as u8;
+	if pwasm_ethereum::input() == [1] {
+		pwasm_ethereum::ret(&[number]);
+	}
+	let mut nested = [0u8; 1];
+	pwasm_ethereum::call(100_000, &Address::from(CONTRACT), U256::zero(), &[1], &mut nested).unwrap();
+	pwasm_ethereum::ret(&[number, pwasm_ethereum::timestamp() as u8, nested[0]])
+}
+
+#[test]
+fn outermost_calls_mine_blocks() {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.auto_mine = Some(AutoMine { blocks: 2, seconds: 10 });
+		ext.register(Address::from(CONTRACT), block_reporter);
+	});
+	let call = || native::transact(Address::from(CONTRACT), U256::zero(), Vec::new());
+	assert_eq!(call(), Outcome::Return(vec![2, 20, 2]));
+	assert_eq!(call(), Outcome::Return(vec![4, 40, 4]));
+	native::with_externalities(|ext| {
+		assert_eq!(ext.block.hash(3), mined_hash(3));
+		assert_eq!(ext.block.hash(4), H256::zero());
+	});
+}
+
+#[test]
+fn contracts_see_the_window() {
+	native::reset();
+	native::with_externalities(|ext| ext.block.advance(260, 1));
+	let outcome = native::execute(|| {
+		assert_eq!(pwasm_ethereum::block_hash(259), mined_hash(259));
+		assert_eq!(pwasm_ethereum::block_hash(4), mined_hash(4));
+		assert_eq!(pwasm_ethereum::block_hash(3), H256::zero());
+		assert_eq!(pwasm_ethereum::block_hash(260), H256::zero());
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn no_mining_by_default() {
+	native::reset();
+	native::with_externalities(|ext| ext.register(Address::from(CONTRACT), block_reporter));
+	let outcome = native::transact(Address::from(CONTRACT), U256::zero(), Vec::new());
+	assert_eq!(outcome, Outcome::Return(vec![0, 0, 0]));
+}
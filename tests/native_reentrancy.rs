@@ -0,0 +1,82 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, Reentrancy, ReentrancyPolicy};
+use pwasm_ethereum::{Address, U256};
+
+const VAULT: [u8; 20] = [1u8; 20];
+const ATTACKER: [u8; 20] = [2u8; 20];
+
+/// `VAULT` calls `ATTACKER` on input `[1]`, inside a non-reentrant section unless
+/// `unguarded`; `ATTACKER` calls back `VAULT` with an empty input.
+fn setup(policy: ReentrancyPolicy, unguarded: bool) {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.reentrancy = policy;
+		ext.register(Address::from(VAULT), move || {
+			if pwasm_ethereum::input().is_empty() {
+				return;
+			}
+			let call = || pwasm_ethereum::call(10_000, &Address::from(ATTACKER), U256::zero(), &[], &mut []).unwrap();
+			if unguarded {
+				call()
+			} else {
+				native::non_reentrant(call)
+			}
+		});
+		ext.register(Address::from(ATTACKER), || {
+			let _ = pwasm_ethereum::call(10_000, &Address::from(VAULT), U256::zero(), &[], &mut []);
+		});
+	});
+}
+
+fn attack() {
+	let outcome = native::transact(Address::from(VAULT), U256::zero(), vec![1]);
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+fn reentrancies() -> Vec<Reentrancy> {
+	native::with_externalities(|ext| ext.reentrancies.clone())
+}
+
+#[test]
+fn flags_reentrant_calls_into_sections() {
+	setup(ReentrancyPolicy::Flag, false);
+	attack();
+	let expected = Reentrancy {
+		address: Address::from(VAULT),
+		path: vec![Address::from(VAULT), Address::from(ATTACKER), Address::from(VAULT)],
+	};
+	assert_eq!(reentrancies(), vec![expected.clone()]);
+	assert_eq!(expected.to_string(), format!("0x{} -> 0x{} -> 0x{}", "01".repeat(20), "02".repeat(20), "01".repeat(20)));
+}
+
+#[test]
+fn calls_outside_sections_are_not_flagged() {
+	setup(ReentrancyPolicy::Flag, true);
+	attack();
+	assert!(reentrancies().is_empty());
+}
+
+#[test]
+fn non_reentrant_accounts_are_guarded_on_every_call() {
+	setup(ReentrancyPolicy::Flag, true);
+	native::with_externalities(|ext| { ext.non_reentrant.insert(Address::from(VAULT)); });
+	attack();
+	assert_eq!(reentrancies().len(), 1);
+}
+
+#[test]
+fn ignored_by_default() {
+	setup(ReentrancyPolicy::default(), false);
+	attack();
+	assert!(reentrancies().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "reentrant call")]
+fn fail_policy_panics_after_the_transaction() {
+	setup(ReentrancyPolicy::Fail, false);
+	let _ = native::transact(Address::from(VAULT), U256::zero(), vec![1]);
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use pwasm_ethereum::native::{self, Log};
+use pwasm_ethereum::{Address, H256, U256};
+
+const ORACLE: [u8; 20] = [1u8; 20];
+const OTHER: [u8; 20] = [2u8; 20];
+const REQUEST: [u8; 32] = [0xaa; 32];
+const ANSWER: [u8; 32] = [0xbb; 32];
+
+/// Both contracts log their input under `REQUEST`, except `[0xff]` which `ORACLE` stores as
+/// the answer and logs under `ANSWER`, and `[0]` which logs then traps.
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| {
+		for &address in [ORACLE, OTHER].iter() {
+			ext.register(Address::from(address), || {
+				let input = pwasm_ethereum::input();
+				if input == [0xff] {
+					pwasm_ethereum::write(&H256::zero(), &[0xff; 32]);
+					pwasm_ethereum::log(&[H256::from(ANSWER)], &input);
+					return;
+				}
+				pwasm_ethereum::log(&[H256::from(REQUEST)], &input);
+				if input == [0] {
+					panic!("failed request");
+				}
+			});
+		}
+	});
+}
+
+fn call(address: [u8; 20], input: &[u8]) -> native::Outcome {
+	native::transact(Address::from(address), U256::zero(), input.to_vec())
+}
+
+/// Records the data of the logs `address` emits under `topic`.
+fn record(address: Option<[u8; 20]>, topic: Option<[u8; 32]>) -> Rc<RefCell<Vec<Vec<u8>>>> {
+	let seen = Rc::new(RefCell::new(Vec::new()));
+	let recorded = seen.clone();
+	native::with_externalities(|ext| ext.subscribe(
+		address.map(Address::from),
+		topic.map(H256::from),
+		move |log: &Log| recorded.borrow_mut().push(log.data.clone()),
+	));
+	seen
+}
+
+#[test]
+fn callbacks_filter_on_emitter_and_first_topic() {
+	setup();
+	let all = record(None, None);
+	let oracle = record(Some(ORACLE), None);
+	let requests = record(None, Some(REQUEST));
+	assert!(call(ORACLE, &[1]).is_success());
+	assert!(call(OTHER, &[2]).is_success());
+	assert!(call(ORACLE, &[0xff]).is_success());
+
+	assert_eq!(*all.borrow(), vec![vec![1], vec![2], vec![0xff]]);
+	assert_eq!(*oracle.borrow(), vec![vec![1], vec![0xff]]);
+	assert_eq!(*requests.borrow(), vec![vec![1], vec![2]]);
+}
+
+#[test]
+fn discarded_logs_are_not_notified() {
+	setup();
+	let all = record(None, None);
+	assert!(!call(ORACLE, &[0]).is_success());
+	assert!(all.borrow().is_empty());
+}
+
+#[test]
+fn callbacks_can_answer_with_transactions() {
+	setup();
+	native::with_externalities(|ext| ext.subscribe(Some(Address::from(ORACLE)), Some(H256::from(REQUEST)), |_: &Log| {
+		assert!(call(ORACLE, &[0xff]).is_success());
+	}));
+	assert!(call(ORACLE, &[1]).is_success());
+	native::with_externalities(|ext| {
+		assert_eq!(ext.storage_at(&Address::from(ORACLE), &H256::zero()), [0xff; 32]);
+		assert_eq!(ext.logs.len(), 2);
+	});
+}
+
+#[test]
+fn cleared_subscriptions_are_not_run() {
+	setup();
+	let all = record(None, None);
+	native::with_externalities(|ext| ext.clear_subscriptions());
+	assert!(call(ORACLE, &[1]).is_success());
+	assert!(all.borrow().is_empty());
+}
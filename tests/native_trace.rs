@@ -0,0 +1,84 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, CallKind, Outcome, StorageOp};
+use pwasm_ethereum::{Address, H256, U256};
+
+const A: [u8; 20] = [0xaa; 20];
+const B: [u8; 20] = [0xbb; 20];
+const C: [u8; 20] = [0xcc; 20];
+
+/// `A` calls `B` then `C`; `B` calls `C`, and `C` stores a word and fails on input `[0]`.
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.register(Address::from(A), || {
+			pwasm_ethereum::call(10_000, &Address::from(B), U256::zero(), &[1], &mut []).unwrap();
+			let _ = pwasm_ethereum::call(10_000, &Address::from(C), U256::zero(), &[0], &mut []);
+			pwasm_ethereum::ret(&[7])
+		});
+		ext.register(Address::from(B), || {
+			pwasm_ethereum::call(10_000, &Address::from(C), U256::zero(), &[2], &mut []).unwrap();
+		});
+		ext.register(Address::from(C), || {
+			pwasm_ethereum::write(&H256::zero(), &[1u8; 32]);
+			if pwasm_ethereum::input() == [0] {
+				panic!("bad input");
+			}
+		});
+	});
+}
+
+#[test]
+fn tree_shape() {
+	setup();
+	let outcome = native::transact(Address::from(A), U256::zero(), vec![9]);
+	assert_eq!(outcome, Outcome::Return(vec![7]));
+
+	native::with_externalities(|ext| {
+		assert_eq!(ext.calls.len(), 1);
+		let root = &ext.calls[0];
+		assert_eq!((root.kind, root.target, root.input.clone()), (CallKind::Transact, Address::from(A), vec![9]));
+		assert_eq!(root.call_count(), 4);
+		assert_eq!(root.depth(), 3);
+
+		let targets: Vec<Address> = root.iter().map(|call| call.target).collect();
+		assert_eq!(targets, vec![Address::from(A), Address::from(B), Address::from(C), Address::from(C)]);
+
+		let b = &root.subcalls[0];
+		assert_eq!((b.kind, b.caller, b.gas), (CallKind::Call, Address::from(A), 10_000));
+		assert_eq!(b.subcalls[0].caller, Address::from(B));
+
+		let failed = root.find(|call| !call.outcome.is_success()).unwrap();
+		assert_eq!(failed.outcome, Outcome::Trap("bad input".into()));
+		assert_eq!(failed.input, vec![0]);
+		assert_eq!(failed.storage, vec![StorageOp::Write { key: H256::zero(), value: [1u8; 32] }]);
+	});
+}
+
+#[test]
+fn one_tree_per_top_level_call() {
+	setup();
+	native::transact(Address::from(B), U256::zero(), Vec::new());
+	native::transact(Address::from(C), U256::zero(), vec![3]);
+	native::with_externalities(|ext| {
+		let counts: Vec<usize> = ext.calls.iter().map(|call| call.call_count()).collect();
+		assert_eq!(counts, vec![2, 1]);
+	});
+}
+
+#[test]
+fn pretty_print() {
+	setup();
+	native::transact(Address::from(B), U256::zero(), vec![1]);
+	let printed = native::with_externalities(|ext| ext.calls[0].to_string());
+	let expected = format!(
+		"TRANSACT 0x{zero} -> 0x{b} value=0 gas=8000000 gas_used=0 input=0x01 => RETURN 0x\n  \
+		 CALL 0x{b} -> 0x{c} value=0 gas=10000 gas_used=0 input=0x02 => RETURN 0x\n",
+		zero = "00".repeat(20),
+		b = "bb".repeat(20),
+		c = "cc".repeat(20),
+	);
+	assert_eq!(printed, expected);
+}
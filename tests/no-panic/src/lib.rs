@@ -0,0 +1,31 @@
+//! Minimal contract built by `tests/no_panic.rs`, using the panic-free API only.
+
+#![no_std]
+
+extern crate pwasm_std;
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::{breaker::CircuitBreaker, forward::forward, guard::non_reentrant, Address, H256};
+
+#[no_mangle]
+pub fn call() {
+	CircuitBreaker::new([0xbb; 31]).require_not_tripped();
+
+	let input = pwasm_ethereum::input();
+	if input.first() == Some(&0xff) {
+		forward(&Address::from([0x11; 20]));
+	}
+
+	let counter = non_reentrant(|| {
+		let key = H256::zero();
+		let mut counter = pwasm_ethereum::read(&key);
+		counter[31] = counter[31].wrapping_add(1);
+		pwasm_ethereum::write(&key, &counter);
+		counter
+	});
+	let _ = pwasm_ethereum::try_log(&[H256::from(counter)], &input);
+
+	let parent = pwasm_ethereum::block_number().saturating_sub(1);
+	let hash = pwasm_ethereum::try_block_hash(parent).unwrap_or_else(H256::zero);
+	pwasm_ethereum::ret(&<[u8; 32]>::from(hash));
+}
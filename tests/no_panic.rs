@@ -0,0 +1,42 @@
+//! Checks that a minimal contract compiles to wasm without the panic formatting machinery.
+//!
+//! Builds the contract in `tests/no-panic` for `wasm32-unknown-unknown`, which must be
+//! installed: `cargo test --test no_panic -- --ignored`.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Symbols and messages only present when panic messages are formatted.
+const PANIC_FORMAT_MARKERS: &[&str] = &[
+	"4core3fmt",
+	"4core9panicking",
+	"panicked at",
+	"index out of bounds",
+	"called `Option::unwrap()`",
+	"called `Result::unwrap()`",
+];
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+	haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[test]
+#[ignore]
+fn minimal_contract_has_no_panic_formatting() {
+	let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+	let target_dir = root.join("target").join("no-panic");
+	let status = Command::new("cargo")
+		.args(&["build", "--release", "--target", "wasm32-unknown-unknown"])
+		.arg("--manifest-path").arg(root.join("tests").join("no-panic").join("Cargo.toml"))
+		.arg("--target-dir").arg(&target_dir)
+		.status()
+		.expect("cargo can be run");
+	assert!(status.success(), "building the contract failed");
+
+	let wasm = fs::read(target_dir.join("wasm32-unknown-unknown/release/pwasm_ethereum_no_panic.wasm"))
+		.expect("the contract is built");
+	for marker in PANIC_FORMAT_MARKERS {
+		assert!(!contains(&wasm, marker.as_bytes()), "compiled contract contains `{}`", marker);
+	}
+}
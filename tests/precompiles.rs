@@ -0,0 +1,194 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::{native, precompiles, Address, H256, U256};
+
+/// Registers `entry` as the precompile at `index`.
+fn mock<F: Fn() + 'static>(index: u8, entry: F) {
+	native::with_externalities(|ext| ext.register(precompiles::address(index), entry));
+}
+
+fn rejecting(index: u8) {
+	mock(index, || panic!("invalid input"));
+}
+
+#[test]
+fn rejected_inputs_are_errors() {
+	native::reset();
+	for &index in &[
+		precompiles::ECRECOVER, precompiles::SHA256, precompiles::RIPEMD160, precompiles::MODEXP,
+		precompiles::BN128_ADD, precompiles::BN128_MUL, precompiles::BN128_PAIRING, precompiles::BLAKE2F,
+	] {
+		rejecting(index);
+	}
+	let outcome = native::execute(|| {
+		let zero = H256::zero();
+		assert_eq!(precompiles::ecrecover(&zero, 27, &zero, &zero), None);
+		assert!(precompiles::sha256(b"abc").is_err());
+		assert!(precompiles::ripemd160(b"abc").is_err());
+		assert!(precompiles::modexp(&[2], &[10], &[3]).is_err());
+		assert!(precompiles::bn128_add(&[0u8; 64], &[0u8; 64]).is_err());
+		assert!(precompiles::bn128_mul(&[0u8; 64], &U256::one()).is_err());
+		assert!(precompiles::bn128_pairing(&[[0u8; 192]]).is_err());
+		assert!(precompiles::blake2f(12, &[0u8; 64], &[0u8; 128], &[0u8; 16], true).is_err());
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn zero_signer_is_no_signer() {
+	native::reset();
+	mock(precompiles::ECRECOVER, || pwasm_ethereum::ret(&[0u8; 32]));
+	let outcome = native::execute(|| {
+		let zero = H256::zero();
+		assert_eq!(precompiles::ecrecover(&zero, 27, &zero, &zero), None);
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn missing_precompiles_return_zeros() {
+	native::reset();
+	let outcome = native::execute(|| {
+		assert_eq!(precompiles::sha256(b"abc").unwrap(), H256::zero());
+		assert_eq!(precompiles::bn128_pairing(&[]).unwrap(), false);
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn inputs_are_laid_out_for_the_runtime() {
+	native::reset();
+	// each mock echoes the part of its input the test checks
+	mock(precompiles::MODEXP, || pwasm_ethereum::ret(&pwasm_ethereum::input()[..96]));
+	mock(precompiles::BLAKE2F, || {
+		let input = pwasm_ethereum::input();
+		assert_eq!(input.len(), 213);
+		assert_eq!(&input[..4], &[0, 0, 0, 12]);
+		assert_eq!(input[212], 1);
+		pwasm_ethereum::ret(&input[4..68])
+	});
+	mock(precompiles::ECRECOVER, || {
+		let input = pwasm_ethereum::input();
+		assert_eq!(input[63], 28);
+		let mut signer = [0u8; 32];
+		signer[12..].copy_from_slice(&input[96..116]);
+		pwasm_ethereum::ret(&signer)
+	});
+	let outcome = native::execute(|| {
+		let lengths = precompiles::modexp(&[0u8; 96], &[1], &[0u8; 96]).unwrap();
+		assert_eq!((lengths[31], lengths[63], lengths[95]), (96, 1, 96));
+		let h = [7u8; 64];
+		assert_eq!(&precompiles::blake2f(12, &h, &[0u8; 128], &[0u8; 16], true).unwrap()[..], &h[..]);
+		let s = H256::from([5u8; 32]);
+		let signer = precompiles::ecrecover(&H256::zero(), 28, &H256::zero(), &s);
+		assert_eq!(signer, Some(Address::from([5u8; 20])));
+	});
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[cfg(feature = "mock-precompiles")]
+mod software {
+	use pwasm_ethereum::{native, precompiles, wallet, H256, U256};
+
+	fn install() {
+		native::reset();
+		native::with_externalities(|ext| ext.register_precompiles());
+	}
+
+	fn hex(value: &str) -> Vec<u8> {
+		(0..value.len()).step_by(2).map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap()).collect()
+	}
+
+	fn point(x: &str, y: &str) -> [u8; 64] {
+		let mut point = [0u8; 64];
+		point[..32].copy_from_slice(&hex(x));
+		point[32..].copy_from_slice(&hex(y));
+		point
+	}
+
+	fn generator() -> [u8; 64] {
+		let mut point = [0u8; 64];
+		point[31] = 1;
+		point[63] = 2;
+		point
+	}
+
+	fn doubled_generator() -> [u8; 64] {
+		point(
+			"030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3",
+			"15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4",
+		)
+	}
+
+	#[test]
+	fn sha256_and_ripemd160() {
+		install();
+		let outcome = native::execute(|| {
+			assert_eq!(
+				precompiles::sha256(b"abc").unwrap().to_vec(),
+				hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+			);
+			assert_eq!(
+				precompiles::ripemd160(b"abc").unwrap().to_vec(),
+				hex("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc")
+			);
+		});
+		assert!(outcome.is_success(), "{:?}", outcome);
+	}
+
+	#[test]
+	fn modexp_small_numbers() {
+		install();
+		let outcome = native::execute(|| {
+			assert_eq!(precompiles::modexp(&[2], &[10], &[0x03, 0xe8]).unwrap(), vec![0x00, 0x18]);
+			assert_eq!(precompiles::modexp(&[2], &[10], &[0, 0]).unwrap(), vec![0, 0]);
+		});
+		assert!(outcome.is_success(), "{:?}", outcome);
+	}
+
+	#[test]
+	fn ecrecover_signed_hash() {
+		install();
+		let account = &wallet::accounts(1)[0];
+		let hash = H256::from([7u8; 32]);
+		let signature = account.sign(&hash);
+		let outcome = native::execute(|| {
+			let recovered = precompiles::ecrecover(&hash, signature.v, &signature.r, &signature.s);
+			assert_eq!(recovered, Some(account.address()));
+			assert_eq!(precompiles::ecrecover(&hash, 29, &signature.r, &signature.s), None);
+		});
+		assert!(outcome.is_success(), "{:?}", outcome);
+	}
+
+	#[test]
+	fn bn128_add_and_mul() {
+		install();
+		let outcome = native::execute(|| {
+			assert_eq!(&precompiles::bn128_add(&generator(), &generator()).unwrap()[..], &doubled_generator()[..]);
+			assert_eq!(&precompiles::bn128_mul(&generator(), &U256::from(2)).unwrap()[..], &doubled_generator()[..]);
+		});
+		assert!(outcome.is_success(), "{:?}", outcome);
+	}
+
+	#[test]
+	fn bn128_rejects_points_off_the_curve() {
+		install();
+		let outcome = native::execute(|| {
+			let mut invalid = generator();
+			invalid[63] = 3;
+			assert!(precompiles::bn128_add(&invalid, &generator()).is_err());
+		});
+		assert!(outcome.is_success(), "{:?}", outcome);
+	}
+
+	#[test]
+	fn bn128_pairing_of_nothing_holds() {
+		install();
+		let outcome = native::execute(|| {
+			assert_eq!(precompiles::bn128_pairing(&[]), Ok(true));
+		});
+		assert!(outcome.is_success(), "{:?}", outcome);
+	}
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native;
+use pwasm_ethereum::{hash, proxy, Address, H256, U256};
+
+const PROXY: [u8; 20] = [1u8; 20];
+const IMPLEMENTATION: [u8; 20] = [2u8; 20];
+const ADMIN: [u8; 20] = [3u8; 20];
+const USER: [u8; 20] = [4u8; 20];
+
+fn word(address: &[u8; 20]) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	word[12..].copy_from_slice(address);
+	word
+}
+
+fn hash_minus_one(data: &[u8]) -> [u8; 32] {
+	let hash = U256::from_big_endian(&<[u8; 32]>::from(hash::keccak256(data)));
+	let mut word = [0u8; 32];
+	(hash - U256::one()).to_big_endian(&mut word);
+	word
+}
+
+/// Runs `f` as the code of the proxy.
+fn in_proxy<F: FnOnce()>(f: F) {
+	native::with_externalities(|ext| ext.frame_mut().address = Address::from(PROXY));
+	let outcome = native::execute(f);
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn slots_and_topics_match_eip1967() {
+	assert_eq!(proxy::IMPLEMENTATION_SLOT, hash_minus_one(b"eip1967.proxy.implementation"));
+	assert_eq!(proxy::ADMIN_SLOT, hash_minus_one(b"eip1967.proxy.admin"));
+	assert_eq!(H256::from(proxy::UPGRADED_TOPIC), hash::keccak256(b"Upgraded(address)"));
+	assert_eq!(H256::from(proxy::ADMIN_CHANGED_TOPIC), hash::keccak256(b"AdminChanged(address,address)"));
+}
+
+#[test]
+fn implementation_is_stored_in_its_slot() {
+	native::reset();
+	in_proxy(|| assert_eq!(proxy::implementation(), Address::zero()));
+	in_proxy(|| proxy::set_implementation(&Address::from(IMPLEMENTATION)));
+	in_proxy(|| assert_eq!(proxy::implementation(), Address::from(IMPLEMENTATION)));
+
+	native::with_externalities(|ext| {
+		let stored = ext.storage_at(&Address::from(PROXY), &H256::from(proxy::IMPLEMENTATION_SLOT));
+		assert_eq!(stored, word(&IMPLEMENTATION));
+		assert_eq!(ext.logs.len(), 1);
+		assert_eq!(ext.logs[0].topics, vec![H256::from(proxy::UPGRADED_TOPIC), H256::from(word(&IMPLEMENTATION))]);
+		assert!(ext.logs[0].data.is_empty());
+	});
+}
+
+#[test]
+fn admin_is_stored_in_its_slot() {
+	native::reset();
+	in_proxy(|| proxy::set_admin(&Address::from(ADMIN)));
+	in_proxy(|| proxy::set_admin(&Address::from(USER)));
+	in_proxy(|| assert_eq!(proxy::admin(), Address::from(USER)));
+
+	native::with_externalities(|ext| {
+		let stored = ext.storage_at(&Address::from(PROXY), &H256::from(proxy::ADMIN_SLOT));
+		assert_eq!(stored, word(&USER));
+		let changes: Vec<Vec<u8>> = ext.logs.iter().map(|log| log.data.clone()).collect();
+		assert_eq!(changes, vec![
+			[[0u8; 32], word(&ADMIN)].concat(),
+			[word(&ADMIN), word(&USER)].concat(),
+		]);
+		assert!(ext.logs.iter().all(|log| log.topics == vec![H256::from(proxy::ADMIN_CHANGED_TOPIC)]));
+	});
+}
+
+#[cfg(all(feature = "eip211", feature = "kip6"))]
+fn deploy_proxy<F: Fn() + 'static>(implementation: F) {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.origin = Address::from(USER);
+		ext.set_balance(Address::from(USER), U256::from(1_000));
+		ext.register(Address::from(PROXY), || proxy::delegate_to(&proxy::implementation()));
+		ext.register(Address::from(IMPLEMENTATION), implementation);
+	});
+	in_proxy(|| proxy::set_implementation(&Address::from(IMPLEMENTATION)));
+}
+
+#[cfg(all(feature = "eip211", feature = "kip6"))]
+#[test]
+fn delegate_to_runs_the_implementation_in_the_proxy_context() {
+	deploy_proxy(|| {
+		pwasm_ethereum::write(&H256::zero(), &[7u8; 32]);
+		let mut output = Vec::new();
+		output.extend_from_slice(&<[u8; 20]>::from(pwasm_ethereum::address()));
+		output.extend_from_slice(&<[u8; 20]>::from(pwasm_ethereum::sender()));
+		output.extend_from_slice(&[pwasm_ethereum::value().low_u64() as u8]);
+		output.extend_from_slice(&pwasm_ethereum::input());
+		pwasm_ethereum::ret(&output)
+	});
+
+	let outcome = native::transact(Address::from(PROXY), U256::from(5), b"hello".to_vec());
+	let expected = [&PROXY[..], &USER[..], &[5u8][..], &b"hello"[..]].concat();
+	assert_eq!(outcome, native::Outcome::Return(expected));
+	native::with_externalities(|ext| {
+		assert_eq!(ext.storage_at(&Address::from(PROXY), &H256::zero()), [7u8; 32]);
+		assert_eq!(ext.storage_at(&Address::from(IMPLEMENTATION), &H256::zero()), [0u8; 32]);
+		assert_eq!(ext.balance(&Address::from(PROXY)), U256::from(5));
+	});
+}
+
+#[cfg(all(feature = "eip211", feature = "kip6", feature = "eip140"))]
+#[test]
+fn delegate_to_passes_reverts_through() {
+	deploy_proxy(|| {
+		pwasm_ethereum::write(&H256::zero(), &[7u8; 32]);
+		pwasm_ethereum::revert(b"not allowed")
+	});
+
+	let outcome = native::transact(Address::from(PROXY), U256::zero(), Vec::new());
+	assert_eq!(outcome, native::Outcome::Revert(b"not allowed".to_vec()));
+	native::with_externalities(|ext| {
+		assert_eq!(ext.storage_at(&Address::from(PROXY), &H256::zero()), [0u8; 32]);
+	});
+}
+
+#[cfg(all(feature = "eip211", feature = "kip6"))]
+#[test]
+fn delegate_to_traps_on_failed_implementations() {
+	deploy_proxy(|| panic!("broken implementation"));
+
+	let outcome = native::transact(Address::from(PROXY), U256::zero(), Vec::new());
+	assert_eq!(outcome, native::Outcome::Trap("trap".into()));
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, Outcome};
+use pwasm_ethereum::registry::{self, Resolver};
+use pwasm_ethereum::{test, Address, H256, U256};
+
+const REGISTRY: [u8; 20] = [1u8; 20];
+const ADMIN: [u8; 20] = [2u8; 20];
+const OTHER: [u8; 20] = [3u8; 20];
+
+fn word(address: &Address) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	word[12..].copy_from_slice(&<[u8; 20]>::from(*address));
+	word
+}
+
+const NAME: [u8; 32] = [0x11; 32];
+
+fn name() -> H256 {
+	H256::from(NAME)
+}
+
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.origin = Address::from(ADMIN);
+		ext.register(Address::from(REGISTRY), || registry::dispatch(&pwasm_ethereum::input()));
+	});
+	native::with_externalities(|ext| ext.frame_mut().address = Address::from(REGISTRY));
+	assert!(native::execute(|| registry::init(&Address::from(ADMIN))).is_success());
+	native::with_externalities(|ext| ext.frame_mut().address = Address::zero());
+}
+
+fn call(from: [u8; 20], selector: [u8; 4], args: &[[u8; 32]]) -> Outcome {
+	native::with_externalities(|ext| ext.origin = Address::from(from));
+	let mut input = selector.to_vec();
+	for arg in args {
+		input.extend_from_slice(arg);
+	}
+	native::transact(Address::from(REGISTRY), U256::zero(), input)
+}
+
+fn expect_rejected(outcome: Outcome, reason: &str) {
+	if cfg!(feature = "eip140") {
+		test::expect_revert_reason(outcome, reason);
+	} else {
+		assert!(!outcome.is_success(), "{:?}", outcome);
+	}
+}
+
+#[test]
+fn admin_registers_and_anyone_resolves() {
+	setup();
+	let target = Address::from([9u8; 20]);
+	assert!(call(ADMIN, registry::REGISTER, &[NAME, word(&target)]).is_success());
+	assert_eq!(call(OTHER, registry::RESOLVE, &[NAME]), Outcome::Return(word(&target).to_vec()));
+
+	native::with_externalities(|ext| {
+		let log = ext.logs.last().unwrap();
+		assert_eq!(log.topics, vec![H256::from(registry::REGISTERED_TOPIC), name()]);
+		assert_eq!(log.data, word(&target).to_vec());
+	});
+}
+
+#[test]
+fn only_the_admin_registers() {
+	setup();
+	let target = Address::from([9u8; 20]);
+	expect_rejected(call(OTHER, registry::REGISTER, &[NAME, word(&target)]), "registry: sender is not the admin");
+	assert_eq!(call(OTHER, registry::RESOLVE, &[NAME]), Outcome::Return(vec![0u8; 32]));
+}
+
+#[test]
+fn admin_hands_over() {
+	setup();
+	expect_rejected(call(OTHER, registry::SET_ADMIN, &[word(&Address::from(OTHER))]), "registry: sender is not the admin");
+	assert!(call(ADMIN, registry::SET_ADMIN, &[word(&Address::from(OTHER))]).is_success());
+	let target = Address::from([9u8; 20]);
+	expect_rejected(call(ADMIN, registry::REGISTER, &[NAME, word(&target)]), "registry: sender is not the admin");
+	assert!(call(OTHER, registry::REGISTER, &[NAME, word(&target)]).is_success());
+}
+
+#[test]
+fn malformed_calls_revert() {
+	setup();
+	expect_rejected(call(ADMIN, [0u8; 4], &[]), "registry: unknown selector");
+	expect_rejected(call(ADMIN, registry::REGISTER, &[NAME]), "registry: missing argument");
+	native::with_externalities(|ext| ext.origin = Address::from(ADMIN));
+	expect_rejected(native::transact(Address::from(REGISTRY), U256::zero(), vec![1, 2]), "registry: missing selector");
+}
+
+#[test]
+fn dirty_address_words_revert() {
+	setup();
+	let mut dirty = word(&Address::from([9u8; 20]));
+	dirty[0] = 1;
+	expect_rejected(call(ADMIN, registry::REGISTER, &[NAME, dirty]), "registry: bad address");
+	expect_rejected(call(ADMIN, registry::SET_ADMIN, &[dirty]), "registry: bad address");
+	assert_eq!(call(OTHER, registry::RESOLVE, &[NAME]), Outcome::Return(vec![0u8; 32]));
+	native::with_externalities(|ext| {
+		let admin = ext.storage_at(&Address::from(REGISTRY), &H256::from([0xff; 32]));
+		assert_eq!(admin, word(&Address::from(ADMIN)));
+	});
+}
+
+#[test]
+fn resolver_caches_entries() {
+	setup();
+	let target = Address::from([9u8; 20]);
+	assert!(call(ADMIN, registry::REGISTER, &[NAME, word(&target)]).is_success());
+	let outcome = native::execute(|| {
+		let mut resolver = Resolver::new(Address::from(REGISTRY)).with_gas(100_000);
+		assert_eq!(resolver.resolve(&name()).unwrap(), target);
+		assert!(resolver.resolve(&H256::zero()).is_err());
+	});
+	assert!(outcome.is_success());
+	native::with_externalities(|ext| ext.calls.clear());
+
+	let outcome = native::execute(|| {
+		let mut resolver = Resolver::new(Address::from(REGISTRY)).with_gas(100_000);
+		resolver.resolve(&name()).unwrap();
+		resolver.resolve(&name()).unwrap();
+	});
+	assert!(outcome.is_success());
+	native::with_externalities(|ext| assert_eq!(ext.calls[0].subcalls.len(), 1));
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, Outcome};
+#[cfg(feature = "kip6")]
+use pwasm_ethereum::native::GasSchedule;
+use pwasm_ethereum::{test, Address, CallError, H256, U256};
+
+const CONTRACT: [u8; 20] = [1u8; 20];
+const CALLER: [u8; 20] = [2u8; 20];
+const USER: [u8; 20] = [3u8; 20];
+
+/// `CONTRACT` writes storage and logs, then reverts with its input.
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.origin = Address::from(USER);
+		ext.set_balance(Address::from(USER), U256::from(100));
+		ext.register(Address::from(CONTRACT), || {
+			pwasm_ethereum::write(&H256::zero(), &[1u8; 32]);
+			pwasm_ethereum::log(&[], b"discarded");
+			pwasm_ethereum::revert(&pwasm_ethereum::input())
+		});
+	});
+}
+
+#[test]
+fn revert_discards_state_changes() {
+	setup();
+	let outcome = native::transact(Address::from(CONTRACT), U256::from(10), b"denied".to_vec());
+	if cfg!(feature = "eip140") {
+		assert_eq!(outcome, Outcome::Revert(b"denied".to_vec()));
+	} else {
+		assert_eq!(outcome, Outcome::Trap("trap".into()));
+	}
+	native::with_externalities(|ext| {
+		assert_eq!(ext.storage_at(&Address::from(CONTRACT), &H256::zero()), [0u8; 32]);
+		assert!(ext.logs.is_empty());
+		assert_eq!(ext.balance(&Address::from(USER)), U256::from(100));
+		assert_eq!(ext.balance(&Address::from(CONTRACT)), U256::zero());
+	});
+}
+
+#[cfg(feature = "kip6")]
+#[test]
+fn revert_refunds_the_remaining_gas() {
+	const GAS: u64 = 50_000;
+	setup();
+	native::with_externalities(|ext| {
+		// the write of the contract is priced by the schedule but fits the given gas
+		ext.gas_schedule = Some(GasSchedule::frontier());
+		ext.register(Address::from(CALLER), || {
+			let before = pwasm_ethereum::gas_left();
+			let result = pwasm_ethereum::call(GAS, &Address::from(CONTRACT), U256::zero(), &[], &mut []);
+			let spent = before - pwasm_ethereum::gas_left();
+			assert!(result.is_err());
+			if cfg!(feature = "eip140") {
+				assert!(spent < 25_000, "spent {}", spent);
+			} else {
+				assert!(spent >= GAS, "spent {}", spent);
+			}
+		});
+	});
+	let outcome = native::transact(Address::from(CALLER), U256::zero(), Vec::new());
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[test]
+fn revert_with_reason_encodes_an_error_string() {
+	const REASON: &str = "a reason longer than a single word";
+	native::reset();
+	let outcome = native::execute(|| pwasm_ethereum::revert_with_reason(REASON));
+	if cfg!(feature = "eip140") {
+		let data = test::expect_revert(outcome.clone());
+		assert_eq!(data.len(), 4 + 32 + 32 + 64);
+		assert_eq!(CallError::Reverted(data).reason(), Some(REASON));
+		test::expect_revert_reason(outcome, REASON);
+	} else {
+		assert_eq!(outcome, Outcome::Trap("trap".into()));
+	}
+}
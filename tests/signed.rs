@@ -0,0 +1,79 @@
+#![cfg(all(feature = "native", feature = "kip6"))]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::{native, signed, Address, U256};
+
+#[test]
+fn unrelated_self_call_cannot_spoof_the_signer() {
+	native::reset();
+	let contract = Address::from([1u8; 20]);
+	let victim = Address::from([0xaa; 20]);
+	native::with_externalities(|ext| ext.register(contract, move || {
+		let input = pwasm_ethereum::input();
+		if input.first() == Some(&1) {
+			// a self-call made by anything but `execute_signed`, appending an arbitrary address
+			let mut forged = vec![2u8];
+			forged.extend_from_slice(&<[u8; 20]>::from(victim));
+			let mut trusted = [0u8; 1];
+			pwasm_ethereum::call(100_000, &contract, U256::zero(), &forged, &mut trusted)
+				.expect("self-call succeeds");
+			pwasm_ethereum::ret(&trusted)
+		}
+		let trusted = signed::signed_sender(&input).is_some();
+		pwasm_ethereum::ret(&[trusted as u8])
+	}));
+
+	let outcome = native::transact(contract, U256::zero(), vec![1]);
+	assert_eq!(outcome, native::Outcome::Return(vec![0]));
+}
+
+#[cfg(feature = "wallet")]
+#[test]
+fn relayed_call_gets_the_signer() {
+	use pwasm_ethereum::{eip712, precompiles, wallet, H256};
+
+	native::reset();
+	let contract = Address::from([2u8; 20]);
+	let domain = eip712::domain_separator("Wallet", "1", 1, &contract);
+	native::with_externalities(|ext| {
+		ext.register(precompiles::address(precompiles::ECRECOVER), || {
+			let input = pwasm_ethereum::input();
+			let word = |offset: usize| {
+				let mut word = [0u8; 32];
+				word.copy_from_slice(&input[offset..offset + 32]);
+				H256::from(word)
+			};
+			let signature = signed::Signature { v: input[63], r: word(64), s: word(96) };
+			let mut result = [0u8; 32];
+			if let Some(signer) = wallet::recover(&word(0), &signature) {
+				result[12..].copy_from_slice(&<[u8; 20]>::from(signer));
+			}
+			pwasm_ethereum::ret(&result)
+		});
+		ext.register(contract, move || {
+			let input = pwasm_ethereum::input();
+			if let Some((signer, call)) = signed::signed_sender(&input) {
+				assert_eq!(call, b"hello");
+				pwasm_ethereum::ret(&<[u8; 20]>::from(signer))
+			}
+			let mut bytes = [0u8; 65];
+			bytes.copy_from_slice(&input);
+			let signature = signed::Signature::from_bytes(&bytes);
+			let mut signer = [0u8; 20];
+			signed::execute_signed(&domain, b"hello", U256::zero(), 100, &signature, &mut signer)
+				.expect("signed call succeeds");
+			pwasm_ethereum::ret(&signer)
+		});
+	});
+
+	let account = wallet::TestAccount::from_index(0);
+	let hash = signed::call_hash(&domain, b"hello", U256::zero(), 100);
+	let signature = account.sign(&hash).to_bytes().to_vec();
+	let outcome = native::transact(contract, U256::zero(), signature.clone());
+	assert_eq!(outcome, native::Outcome::Return(<[u8; 20]>::from(account.address()).to_vec()));
+
+	// the nonce is consumed, so the signature cannot be replayed
+	let outcome = native::transact(contract, U256::zero(), signature);
+	assert!(!outcome.is_success(), "{:?}", outcome);
+}
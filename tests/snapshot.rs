@@ -0,0 +1,71 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use std::env;
+use std::fs;
+use std::panic;
+use std::path::PathBuf;
+
+use pwasm_ethereum::native::{self, GasSchedule, GasSnapshot};
+use pwasm_ethereum::{Address, H256, U256};
+
+fn snapshot_path(name: &str) -> PathBuf {
+	let path = env::temp_dir().join(format!("pwasm-ethereum-{}-{}", name, std::process::id()));
+	let _ = fs::remove_file(&path);
+	path
+}
+
+fn store_call() -> u64 {
+	native::reset();
+	let contract = Address::from([1u8; 20]);
+	native::with_externalities(|ext| {
+		ext.gas_schedule = Some(GasSchedule::frontier());
+		ext.register(contract, || pwasm_ethereum::write(&H256::from([1u8; 32]), &[1u8; 32]));
+	});
+	assert!(native::transact(contract, U256::zero(), Vec::new()).is_success());
+	native::with_externalities(|ext| ext.calls.last().unwrap().gas_used)
+}
+
+#[test]
+fn records_and_reads_back() {
+	let path = snapshot_path("record");
+	let gas = store_call();
+	assert!(gas > 0);
+	let mut snapshot = GasSnapshot::open(&path).unwrap();
+	snapshot.last_call("store");
+	snapshot.check("other call", 21000);
+	snapshot.save().unwrap();
+
+	assert_eq!(fs::read_to_string(&path).unwrap(), format!("other call 21000\nstore {}\n", gas));
+	let snapshot = GasSnapshot::open(&path).unwrap();
+	assert_eq!(snapshot.recorded("store"), Some(gas));
+	assert_eq!(snapshot.recorded("other call"), Some(21000));
+	fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn regression_beyond_tolerance_fails() {
+	let path = snapshot_path("regression");
+	fs::write(&path, "store 1000\n").unwrap();
+
+	let mut snapshot = GasSnapshot::open(&path).unwrap().tolerance(10);
+	snapshot.check("store", 1100);
+	snapshot.check("store", 900);
+	let result = panic::catch_unwind(move || snapshot.check("store", 1101));
+	assert!(result.is_err());
+
+	let mut snapshot = GasSnapshot::open(&path).unwrap().update(true);
+	snapshot.check("store", 2000);
+	snapshot.save().unwrap();
+	assert_eq!(fs::read_to_string(&path).unwrap(), "store 2000\n");
+	fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn malformed_snapshot_is_an_error() {
+	let path = snapshot_path("malformed");
+	fs::write(&path, "store lots\n").unwrap();
+	assert!(GasSnapshot::open(&path).is_err());
+	fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, Change};
+use pwasm_ethereum::{Address, H256, U256};
+
+#[test]
+fn diff_of_a_transaction() {
+	native::reset();
+	let contract = Address::from([1u8; 20]);
+	native::with_externalities(|ext| {
+		ext.set_storage(contract, H256::from([1u8; 32]), [1u8; 32]);
+		ext.set_storage(contract, H256::from([2u8; 32]), [2u8; 32]);
+		ext.register(contract, || {
+			pwasm_ethereum::write(&H256::from([1u8; 32]), &[0u8; 32]);
+			pwasm_ethereum::write(&H256::from([2u8; 32]), &[2u8; 32]);
+			pwasm_ethereum::write(&H256::from([3u8; 32]), &[3u8; 32]);
+		});
+	});
+	let before = native::with_externalities(|ext| ext.dump());
+	assert!(native::transact(contract, U256::zero(), Vec::new()).is_success());
+	let after = native::with_externalities(|ext| ext.dump());
+
+	let diff = native::diff(&before, &after);
+	assert_eq!(diff.changes, vec![
+		Change::Storage { address: contract, key: H256::from([1u8; 32]), before: [1u8; 32], after: [0u8; 32] },
+		Change::Storage { address: contract, key: H256::from([3u8; 32]), before: [0u8; 32], after: [3u8; 32] },
+	]);
+	assert!(native::diff(&after, &after).is_empty());
+	assert_eq!(after.accounts[&contract].storage.len(), 2);
+}
+
+#[test]
+fn dump_leaves_out_empty_accounts() {
+	native::reset();
+	let funded = Address::from([2u8; 20]);
+	native::with_externalities(|ext| {
+		ext.set_balance(funded, U256::from(10));
+		ext.set_balance(Address::from([3u8; 20]), U256::zero());
+		ext.set_storage(Address::from([4u8; 20]), H256::zero(), [0u8; 32]);
+	});
+	let dump = native::with_externalities(|ext| ext.dump());
+	assert_eq!(dump.accounts.keys().collect::<Vec<_>>(), vec![&funded]);
+}
+
+#[test]
+fn json_of_dump_and_diff() {
+	native::reset();
+	let account = Address::from([2u8; 20]);
+	let before = native::with_externalities(|ext| ext.dump());
+	native::with_externalities(|ext| ext.set_balance(account, U256::from(10)));
+	let after = native::with_externalities(|ext| ext.dump());
+
+	let address = format!("0x{}", "02".repeat(20));
+	assert_eq!(
+		after.to_json(),
+		format!("{{\"{}\":{{\"balance\":\"10\",\"code\":\"0x\",\"storage\":{{}}}}}}", address)
+	);
+	let diff = native::diff(&before, &after);
+	assert_eq!(
+		diff.to_json(),
+		format!("[{{\"type\":\"balance\",\"address\":\"{}\",\"before\":\"0\",\"after\":\"10\"}}]", address)
+	);
+	assert_eq!(diff.to_string(), format!("{} balance 0 -> 10\n", address));
+}
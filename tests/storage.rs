@@ -0,0 +1,48 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::{native, Address, CachedStorage, H256, U256};
+
+fn key(n: u8) -> H256 {
+	H256::from([n; 32])
+}
+
+#[test]
+fn session_ret_flushes_before_halting() {
+	native::reset();
+	let contract = Address::from([1u8; 20]);
+	native::with_externalities(|ext| ext.register(contract, || {
+		let mut session = CachedStorage::new();
+		session.write(&key(1), &[1u8; 32]);
+		session.write(&key(2), &[2u8; 32]);
+		session.ret(b"done")
+	}));
+
+	let outcome = native::transact(contract, U256::zero(), Vec::new());
+	assert_eq!(outcome, native::Outcome::Return(b"done".to_vec()));
+	native::with_externalities(|ext| {
+		assert_eq!(ext.storage_at(&contract, &key(1)), [1u8; 32]);
+		assert_eq!(ext.storage_at(&contract, &key(2)), [2u8; 32]);
+	});
+}
+
+#[test]
+fn commit_before_ret_keeps_writes() {
+	native::reset();
+	let contract = Address::from([2u8; 20]);
+	native::with_externalities(|ext| ext.register(contract, || {
+		let mut session = CachedStorage::new();
+		let mut value = session.read(&key(1));
+		value[31] += 1;
+		session.write(&key(1), &value);
+		session.commit();
+		pwasm_ethereum::ret(&value)
+	}));
+
+	for count in 1..3u8 {
+		let outcome = native::transact(contract, U256::zero(), Vec::new());
+		assert!(outcome.is_success(), "{:?}", outcome);
+		native::with_externalities(|ext| assert_eq!(ext.storage_at(&contract, &key(1))[31], count));
+	}
+}
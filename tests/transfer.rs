@@ -0,0 +1,68 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, GasSchedule};
+use pwasm_ethereum::{Address, H256, U256};
+
+const SENDER: [u8; 20] = [1u8; 20];
+const WALLET: [u8; 20] = [2u8; 20];
+const LOGGER: [u8; 20] = [3u8; 20];
+const HOARDER: [u8; 20] = [4u8; 20];
+
+/// `SENDER` holds 100 Wei; `LOGGER` logs what it receives, which the stipend covers, while
+/// `HOARDER` writes storage, which it does not.
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.gas_schedule = Some(GasSchedule::frontier());
+		ext.set_balance(Address::from(SENDER), U256::from(100));
+		ext.frame_mut().address = Address::from(SENDER);
+		ext.register(Address::from(LOGGER), || pwasm_ethereum::log(&[], b"received"));
+		ext.register(Address::from(HOARDER), || pwasm_ethereum::write(&H256::zero(), &[1u8; 32]));
+	});
+}
+
+fn execute<F: FnOnce()>(f: F) {
+	let outcome = native::execute(f);
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+fn balance(address: [u8; 20]) -> U256 {
+	native::with_externalities(|ext| ext.balance(&Address::from(address)))
+}
+
+#[test]
+fn transfers_to_accounts_and_cheap_recipients() {
+	setup();
+	execute(|| {
+		assert!(pwasm_ethereum::transfer(&Address::from(WALLET), U256::from(10)).is_ok());
+		assert!(pwasm_ethereum::send(&Address::from(LOGGER), U256::from(20)));
+	});
+	assert_eq!((balance(SENDER), balance(WALLET), balance(LOGGER)), (U256::from(70), U256::from(10), U256::from(20)));
+	native::with_externalities(|ext| {
+		assert_eq!(ext.logs.len(), 1);
+		assert_eq!(ext.logs[0].address, Address::from(LOGGER));
+	});
+}
+
+#[test]
+fn stipend_does_not_cover_storage_writes() {
+	setup();
+	execute(|| {
+		assert!(pwasm_ethereum::transfer(&Address::from(HOARDER), U256::from(10)).is_err());
+		assert!(!pwasm_ethereum::send(&Address::from(HOARDER), U256::from(10)));
+	});
+	assert_eq!((balance(SENDER), balance(HOARDER)), (U256::from(100), U256::zero()));
+	native::with_externalities(|ext| assert_eq!(ext.storage_at(&Address::from(HOARDER), &H256::zero()), [0u8; 32]));
+}
+
+#[test]
+fn transfers_fail_without_the_balance() {
+	setup();
+	execute(|| {
+		assert!(pwasm_ethereum::transfer(&Address::from(WALLET), U256::from(101)).is_err());
+		assert!(!pwasm_ethereum::send(&Address::from(WALLET), U256::from(101)));
+	});
+	assert_eq!(balance(SENDER), U256::from(100));
+}
@@ -0,0 +1,87 @@
+#![cfg(all(feature = "native", feature = "eip1153"))]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native::{self, Outcome};
+use pwasm_ethereum::{Address, H256, U256};
+
+const CONTRACT: [u8; 20] = [1u8; 20];
+const HELPER: [u8; 20] = [2u8; 20];
+
+fn key() -> H256 {
+	H256::from([0x7a; 32])
+}
+
+fn tread() -> u8 {
+	pwasm_ethereum::tread(&key())[31]
+}
+
+fn twrite(value: u8) {
+	let mut word = [0u8; 32];
+	word[31] = value;
+	pwasm_ethereum::twrite(&key(), &word);
+}
+
+fn call(address: [u8; 20], input: &[u8], result: &mut [u8]) -> bool {
+	pwasm_ethereum::call(100_000, &Address::from(address), U256::zero(), input, result).is_ok()
+}
+
+/// On `[1]`, `CONTRACT` stores 1 and returns what `HELPER` reports: the value `CONTRACT`
+/// reads back on `[2]`, then the value `HELPER` reads itself. On `[3]` it stores 1, lets a
+/// reentrant call storing 2 on `[4]` trap and returns its value. Any other input returns its
+/// value.
+fn setup() {
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.register(Address::from(CONTRACT), || {
+			match pwasm_ethereum::input()[0] {
+				1 => {
+					twrite(1);
+					let mut result = [0u8; 2];
+					assert!(call(HELPER, &[], &mut result));
+					pwasm_ethereum::ret(&result)
+				},
+				3 => {
+					twrite(1);
+					assert!(!call(CONTRACT, &[4], &mut []));
+				},
+				4 => {
+					twrite(2);
+					panic!("discards the write");
+				},
+				_ => {},
+			}
+			pwasm_ethereum::ret(&[tread()])
+		});
+		ext.register(Address::from(HELPER), || {
+			let mut result = [0u8; 1];
+			assert!(call(CONTRACT, &[2], &mut result));
+			pwasm_ethereum::ret(&[result[0], tread()])
+		});
+	});
+}
+
+fn transact(input: u8) -> Outcome {
+	native::transact(Address::from(CONTRACT), U256::zero(), vec![input])
+}
+
+#[test]
+fn transient_storage_is_shared_by_calls_of_a_transaction() {
+	setup();
+	// the helper sees the write of the contract, but has its own transient storage
+	assert_eq!(transact(1), Outcome::Return(vec![1, 0]));
+}
+
+#[test]
+fn transient_storage_is_cleared_after_the_transaction() {
+	setup();
+	assert_eq!(transact(1), Outcome::Return(vec![1, 0]));
+	assert_eq!(transact(2), Outcome::Return(vec![0]));
+	native::with_externalities(|ext| assert_eq!(ext.storage_at(&Address::from(CONTRACT), &key()), [0u8; 32]));
+}
+
+#[test]
+fn failed_calls_discard_their_transient_writes() {
+	setup();
+	assert_eq!(transact(3), Outcome::Return(vec![1]));
+}
@@ -0,0 +1,56 @@
+#![cfg(all(feature = "native", any(feature = "gasprice", feature = "eip1884")))]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::native;
+use pwasm_ethereum::U256;
+#[cfg(feature = "eip1884")]
+use pwasm_ethereum::Address;
+#[cfg(feature = "gasprice")]
+use pwasm_ethereum::test::ExternalsBuilder;
+
+fn execute<F: FnOnce()>(f: F) {
+	let outcome = native::execute(f);
+	assert!(outcome.is_success(), "{:?}", outcome);
+}
+
+#[cfg(feature = "gasprice")]
+#[test]
+fn gas_price_of_the_transaction() {
+	native::reset();
+	native::with_externalities(|ext| ext.gas_price = U256::from(20_000_000_000u64));
+	execute(|| assert_eq!(pwasm_ethereum::gas_price(), U256::from(20_000_000_000u64)));
+
+	let _externals = ExternalsBuilder::new().gas_price(U256::from(3)).build();
+	execute(|| assert_eq!(pwasm_ethereum::gas_price(), U256::from(3)));
+}
+
+#[cfg(feature = "eip1884")]
+#[test]
+fn self_balance_follows_the_executing_contract() {
+	const CONTRACT: [u8; 20] = [1u8; 20];
+	const OTHER: [u8; 20] = [2u8; 20];
+	native::reset();
+	native::with_externalities(|ext| {
+		ext.set_balance(Address::from(CONTRACT), U256::from(10));
+		ext.set_balance(Address::from(OTHER), U256::from(20));
+		ext.register(Address::from(CONTRACT), || {
+			assert_eq!(pwasm_ethereum::self_balance(), pwasm_ethereum::balance(&pwasm_ethereum::address()));
+			let mut word = [0u8; 32];
+			pwasm_ethereum::self_balance().to_big_endian(&mut word);
+			pwasm_ethereum::ret(&word)
+		});
+	});
+	let outcome = native::transact(Address::from(CONTRACT), U256::zero(), Vec::new());
+	assert_eq!(outcome, native::Outcome::Return(word(10)));
+
+	native::with_externalities(|ext| ext.frame_mut().address = Address::from(OTHER));
+	execute(|| assert_eq!(pwasm_ethereum::self_balance(), U256::from(20)));
+}
+
+#[cfg(feature = "eip1884")]
+fn word(value: u64) -> Vec<u8> {
+	let mut word = [0u8; 32];
+	U256::from(value).to_big_endian(&mut word);
+	word.to_vec()
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "native")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::typed_storage::{StorageMap, StorageValue, StorageVec};
+use pwasm_ethereum::{native, Address, H256, U256};
+
+fn key(slot: U256) -> H256 {
+	let mut key = [0u8; 32];
+	slot.to_big_endian(&mut key);
+	H256::from(key)
+}
+
+fn word(value: u64) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	U256::from(value).to_big_endian(&mut word);
+	word
+}
+
+fn stored(slot: U256) -> [u8; 32] {
+	native::with_externalities(|ext| {
+		let address = ext.frame().address;
+		ext.storage_at(&address, &key(slot))
+	})
+}
+
+#[test]
+fn tuples_take_consecutive_slots() {
+	native::reset();
+	let value = StorageValue::<(U256, (Address, bool))>::new(U256::from(5));
+	let outcome = native::execute(|| {
+		value.set(&(U256::from(7), (Address::from([1u8; 20]), true)));
+	});
+	assert!(outcome.is_success());
+	assert_eq!(stored(U256::from(5)), word(7));
+	let mut address = [0u8; 32];
+	address[12..].copy_from_slice(&[1u8; 20]);
+	assert_eq!(stored(U256::from(6)), address);
+	assert_eq!(stored(U256::from(7)), word(1));
+
+	let outcome = native::execute(|| {
+		assert_eq!(value.get(), (U256::from(7), (Address::from([1u8; 20]), true)));
+		value.clear();
+		assert_eq!(value.get(), (U256::zero(), (Address::zero(), false)));
+	});
+	assert!(outcome.is_success());
+}
+
+#[test]
+fn map_entries_use_the_solidity_slot() {
+	native::reset();
+	let map = StorageMap::<U256, u64>::new(U256::zero());
+	// keccak256(uint256(0) . uint256(0)), the slot of `m[0]` for `mapping(uint => uint) m` at slot 0
+	let expected = U256::from_big_endian(&[
+		0xad, 0x32, 0x28, 0xb6, 0x76, 0xf7, 0xd3, 0xcd, 0x42, 0x84, 0xa5, 0x44, 0x3f, 0x17, 0xf1, 0x96,
+		0x2b, 0x36, 0xe4, 0x91, 0xb3, 0x0a, 0x40, 0xb2, 0x40, 0x58, 0x49, 0xe5, 0x97, 0xba, 0x5f, 0xb5,
+	]);
+	assert_eq!(map.slot_of(&U256::zero()), expected);
+
+	let outcome = native::execute(|| {
+		map.set(&U256::zero(), &42);
+		assert_eq!(map.get(&U256::zero()), 42);
+		assert_eq!(map.get(&U256::one()), 0);
+	});
+	assert!(outcome.is_success());
+	assert_eq!(stored(expected), word(42));
+}
+
+#[test]
+fn vec_elements_follow_the_hash_of_the_slot() {
+	native::reset();
+	let vec = StorageVec::<(u64, u64)>::new(U256::zero());
+	// keccak256(uint256(0)), the first element slot of an array at slot 0
+	let first = U256::from_big_endian(&[
+		0x29, 0x0d, 0xec, 0xd9, 0x54, 0x8b, 0x62, 0xa8, 0xd6, 0x03, 0x45, 0xa9, 0x88, 0x38, 0x6f, 0xc8,
+		0x4b, 0xa6, 0xbc, 0x95, 0x48, 0x40, 0x08, 0xf6, 0x36, 0x2f, 0x93, 0x16, 0x0e, 0xf3, 0xe5, 0x63,
+	]);
+
+	let outcome = native::execute(|| {
+		assert!(vec.is_empty());
+		vec.push(&(1, 2));
+		vec.push(&(3, 4));
+		vec.set(0, &(5, 6));
+		assert_eq!(vec.get(2), None);
+	});
+	assert!(outcome.is_success());
+	assert_eq!(stored(U256::zero()), word(2));
+	assert_eq!(stored(first), word(5));
+	assert_eq!(stored(first + U256::from(1)), word(6));
+	assert_eq!(stored(first + U256::from(2)), word(3));
+	assert_eq!(stored(first + U256::from(3)), word(4));
+
+	let outcome = native::execute(|| {
+		assert_eq!(vec.pop(), Some((3, 4)));
+		assert_eq!(vec.len(), 1);
+	});
+	assert!(outcome.is_success());
+	assert_eq!(stored(first + U256::from(2)), [0u8; 32]);
+	assert_eq!(stored(U256::zero()), word(1));
+}
+
+#[test]
+fn vec_set_out_of_bounds_traps() {
+	native::reset();
+	let vec = StorageVec::<u64>::new(U256::zero());
+	let outcome = native::execute(|| vec.set(0, &1));
+	assert_eq!(outcome, native::Outcome::Trap("trap".into()));
+}
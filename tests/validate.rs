@@ -0,0 +1,111 @@
+#![cfg(all(feature = "std", not(feature = "backend-ewasm")))]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::validate::{self, Import, ImportKind, ValidationError};
+
+const FUNCTION: u8 = 0;
+const MEMORY: u8 = 2;
+
+fn push_name(out: &mut Vec<u8>, name: &str) {
+	out.push(name.len() as u8);
+	out.extend_from_slice(name.as_bytes());
+}
+
+/// Module with a type section and an import section holding `imports`.
+fn module(imports: &[(&str, &str, u8)]) -> Vec<u8> {
+	let mut payload = vec![imports.len() as u8];
+	for &(module, field, kind) in imports {
+		push_name(&mut payload, module);
+		push_name(&mut payload, field);
+		payload.push(kind);
+		match kind {
+			FUNCTION => payload.push(0),
+			MEMORY => payload.extend_from_slice(&[1, 1, 16]),
+			_ => unreachable!(),
+		}
+	}
+	let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+	wasm.extend_from_slice(&[1, 4, 1, 0x60, 0, 0]);
+	wasm.push(2);
+	wasm.push(payload.len() as u8);
+	wasm.extend_from_slice(&payload);
+	wasm
+}
+
+#[test]
+fn valid_module() {
+	let wasm = module(&[
+		("env", "storage_read", FUNCTION),
+		("env", "ret", FUNCTION),
+		("env", "panic", FUNCTION),
+		("env", "memory", MEMORY),
+	]);
+	assert_eq!(validate::validate_imports(&wasm), Ok(()));
+	assert_eq!(validate::imports(&wasm).unwrap()[3], Import {
+		module: "env".into(),
+		field: "memory".into(),
+		kind: ImportKind::Memory,
+	});
+}
+
+#[test]
+fn module_without_imports() {
+	assert_eq!(validate::validate_imports(b"\0asm\x01\0\0\0"), Ok(()));
+}
+
+#[test]
+fn truncated_module() {
+	let wasm = module(&[("env", "storage_read", FUNCTION)]);
+	for len in 0..wasm.len() {
+		if len == 8 || len == 14 {
+			// ends between sections, which is valid
+			continue;
+		}
+		assert!(validate::imports(&wasm[..len]).is_err(), "accepted {} bytes", len);
+	}
+}
+
+#[test]
+fn bad_magic_and_version() {
+	let mut wasm = module(&[]);
+	wasm[0] = 1;
+	assert_eq!(validate::imports(&wasm), Err(ValidationError::Malformed("invalid magic")));
+	let mut wasm = module(&[]);
+	wasm[4] = 2;
+	assert_eq!(validate::imports(&wasm), Err(ValidationError::Malformed("unsupported version")));
+}
+
+#[test]
+fn unknown_import_with_suggestion() {
+	let wasm = module(&[("env", "storage_reed", FUNCTION), ("env", "frobnicate", FUNCTION)]);
+	match validate::validate_imports(&wasm) {
+		Err(ValidationError::UnknownImports(unknown)) => {
+			assert_eq!(unknown.len(), 2);
+			assert_eq!(unknown[0].import.field, "storage_reed");
+			assert_eq!(unknown[0].suggestion, Some("storage_read"));
+			assert_eq!(unknown[1].suggestion, None);
+			assert_eq!(
+				unknown[0].to_string(),
+				"unknown import env.storage_reed (Function), did you mean `storage_read`?"
+			);
+		},
+		result => panic!("unexpected {:?}", result),
+	}
+}
+
+#[test]
+fn memory_from_another_module_is_unknown() {
+	let wasm = module(&[("other", "memory", MEMORY)]);
+	assert!(validate::validate_imports(&wasm).is_err());
+}
+
+#[test]
+fn feature_gated_imports() {
+	let gasleft = module(&[("env", "gasleft", FUNCTION)]);
+	assert_eq!(validate::validate_imports(&gasleft).is_ok(), cfg!(feature = "kip6"));
+	let revert = module(&[("env", "revert", FUNCTION)]);
+	assert_eq!(validate::validate_imports(&revert).is_ok(), cfg!(feature = "eip140"));
+	let create2 = module(&[("env", "create2", FUNCTION)]);
+	assert_eq!(validate::validate_imports(&create2).is_ok(), cfg!(feature = "kip4"));
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "wallet")]
+
+extern crate pwasm_ethereum;
+
+use pwasm_ethereum::wallet::{self, Domain, Signature, TestAccount};
+use pwasm_ethereum::{eip712, hash, Address, H256};
+
+fn bytes(hex: &str) -> Vec<u8> {
+	(0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}
+
+fn word(hex: &str) -> H256 {
+	let mut word = [0u8; 32];
+	word.copy_from_slice(&bytes(hex));
+	H256::from(word)
+}
+
+/// Signer of the `Mail` example of EIP-712.
+fn cow() -> TestAccount {
+	TestAccount::from_secret(<[u8; 32]>::from(hash::keccak256(b"cow"))).unwrap()
+}
+
+#[test]
+fn address_of_secret() {
+	let mut address = [0u8; 20];
+	address.copy_from_slice(&bytes("cd2a3d9f938e13cd947ec05abc7fe734df8dd826"));
+	assert_eq!(cow().address(), Address::from(address));
+	assert!(TestAccount::from_secret([0u8; 32]).is_none());
+}
+
+#[test]
+fn signature_of_mail_example() {
+	let hash = word("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2");
+	assert_eq!(cow().sign(&hash), Signature {
+		v: 28,
+		r: word("4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d"),
+		s: word("07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562"),
+	});
+}
+
+#[test]
+fn sign_then_recover() {
+	for account in wallet::accounts(3) {
+		let hash = hash::keccak256(b"message");
+		let signature = account.sign(&hash);
+		assert_eq!(wallet::recover(&hash, &signature), Some(account.address()));
+		assert_ne!(wallet::recover(&hash::keccak256(b"other"), &signature), Some(account.address()));
+		assert_eq!(wallet::recover(&hash, &Signature { v: 29, ..signature }), None);
+	}
+}
+
+#[test]
+fn sign_typed_data() {
+	let account = TestAccount::from_index(0);
+	let domain = Domain {
+		name: "Ether Mail".into(),
+		version: "1".into(),
+		chain_id: 1,
+		verifying_contract: Address::from([0xcc; 20]),
+	};
+	let struct_hash = hash::keccak256(b"struct");
+	let signature = account.sign_typed(&domain.separator(), &struct_hash);
+	let hash = eip712::typed_data_hash(&domain.separator(), &struct_hash);
+	assert_eq!(wallet::recover(&hash, &signature), Some(account.address()));
+}
+
+#[test]
+fn accounts_are_deterministic_and_distinct() {
+	let accounts = wallet::accounts(2);
+	assert_eq!(accounts[0].address(), TestAccount::from_index(0).address());
+	assert_eq!(accounts[0].secret(), TestAccount::from_phrase(wallet::DEFAULT_PHRASE, 0).secret());
+	assert_ne!(accounts[0].address(), accounts[1].address());
+	assert_ne!(TestAccount::from_phrase("other", 0).address(), accounts[0].address());
+}